@@ -0,0 +1,50 @@
+use std::process::Command;
+
+/// Exposes the git revision and rustc version the exporter is built from, for
+/// `lnd_exporter_build_info`. Builds outside a git checkout, e.g. in Docker,
+/// take the revision from `LND_EXPORTER_REVISION`, or report it unknown. Also
+/// generates the OTLP client.
+fn main() {
+    let revision = std::env::var("LND_EXPORTER_REVISION")
+        .ok()
+        .filter(|revision| !revision.is_empty())
+        .or_else(|| command_output("git", &["rev-parse", "--short", "HEAD"]));
+    println!(
+        "cargo:rustc-env=LND_EXPORTER_REVISION={}",
+        revision.as_deref().unwrap_or("unknown")
+    );
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]);
+    println!(
+        "cargo:rustc-env=LND_EXPORTER_RUSTC_VERSION={}",
+        rustc_version
+            .as_deref()
+            .and_then(|version| version.split_whitespace().nth(1))
+            .unwrap_or("unknown")
+    );
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-env-changed=LND_EXPORTER_REVISION");
+
+    // The server side serves as the OTLP receiver of tests
+    tonic_build::configure()
+        .build_server(true)
+        .build_client(true)
+        .compile(&["protos/otlp_metrics.proto"], &["protos"])
+        .expect("compiled OTLP protos");
+    println!("cargo:rerun-if-changed=protos");
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|stdout| stdout.trim().to_string())
+        .filter(|stdout| !stdout.is_empty())
+}
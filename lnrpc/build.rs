@@ -2,8 +2,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure()
         .build_server(false)
         .build_client(true)
+        .type_attribute(
+            ".",
+            "#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]",
+        )
         .out_dir("src/gen")
-        .compile(&["protos/lightning.proto"], &["protos"])?;
+        .compile(
+            &[
+                "protos/lightning.proto",
+                "protos/health.proto",
+                "protos/router.proto",
+                "protos/stateservice.proto",
+                "protos/wtclient.proto",
+                "protos/autopilot.proto",
+            ],
+            &["protos"],
+        )?;
+
+    // Server side for mock lnd nodes in tests, reusing the committed messages
+    if std::env::var_os("CARGO_FEATURE_SERVER").is_some() {
+        tonic_build::configure()
+            .build_server(true)
+            .build_client(false)
+            .extern_path(".lnrpc", "crate")
+            .compile(
+                &["protos/lightning.proto", "protos/stateservice.proto"],
+                &["protos"],
+            )?;
+    }
 
     Ok(())
 }
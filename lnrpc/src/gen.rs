@@ -1 +1,8 @@
+pub mod autopilotrpc;
 pub mod lnrpc;
+pub mod routerrpc;
+pub mod wtclientrpc;
+
+pub mod health {
+    include!("gen/grpc.health.v1.rs");
+}
@@ -1,3 +1,20 @@
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeCustomMessagesRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CustomMessage {
+    /// Peer from which the message originates
+    #[prost(bytes = "vec", tag = "1")]
+    pub peer: ::prost::alloc::vec::Vec<u8>,
+    /// Message type. This value will be in the custom range (>= 32768).
+    #[prost(uint32, tag = "2")]
+    pub r#type: u32,
+    /// Raw message data
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Utxo {
     /// The type of address
@@ -19,6 +36,7 @@ pub struct Utxo {
     #[prost(int64, tag = "6")]
     pub confirmations: i64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Transaction {
     /// The transaction hash
@@ -52,6 +70,7 @@ pub struct Transaction {
     #[prost(string, tag = "10")]
     pub label: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetTransactionsRequest {
     ///
@@ -71,12 +90,14 @@ pub struct GetTransactionsRequest {
     #[prost(string, tag = "3")]
     pub account: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TransactionDetails {
     /// The list of transactions relevant to the wallet.
     #[prost(message, repeated, tag = "1")]
     pub transactions: ::prost::alloc::vec::Vec<Transaction>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FeeLimit {
     #[prost(oneof = "fee_limit::Limit", tags = "1, 3, 2")]
@@ -84,6 +105,7 @@ pub struct FeeLimit {
 }
 /// Nested message and enum types in `FeeLimit`.
 pub mod fee_limit {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Limit {
         ///
@@ -103,6 +125,7 @@ pub mod fee_limit {
         Percent(i64),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendRequest {
     ///
@@ -196,6 +219,7 @@ pub struct SendRequest {
     #[prost(bytes = "vec", tag = "16")]
     pub payment_addr: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendResponse {
     #[prost(string, tag = "1")]
@@ -207,6 +231,7 @@ pub struct SendResponse {
     #[prost(bytes = "vec", tag = "4")]
     pub payment_hash: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendToRouteRequest {
     ///
@@ -224,6 +249,7 @@ pub struct SendToRouteRequest {
     #[prost(message, optional, tag = "4")]
     pub route: ::core::option::Option<Route>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelAcceptRequest {
     /// The pubkey of the node that wishes to open an inbound channel.
@@ -273,6 +299,7 @@ pub struct ChannelAcceptRequest {
     #[prost(uint32, tag = "13")]
     pub channel_flags: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelAcceptResponse {
     /// Whether or not the client accepts the channel.
@@ -326,6 +353,7 @@ pub struct ChannelAcceptResponse {
     #[prost(uint32, tag = "10")]
     pub min_accept_depth: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelPoint {
     /// The index of the output of the funding transaction
@@ -336,6 +364,7 @@ pub struct ChannelPoint {
 }
 /// Nested message and enum types in `ChannelPoint`.
 pub mod channel_point {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum FundingTxid {
         ///
@@ -350,6 +379,7 @@ pub mod channel_point {
         FundingTxidStr(::prost::alloc::string::String),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OutPoint {
     /// Raw bytes representing the transaction id.
@@ -362,6 +392,7 @@ pub struct OutPoint {
     #[prost(uint32, tag = "3")]
     pub output_index: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LightningAddress {
     /// The identity pubkey of the Lightning node
@@ -372,6 +403,7 @@ pub struct LightningAddress {
     #[prost(string, tag = "2")]
     pub host: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EstimateFeeRequest {
     /// The map from addresses to amounts for the transaction.
@@ -389,6 +421,7 @@ pub struct EstimateFeeRequest {
     #[prost(bool, tag = "4")]
     pub spend_unconfirmed: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EstimateFeeResponse {
     /// The total fee in satoshis.
@@ -403,6 +436,7 @@ pub struct EstimateFeeResponse {
     #[prost(uint64, tag = "3")]
     pub sat_per_vbyte: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendManyRequest {
     /// The map from addresses to amounts
@@ -433,12 +467,14 @@ pub struct SendManyRequest {
     #[prost(bool, tag = "8")]
     pub spend_unconfirmed: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendManyResponse {
     /// The id of the transaction
     #[prost(string, tag = "1")]
     pub txid: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendCoinsRequest {
     /// The address to send coins to
@@ -478,12 +514,14 @@ pub struct SendCoinsRequest {
     #[prost(bool, tag = "9")]
     pub spend_unconfirmed: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendCoinsResponse {
     /// The transaction ID of the transaction
     #[prost(string, tag = "1")]
     pub txid: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListUnspentRequest {
     /// The minimum number of confirmations to be included.
@@ -496,12 +534,14 @@ pub struct ListUnspentRequest {
     #[prost(string, tag = "3")]
     pub account: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListUnspentResponse {
     /// A list of utxos
     #[prost(message, repeated, tag = "1")]
     pub utxos: ::prost::alloc::vec::Vec<Utxo>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NewAddressRequest {
     /// The type of address to generate.
@@ -513,12 +553,14 @@ pub struct NewAddressRequest {
     #[prost(string, tag = "2")]
     pub account: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NewAddressResponse {
     /// The newly generated wallet address
     #[prost(string, tag = "1")]
     pub address: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SignMessageRequest {
     ///
@@ -527,12 +569,14 @@ pub struct SignMessageRequest {
     #[prost(bytes = "vec", tag = "1")]
     pub msg: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SignMessageResponse {
     /// The signature for the given message
     #[prost(string, tag = "1")]
     pub signature: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VerifyMessageRequest {
     ///
@@ -544,6 +588,7 @@ pub struct VerifyMessageRequest {
     #[prost(string, tag = "2")]
     pub signature: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VerifyMessageResponse {
     /// Whether the signature was valid over the given message
@@ -553,6 +598,7 @@ pub struct VerifyMessageResponse {
     #[prost(string, tag = "2")]
     pub pubkey: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConnectPeerRequest {
     /// Lightning address of the peer, in the format `<pubkey>@host`
@@ -568,16 +614,20 @@ pub struct ConnectPeerRequest {
     #[prost(uint64, tag = "3")]
     pub timeout: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConnectPeerResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DisconnectPeerRequest {
     /// The pubkey of the node to disconnect from
     #[prost(string, tag = "1")]
     pub pub_key: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DisconnectPeerResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Htlc {
     #[prost(bool, tag = "1")]
@@ -604,6 +654,7 @@ pub struct Htlc {
     #[prost(uint64, tag = "7")]
     pub forwarding_htlc_index: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelConstraints {
     ///
@@ -628,6 +679,7 @@ pub struct ChannelConstraints {
     #[prost(uint32, tag = "6")]
     pub max_accepted_htlcs: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Channel {
     /// Whether this channel is active or not
@@ -770,7 +822,19 @@ pub struct Channel {
     /// List constraints for the remote node.
     #[prost(message, optional, tag = "30")]
     pub remote_constraints: ::core::option::Option<ChannelConstraints>,
+    ///
+    ///This lists out the set of alias short channel ids that exist for a channel.
+    ///This may be empty.
+    #[prost(uint64, repeated, tag = "31")]
+    pub alias_scids: ::prost::alloc::vec::Vec<u64>,
+    /// Whether or not this is a zero-conf channel.
+    #[prost(bool, tag = "32")]
+    pub zero_conf: bool,
+    /// This is the confirmed / on-chain zero-conf SCID.
+    #[prost(uint64, tag = "33")]
+    pub zero_conf_confirmed_scid: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListChannelsRequest {
     #[prost(bool, tag = "1")]
@@ -787,12 +851,14 @@ pub struct ListChannelsRequest {
     #[prost(bytes = "vec", tag = "5")]
     pub peer: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListChannelsResponse {
     /// The list of active channels
     #[prost(message, repeated, tag = "11")]
     pub channels: ::prost::alloc::vec::Vec<Channel>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelCloseSummary {
     /// The outpoint (txid:index) of the funding transaction.
@@ -844,6 +910,7 @@ pub struct ChannelCloseSummary {
 }
 /// Nested message and enum types in `ChannelCloseSummary`.
 pub mod channel_close_summary {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum ClosureType {
@@ -855,6 +922,7 @@ pub mod channel_close_summary {
         Abandoned = 5,
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Resolution {
     /// The type of output we are resolving.
@@ -874,6 +942,7 @@ pub struct Resolution {
     #[prost(string, tag = "5")]
     pub sweep_txid: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClosedChannelsRequest {
     #[prost(bool, tag = "1")]
@@ -889,11 +958,13 @@ pub struct ClosedChannelsRequest {
     #[prost(bool, tag = "6")]
     pub abandoned: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClosedChannelsResponse {
     #[prost(message, repeated, tag = "1")]
     pub channels: ::prost::alloc::vec::Vec<ChannelCloseSummary>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Peer {
     /// The identity pubkey of the peer
@@ -953,6 +1024,7 @@ pub struct Peer {
 }
 /// Nested message and enum types in `Peer`.
 pub mod peer {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum SyncType {
@@ -970,6 +1042,7 @@ pub mod peer {
         PinnedSync = 3,
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TimestampedError {
     /// The unix timestamp in seconds when the error occurred.
@@ -979,6 +1052,7 @@ pub struct TimestampedError {
     #[prost(string, tag = "2")]
     pub error: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListPeersRequest {
     ///
@@ -988,14 +1062,17 @@ pub struct ListPeersRequest {
     #[prost(bool, tag = "1")]
     pub latest_error: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListPeersResponse {
     /// The list of currently connected peers
     #[prost(message, repeated, tag = "1")]
     pub peers: ::prost::alloc::vec::Vec<Peer>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PeerEventSubscription {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PeerEvent {
     /// The identity pubkey of the peer.
@@ -1006,6 +1083,7 @@ pub struct PeerEvent {
 }
 /// Nested message and enum types in `PeerEvent`.
 pub mod peer_event {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum EventType {
@@ -1013,8 +1091,10 @@ pub mod peer_event {
         PeerOffline = 1,
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetInfoRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetInfoResponse {
     /// The version of the LND software that the node is running.
@@ -1077,8 +1157,10 @@ pub struct GetInfoResponse {
     #[prost(map = "uint32, message", tag = "19")]
     pub features: ::std::collections::HashMap<u32, Feature>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetRecoveryInfoRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetRecoveryInfoResponse {
     /// Whether the wallet is in recovery mode
@@ -1091,6 +1173,7 @@ pub struct GetRecoveryInfoResponse {
     #[prost(double, tag = "3")]
     pub progress: f64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Chain {
     /// The blockchain the node is on (eg bitcoin, litecoin)
@@ -1100,6 +1183,7 @@ pub struct Chain {
     #[prost(string, tag = "2")]
     pub network: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConfirmationUpdate {
     #[prost(bytes = "vec", tag = "1")]
@@ -1109,11 +1193,13 @@ pub struct ConfirmationUpdate {
     #[prost(uint32, tag = "3")]
     pub num_confs_left: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelOpenUpdate {
     #[prost(message, optional, tag = "1")]
     pub channel_point: ::core::option::Option<ChannelPoint>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelCloseUpdate {
     #[prost(bytes = "vec", tag = "1")]
@@ -1121,6 +1207,7 @@ pub struct ChannelCloseUpdate {
     #[prost(bool, tag = "2")]
     pub success: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CloseChannelRequest {
     ///
@@ -1155,6 +1242,7 @@ pub struct CloseChannelRequest {
     #[prost(uint64, tag = "6")]
     pub sat_per_vbyte: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CloseStatusUpdate {
     #[prost(oneof = "close_status_update::Update", tags = "1, 3")]
@@ -1162,6 +1250,7 @@ pub struct CloseStatusUpdate {
 }
 /// Nested message and enum types in `CloseStatusUpdate`.
 pub mod close_status_update {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Update {
         #[prost(message, tag = "1")]
@@ -1170,6 +1259,7 @@ pub mod close_status_update {
         ChanClose(super::ChannelCloseUpdate),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PendingUpdate {
     #[prost(bytes = "vec", tag = "1")]
@@ -1177,6 +1267,7 @@ pub struct PendingUpdate {
     #[prost(uint32, tag = "2")]
     pub output_index: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ReadyForPsbtFunding {
     ///
@@ -1197,6 +1288,7 @@ pub struct ReadyForPsbtFunding {
     #[prost(bytes = "vec", tag = "3")]
     pub psbt: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BatchOpenChannelRequest {
     /// The list of channels to open.
@@ -1222,6 +1314,7 @@ pub struct BatchOpenChannelRequest {
     #[prost(string, tag = "6")]
     pub label: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BatchOpenChannel {
     /// The pubkey of the node to open a channel with. When using REST, this
@@ -1270,11 +1363,13 @@ pub struct BatchOpenChannel {
     #[prost(enumeration = "CommitmentType", tag = "9")]
     pub commitment_type: i32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BatchOpenChannelResponse {
     #[prost(message, repeated, tag = "1")]
     pub pending_channels: ::prost::alloc::vec::Vec<PendingUpdate>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpenChannelRequest {
     /// A manual fee rate set in sat/vbyte that should be used when crafting the
@@ -1369,6 +1464,7 @@ pub struct OpenChannelRequest {
     #[prost(enumeration = "CommitmentType", tag = "18")]
     pub commitment_type: i32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpenStatusUpdate {
     ///
@@ -1381,6 +1477,7 @@ pub struct OpenStatusUpdate {
 }
 /// Nested message and enum types in `OpenStatusUpdate`.
 pub mod open_status_update {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Update {
         ///
@@ -1400,6 +1497,7 @@ pub mod open_status_update {
         PsbtFund(super::ReadyForPsbtFunding),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct KeyLocator {
     /// The family of key being identified.
@@ -1409,6 +1507,7 @@ pub struct KeyLocator {
     #[prost(int32, tag = "2")]
     pub key_index: i32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct KeyDescriptor {
     ///
@@ -1420,6 +1519,7 @@ pub struct KeyDescriptor {
     #[prost(message, optional, tag = "2")]
     pub key_loc: ::core::option::Option<KeyLocator>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChanPointShim {
     ///
@@ -1452,6 +1552,7 @@ pub struct ChanPointShim {
     #[prost(uint32, tag = "6")]
     pub thaw_height: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PsbtShim {
     ///
@@ -1475,6 +1576,7 @@ pub struct PsbtShim {
     #[prost(bool, tag = "3")]
     pub no_publish: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FundingShim {
     #[prost(oneof = "funding_shim::Shim", tags = "1, 2")]
@@ -1482,6 +1584,7 @@ pub struct FundingShim {
 }
 /// Nested message and enum types in `FundingShim`.
 pub mod funding_shim {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Shim {
         ///
@@ -1496,12 +1599,14 @@ pub mod funding_shim {
         PsbtShim(super::PsbtShim),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FundingShimCancel {
     /// The pending channel ID of the channel to cancel the funding shim for.
     #[prost(bytes = "vec", tag = "1")]
     pub pending_chan_id: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FundingPsbtVerify {
     ///
@@ -1514,6 +1619,7 @@ pub struct FundingPsbtVerify {
     #[prost(bytes = "vec", tag = "2")]
     pub pending_chan_id: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FundingPsbtFinalize {
     ///
@@ -1532,6 +1638,7 @@ pub struct FundingPsbtFinalize {
     #[prost(bytes = "vec", tag = "3")]
     pub final_raw_tx: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FundingTransitionMsg {
     #[prost(oneof = "funding_transition_msg::Trigger", tags = "1, 2, 3, 4")]
@@ -1539,6 +1646,7 @@ pub struct FundingTransitionMsg {
 }
 /// Nested message and enum types in `FundingTransitionMsg`.
 pub mod funding_transition_msg {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Trigger {
         ///
@@ -1565,8 +1673,10 @@ pub mod funding_transition_msg {
         PsbtFinalize(super::FundingPsbtFinalize),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FundingStateStepResp {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PendingHtlc {
     /// The direction within the channel that the htlc was sent
@@ -1591,8 +1701,10 @@ pub struct PendingHtlc {
     #[prost(uint32, tag = "6")]
     pub stage: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PendingChannelsRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PendingChannelsResponse {
     /// The balance in satoshis encumbered in pending channels
@@ -1621,6 +1733,7 @@ pub struct PendingChannelsResponse {
 }
 /// Nested message and enum types in `PendingChannelsResponse`.
 pub mod pending_channels_response {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct PendingChannel {
         #[prost(string, tag = "1")]
@@ -1649,6 +1762,7 @@ pub mod pending_channels_response {
         #[prost(enumeration = "super::CommitmentType", tag = "9")]
         pub commitment_type: i32,
     }
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct PendingOpenChannel {
         /// The pending channel
@@ -1675,6 +1789,7 @@ pub mod pending_channels_response {
         #[prost(int64, tag = "6")]
         pub fee_per_kw: i64,
     }
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct WaitingCloseChannel {
         /// The pending channel waiting for closing tx to confirm
@@ -1689,6 +1804,7 @@ pub mod pending_channels_response {
         #[prost(message, optional, tag = "3")]
         pub commitments: ::core::option::Option<Commitments>,
     }
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Commitments {
         /// Hash of the local version of the commitment tx.
@@ -1716,6 +1832,7 @@ pub mod pending_channels_response {
         #[prost(uint64, tag = "6")]
         pub remote_pending_commit_fee_sat: u64,
     }
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct ClosedChannel {
         /// The pending channel to be closed
@@ -1725,6 +1842,7 @@ pub mod pending_channels_response {
         #[prost(string, tag = "2")]
         pub closing_txid: ::prost::alloc::string::String,
     }
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct ForceClosedChannel {
         /// The pending channel to be force closed
@@ -1755,6 +1873,7 @@ pub mod pending_channels_response {
     }
     /// Nested message and enum types in `ForceClosedChannel`.
     pub mod force_closed_channel {
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[derive(
             Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration,
         )]
@@ -1766,8 +1885,10 @@ pub mod pending_channels_response {
         }
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelEventSubscription {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelEventUpdate {
     #[prost(enumeration = "channel_event_update::UpdateType", tag = "5")]
@@ -1777,6 +1898,7 @@ pub struct ChannelEventUpdate {
 }
 /// Nested message and enum types in `ChannelEventUpdate`.
 pub mod channel_event_update {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum UpdateType {
@@ -1787,6 +1909,7 @@ pub mod channel_event_update {
         PendingOpenChannel = 4,
         FullyResolvedChannel = 5,
     }
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Channel {
         #[prost(message, tag = "1")]
@@ -1803,6 +1926,7 @@ pub mod channel_event_update {
         FullyResolvedChannel(super::ChannelPoint),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WalletAccountBalance {
     /// The confirmed balance of the account (with >= 1 confirmations).
@@ -1812,8 +1936,10 @@ pub struct WalletAccountBalance {
     #[prost(int64, tag = "2")]
     pub unconfirmed_balance: i64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WalletBalanceRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WalletBalanceResponse {
     /// The balance of the wallet
@@ -1830,6 +1956,7 @@ pub struct WalletBalanceResponse {
     pub account_balance:
         ::std::collections::HashMap<::prost::alloc::string::String, WalletAccountBalance>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Amount {
     /// Value denominated in satoshis.
@@ -1839,8 +1966,10 @@ pub struct Amount {
     #[prost(uint64, tag = "2")]
     pub msat: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelBalanceRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelBalanceResponse {
     /// Deprecated. Sum of channels balances denominated in satoshis
@@ -1870,6 +1999,7 @@ pub struct ChannelBalanceResponse {
     #[prost(message, optional, tag = "8")]
     pub pending_open_remote_balance: ::core::option::Option<Amount>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct QueryRoutesRequest {
     /// The 33-byte hex-encoded public key for the payment destination
@@ -1963,6 +2093,7 @@ pub struct QueryRoutesRequest {
     #[prost(enumeration = "FeatureBit", repeated, tag = "17")]
     pub dest_features: ::prost::alloc::vec::Vec<i32>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NodePair {
     ///
@@ -1976,6 +2107,7 @@ pub struct NodePair {
     #[prost(bytes = "vec", tag = "2")]
     pub to: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EdgeLocator {
     /// The short channel id of this edge.
@@ -1989,6 +2121,7 @@ pub struct EdgeLocator {
     #[prost(bool, tag = "2")]
     pub direction_reverse: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct QueryRoutesResponse {
     ///
@@ -2002,6 +2135,7 @@ pub struct QueryRoutesResponse {
     #[prost(double, tag = "2")]
     pub success_prob: f64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Hop {
     ///
@@ -2059,6 +2193,7 @@ pub struct Hop {
     #[prost(map = "uint64, bytes", tag = "11")]
     pub custom_records: ::std::collections::HashMap<u64, ::prost::alloc::vec::Vec<u8>>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MppRecord {
     ///
@@ -2076,6 +2211,7 @@ pub struct MppRecord {
     #[prost(int64, tag = "10")]
     pub total_amt_msat: i64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AmpRecord {
     #[prost(bytes = "vec", tag = "1")]
@@ -2091,6 +2227,7 @@ pub struct AmpRecord {
 ///Sphinx onion packet, and send the payment along the first hop in the path. A
 ///route is only selected as valid if all the channels have sufficient capacity to
 ///carry the initial payment amount after fees are accounted for.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Route {
     ///
@@ -2129,6 +2266,7 @@ pub struct Route {
     #[prost(int64, tag = "6")]
     pub total_amt_msat: i64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NodeInfoRequest {
     /// The 33-byte hex-encoded compressed public of the target node
@@ -2138,6 +2276,7 @@ pub struct NodeInfoRequest {
     #[prost(bool, tag = "2")]
     pub include_channels: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NodeInfo {
     ///
@@ -2162,6 +2301,7 @@ pub struct NodeInfo {
 ///connected to other nodes by one or more channel edges emanating from it. As the
 ///graph is directed, a node will also have an incoming edge attached to it for
 ///each outgoing edge.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LightningNode {
     #[prost(uint32, tag = "1")]
@@ -2177,6 +2317,7 @@ pub struct LightningNode {
     #[prost(map = "uint32, message", tag = "6")]
     pub features: ::std::collections::HashMap<u32, Feature>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NodeAddress {
     #[prost(string, tag = "1")]
@@ -2184,6 +2325,7 @@ pub struct NodeAddress {
     #[prost(string, tag = "2")]
     pub addr: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RoutingPolicy {
     #[prost(uint32, tag = "1")]
@@ -2207,6 +2349,7 @@ pub struct RoutingPolicy {
 ///then an instance of ChannelEdgeInfo encapsulating the channels attributes is
 ///stored. The other portions relevant to routing policy of a channel are stored
 ///within a ChannelEdgePolicy for each direction of the channel.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelEdge {
     ///
@@ -2231,6 +2374,7 @@ pub struct ChannelEdge {
     #[prost(message, optional, tag = "8")]
     pub node2_policy: ::core::option::Option<RoutingPolicy>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelGraphRequest {
     ///
@@ -2241,6 +2385,7 @@ pub struct ChannelGraphRequest {
     pub include_unannounced: bool,
 }
 /// Returns a new instance of the directed channel graph.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelGraph {
     /// The list of `LightningNode`s in this channel graph
@@ -2250,12 +2395,14 @@ pub struct ChannelGraph {
     #[prost(message, repeated, tag = "2")]
     pub edges: ::prost::alloc::vec::Vec<ChannelEdge>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NodeMetricsRequest {
     /// The requested node metrics.
     #[prost(enumeration = "NodeMetricType", repeated, tag = "1")]
     pub types: ::prost::alloc::vec::Vec<i32>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NodeMetricsResponse {
     ///
@@ -2268,6 +2415,7 @@ pub struct NodeMetricsResponse {
     pub betweenness_centrality:
         ::std::collections::HashMap<::prost::alloc::string::String, FloatMetric>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FloatMetric {
     /// Arbitrary float value.
@@ -2277,6 +2425,7 @@ pub struct FloatMetric {
     #[prost(double, tag = "2")]
     pub normalized_value: f64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChanInfoRequest {
     ///
@@ -2286,8 +2435,10 @@ pub struct ChanInfoRequest {
     #[prost(uint64, tag = "1")]
     pub chan_id: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NetworkInfoRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NetworkInfo {
     #[prost(uint32, tag = "1")]
@@ -2314,12 +2465,16 @@ pub struct NetworkInfo {
     #[prost(uint64, tag = "11")]
     pub num_zombie_chans: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StopRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StopResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTopologySubscription {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GraphTopologyUpdate {
     #[prost(message, repeated, tag = "1")]
@@ -2329,6 +2484,7 @@ pub struct GraphTopologyUpdate {
     #[prost(message, repeated, tag = "3")]
     pub closed_chans: ::prost::alloc::vec::Vec<ClosedChannelUpdate>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NodeUpdate {
     ///
@@ -2355,6 +2511,7 @@ pub struct NodeUpdate {
     #[prost(map = "uint32, message", tag = "6")]
     pub features: ::std::collections::HashMap<u32, Feature>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelEdgeUpdate {
     ///
@@ -2374,6 +2531,7 @@ pub struct ChannelEdgeUpdate {
     #[prost(string, tag = "6")]
     pub connecting_node: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClosedChannelUpdate {
     ///
@@ -2389,6 +2547,7 @@ pub struct ClosedChannelUpdate {
     #[prost(message, optional, tag = "4")]
     pub chan_point: ::core::option::Option<ChannelPoint>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HopHint {
     /// The public key of the node at the start of the channel.
@@ -2409,6 +2568,7 @@ pub struct HopHint {
     #[prost(uint32, tag = "5")]
     pub cltv_expiry_delta: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RouteHint {
     ///
@@ -2417,6 +2577,7 @@ pub struct RouteHint {
     #[prost(message, repeated, tag = "1")]
     pub hop_hints: ::prost::alloc::vec::Vec<HopHint>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Invoice {
     ///
@@ -2553,6 +2714,7 @@ pub struct Invoice {
 }
 /// Nested message and enum types in `Invoice`.
 pub mod invoice {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum InvoiceState {
@@ -2563,6 +2725,7 @@ pub mod invoice {
     }
 }
 /// Details of an HTLC that paid to an invoice
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct InvoiceHtlc {
     /// Short channel id over which the htlc was received.
@@ -2600,6 +2763,7 @@ pub struct InvoiceHtlc {
     pub amp: ::core::option::Option<Amp>,
 }
 /// Details specific to AMP HTLCs.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Amp {
     /// An n-of-n secret share of the root seed from which child payment hashes
@@ -2622,6 +2786,7 @@ pub struct Amp {
     #[prost(bytes = "vec", tag = "5")]
     pub preimage: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AddInvoiceResponse {
     #[prost(bytes = "vec", tag = "1")]
@@ -2646,6 +2811,7 @@ pub struct AddInvoiceResponse {
     #[prost(bytes = "vec", tag = "17")]
     pub payment_addr: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PaymentHash {
     ///
@@ -2662,6 +2828,7 @@ pub struct PaymentHash {
     #[prost(bytes = "vec", tag = "2")]
     pub r_hash: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListInvoiceRequest {
     ///
@@ -2683,6 +2850,7 @@ pub struct ListInvoiceRequest {
     #[prost(bool, tag = "6")]
     pub reversed: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListInvoiceResponse {
     ///
@@ -2701,6 +2869,7 @@ pub struct ListInvoiceResponse {
     #[prost(uint64, tag = "3")]
     pub first_index_offset: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct InvoiceSubscription {
     ///
@@ -2718,6 +2887,7 @@ pub struct InvoiceSubscription {
     #[prost(uint64, tag = "2")]
     pub settle_index: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Payment {
     /// The payment hash
@@ -2773,6 +2943,7 @@ pub struct Payment {
 }
 /// Nested message and enum types in `Payment`.
 pub mod payment {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum PaymentStatus {
@@ -2782,6 +2953,7 @@ pub mod payment {
         Failed = 3,
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HtlcAttempt {
     /// The unique ID that is used for this attempt.
@@ -2810,6 +2982,7 @@ pub struct HtlcAttempt {
 }
 /// Nested message and enum types in `HTLCAttempt`.
 pub mod htlc_attempt {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum HtlcStatus {
@@ -2818,6 +2991,7 @@ pub mod htlc_attempt {
         Failed = 2,
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListPaymentsRequest {
     ///
@@ -2845,6 +3019,7 @@ pub struct ListPaymentsRequest {
     #[prost(bool, tag = "4")]
     pub reversed: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListPaymentsResponse {
     /// The list of payments
@@ -2861,6 +3036,7 @@ pub struct ListPaymentsResponse {
     #[prost(uint64, tag = "3")]
     pub last_index_offset: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeletePaymentRequest {
     /// Payment hash to delete.
@@ -2871,6 +3047,7 @@ pub struct DeletePaymentRequest {
     #[prost(bool, tag = "2")]
     pub failed_htlcs_only: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteAllPaymentsRequest {
     /// Only delete failed payments.
@@ -2881,10 +3058,13 @@ pub struct DeleteAllPaymentsRequest {
     #[prost(bool, tag = "2")]
     pub failed_htlcs_only: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeletePaymentResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteAllPaymentsResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AbandonChannelRequest {
     #[prost(message, optional, tag = "1")]
@@ -2898,8 +3078,10 @@ pub struct AbandonChannelRequest {
     #[prost(bool, tag = "3")]
     pub i_know_what_i_am_doing: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AbandonChannelResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DebugLevelRequest {
     #[prost(bool, tag = "1")]
@@ -2907,17 +3089,20 @@ pub struct DebugLevelRequest {
     #[prost(string, tag = "2")]
     pub level_spec: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DebugLevelResponse {
     #[prost(string, tag = "1")]
     pub sub_systems: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PayReqString {
     /// The payment request string to be decoded
     #[prost(string, tag = "1")]
     pub pay_req: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PayReq {
     #[prost(string, tag = "1")]
@@ -2947,6 +3132,7 @@ pub struct PayReq {
     #[prost(map = "uint32, message", tag = "13")]
     pub features: ::std::collections::HashMap<u32, Feature>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Feature {
     #[prost(string, tag = "2")]
@@ -2956,8 +3142,10 @@ pub struct Feature {
     #[prost(bool, tag = "4")]
     pub is_known: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FeeReportRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelFeeReport {
     /// The short channel id that this fee report belongs to.
@@ -2978,6 +3166,7 @@ pub struct ChannelFeeReport {
     #[prost(double, tag = "4")]
     pub fee_rate: f64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FeeReportResponse {
     /// An array of channel fee reports which describes the current fee schedule
@@ -2997,6 +3186,7 @@ pub struct FeeReportResponse {
     #[prost(uint64, tag = "4")]
     pub month_fee_sum: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PolicyUpdateRequest {
     /// The base fee charged regardless of the number of milli-satoshis sent.
@@ -3025,6 +3215,7 @@ pub struct PolicyUpdateRequest {
 }
 /// Nested message and enum types in `PolicyUpdateRequest`.
 pub mod policy_update_request {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Scope {
         /// If set, then this update applies to all currently active channels.
@@ -3035,8 +3226,10 @@ pub mod policy_update_request {
         ChanPoint(super::ChannelPoint),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PolicyUpdateResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ForwardingHistoryRequest {
     /// Start time is the starting point of the forwarding history request. All
@@ -3058,6 +3251,7 @@ pub struct ForwardingHistoryRequest {
     #[prost(uint32, tag = "4")]
     pub num_max_events: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ForwardingEvent {
     /// Timestamp is the time (unix epoch offset) that this circuit was
@@ -3099,6 +3293,7 @@ pub struct ForwardingEvent {
     #[prost(uint64, tag = "11")]
     pub timestamp_ns: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ForwardingHistoryResponse {
     /// A list of forwarding events from the time slice of the time series
@@ -3110,12 +3305,14 @@ pub struct ForwardingHistoryResponse {
     #[prost(uint32, tag = "2")]
     pub last_offset_index: u32,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ExportChannelBackupRequest {
     /// The target channel point to obtain a back up for.
     #[prost(message, optional, tag = "1")]
     pub chan_point: ::core::option::Option<ChannelPoint>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelBackup {
     ///
@@ -3130,6 +3327,7 @@ pub struct ChannelBackup {
     #[prost(bytes = "vec", tag = "2")]
     pub chan_backup: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MultiChanBackup {
     ///
@@ -3144,8 +3342,10 @@ pub struct MultiChanBackup {
     #[prost(bytes = "vec", tag = "2")]
     pub multi_chan_backup: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChanBackupExportRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChanBackupSnapshot {
     ///
@@ -3159,6 +3359,7 @@ pub struct ChanBackupSnapshot {
     #[prost(message, optional, tag = "2")]
     pub multi_chan_backup: ::core::option::Option<MultiChanBackup>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelBackups {
     ///
@@ -3166,6 +3367,7 @@ pub struct ChannelBackups {
     #[prost(message, repeated, tag = "1")]
     pub chan_backups: ::prost::alloc::vec::Vec<ChannelBackup>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RestoreChanBackupRequest {
     #[prost(oneof = "restore_chan_backup_request::Backup", tags = "1, 2")]
@@ -3173,6 +3375,7 @@ pub struct RestoreChanBackupRequest {
 }
 /// Nested message and enum types in `RestoreChanBackupRequest`.
 pub mod restore_chan_backup_request {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Backup {
         ///
@@ -3186,12 +3389,16 @@ pub mod restore_chan_backup_request {
         MultiChanBackup(::prost::alloc::vec::Vec<u8>),
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RestoreBackupResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelBackupSubscription {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VerifyChanBackupResponse {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MacaroonPermission {
     /// The entity a permission grants access to.
@@ -3201,6 +3408,7 @@ pub struct MacaroonPermission {
     #[prost(string, tag = "2")]
     pub action: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BakeMacaroonRequest {
     /// The list of permissions the new macaroon should grant.
@@ -3215,40 +3423,48 @@ pub struct BakeMacaroonRequest {
     #[prost(bool, tag = "3")]
     pub allow_external_permissions: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BakeMacaroonResponse {
     /// The hex encoded macaroon, serialized in binary format.
     #[prost(string, tag = "1")]
     pub macaroon: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListMacaroonIDsRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListMacaroonIDsResponse {
     /// The list of root key IDs that are in use.
     #[prost(uint64, repeated, tag = "1")]
     pub root_key_ids: ::prost::alloc::vec::Vec<u64>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteMacaroonIdRequest {
     /// The root key ID to be removed.
     #[prost(uint64, tag = "1")]
     pub root_key_id: u64,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteMacaroonIdResponse {
     /// A boolean indicates that the deletion is successful.
     #[prost(bool, tag = "1")]
     pub deleted: bool,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MacaroonPermissionList {
     /// A list of macaroon permissions.
     #[prost(message, repeated, tag = "1")]
     pub permissions: ::prost::alloc::vec::Vec<MacaroonPermission>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListPermissionsRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListPermissionsResponse {
     ///
@@ -3258,6 +3474,7 @@ pub struct ListPermissionsResponse {
     pub method_permissions:
         ::std::collections::HashMap<::prost::alloc::string::String, MacaroonPermissionList>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Failure {
     /// Failure code as defined in the Lightning spec
@@ -3289,6 +3506,7 @@ pub struct Failure {
 }
 /// Nested message and enum types in `Failure`.
 pub mod failure {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
     #[repr(i32)]
     pub enum FailureCode {
@@ -3333,6 +3551,7 @@ pub mod failure {
         UnreadableFailure = 999,
     }
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelUpdate {
     ///
@@ -3405,6 +3624,7 @@ pub struct ChannelUpdate {
     #[prost(bytes = "vec", tag = "12")]
     pub extra_opaque_data: ::prost::alloc::vec::Vec<u8>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MacaroonId {
     #[prost(bytes = "vec", tag = "1")]
@@ -3414,6 +3634,7 @@ pub struct MacaroonId {
     #[prost(message, repeated, tag = "3")]
     pub ops: ::prost::alloc::vec::Vec<Op>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Op {
     #[prost(string, tag = "1")]
@@ -3421,6 +3642,7 @@ pub struct Op {
     #[prost(string, repeated, tag = "2")]
     pub actions: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CheckMacPermRequest {
     #[prost(bytes = "vec", tag = "1")]
@@ -3430,6 +3652,7 @@ pub struct CheckMacPermRequest {
     #[prost(string, tag = "3")]
     pub full_method: ::prost::alloc::string::String,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CheckMacPermResponse {
     #[prost(bool, tag = "1")]
@@ -3440,6 +3663,7 @@ pub struct CheckMacPermResponse {
 ///
 ///- `p2wkh`: Pay to witness key hash (`WITNESS_PUBKEY_HASH` = 0)
 ///- `np2wkh`: Pay to nested witness key hash (`NESTED_PUBKEY_HASH` = 1)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum AddressType {
@@ -3448,6 +3672,7 @@ pub enum AddressType {
     UnusedWitnessPubkeyHash = 2,
     UnusedNestedPubkeyHash = 3,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum CommitmentType {
@@ -3470,6 +3695,7 @@ pub enum CommitmentType {
     ///been broadcast.
     Anchors = 3,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum Initiator {
@@ -3478,6 +3704,7 @@ pub enum Initiator {
     Remote = 2,
     Both = 3,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum ResolutionType {
@@ -3497,6 +3724,7 @@ pub enum ResolutionType {
     /// We force closed and need to sweep our time locked commitment output.
     Commit = 4,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum ResolutionOutcome {
@@ -3520,12 +3748,14 @@ pub enum ResolutionOutcome {
     /// A htlc was timed out on chain.
     Timeout = 5,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum NodeMetricType {
     Unknown = 0,
     BetweennessCentrality = 1,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum InvoiceHtlcState {
@@ -3533,6 +3763,7 @@ pub enum InvoiceHtlcState {
     Settled = 1,
     Canceled = 2,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum PaymentFailureReason {
@@ -3557,6 +3788,7 @@ pub enum PaymentFailureReason {
     ///Insufficient local balance.
     FailureReasonInsufficientBalance = 5,
 }
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum FeatureBit {
@@ -4870,5 +5102,157 @@ pub mod lightning_client {
                 http::uri::PathAndQuery::from_static("/lnrpc.Lightning/CheckMacaroonPermissions");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        #[doc = ""]
+        #[doc = "SubscribeCustomMessages subscribes to a stream of incoming custom peer"]
+        #[doc = "messages."]
+        pub async fn subscribe_custom_messages(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubscribeCustomMessagesRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::CustomMessage>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/lnrpc.Lightning/SubscribeCustomMessages");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+    }
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeStateRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeStateResponse {
+    #[prost(enumeration = "WalletState", tag = "1")]
+    pub state: i32,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStateRequest {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStateResponse {
+    #[prost(enumeration = "WalletState", tag = "1")]
+    pub state: i32,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WalletState {
+    NonExisting = 0,
+    Locked = 1,
+    Unlocked = 2,
+    RpcActive = 3,
+    /// SERVER_ACTIVE means that the lnd server is ready to accept calls.
+    ServerActive = 4,
+    WaitingToStart = 255,
+}
+#[doc = r" Generated client implementations."]
+pub mod state_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    #[doc = " State service is a always running service that exposes the current state of"]
+    #[doc = " the wallet and RPC server."]
+    #[derive(Debug, Clone)]
+    pub struct StateClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl StateClient<tonic::transport::Channel> {
+        #[doc = r" Attempt to create a new client by connecting to a given endpoint."]
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> StateClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        T::Error: Into<StdError>,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> StateClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + Send + Sync,
+        {
+            StateClient::new(InterceptedService::new(inner, interceptor))
+        }
+        #[doc = r" Compress requests with `gzip`."]
+        #[doc = r""]
+        #[doc = r" This requires the server to support it otherwise it might respond with an"]
+        #[doc = r" error."]
+        pub fn send_gzip(mut self) -> Self {
+            self.inner = self.inner.send_gzip();
+            self
+        }
+        #[doc = r" Enable decompressing responses with `gzip`."]
+        pub fn accept_gzip(mut self) -> Self {
+            self.inner = self.inner.accept_gzip();
+            self
+        }
+        #[doc = " SubscribeState subscribes to the state of the wallet. The current wallet"]
+        #[doc = " state will always be delivered immediately."]
+        pub async fn subscribe_state(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubscribeStateRequest>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::SubscribeStateResponse>>,
+            tonic::Status,
+        > {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/lnrpc.State/SubscribeState");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        #[doc = " GetState returns the current wallet state without streaming further"]
+        #[doc = " changes."]
+        pub async fn get_state(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetStateRequest>,
+        ) -> Result<tonic::Response<super::GetStateResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/lnrpc.State/GetState");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
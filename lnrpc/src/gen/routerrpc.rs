@@ -0,0 +1,238 @@
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeHtlcEventsRequest {}
+///
+///HtlcEvent contains the htlc event that was processed. These are served on a
+///best-effort basis; events are not persisted, delivery is not guaranteed
+///(in the event of a crash in the switch, forward events may be lost) and
+///some events may be replayed upon restart. Events consumed from this package
+///should be de-duplicated by the htlc's unique combination of incoming and
+///outgoing channel id and htlc id.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HtlcEvent {
+    ///
+    ///The short channel id that the incoming htlc arrived at our node on. This
+    ///value is zero for sends.
+    #[prost(uint64, tag = "1")]
+    pub incoming_channel_id: u64,
+    ///
+    ///The short channel id that the outgoing htlc left our node on. This value
+    ///is zero for receives.
+    #[prost(uint64, tag = "2")]
+    pub outgoing_channel_id: u64,
+    ///
+    ///Incoming id is the index of the incoming htlc in the incoming channel.
+    ///This value is zero for sends.
+    #[prost(uint64, tag = "3")]
+    pub incoming_htlc_id: u64,
+    ///
+    ///Outgoing id is the index of the outgoing htlc in the outgoing channel.
+    ///This value is zero for receives.
+    #[prost(uint64, tag = "4")]
+    pub outgoing_htlc_id: u64,
+    ///
+    ///The time in unix nanoseconds that the event occurred.
+    #[prost(uint64, tag = "5")]
+    pub timestamp_ns: u64,
+    ///
+    ///The event type indicates whether the htlc was part of a send, receive or
+    ///forward.
+    #[prost(enumeration = "htlc_event::EventType", tag = "6")]
+    pub event_type: i32,
+    #[prost(oneof = "htlc_event::Event", tags = "7, 8, 9, 10, 11")]
+    pub event: ::core::option::Option<htlc_event::Event>,
+}
+/// Nested message and enum types in `HtlcEvent`.
+pub mod htlc_event {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum EventType {
+        Unknown = 0,
+        Send = 1,
+        Receive = 2,
+        Forward = 3,
+    }
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Event {
+        #[prost(message, tag = "7")]
+        ForwardEvent(super::ForwardEvent),
+        #[prost(message, tag = "8")]
+        ForwardFailEvent(super::ForwardFailEvent),
+        #[prost(message, tag = "9")]
+        SettleEvent(super::SettleEvent),
+        #[prost(message, tag = "10")]
+        LinkFailEvent(super::LinkFailEvent),
+        #[prost(message, tag = "11")]
+        SubscribedEvent(super::SubscribedEvent),
+    }
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HtlcInfo {
+    /// The timelock on the incoming htlc.
+    #[prost(uint32, tag = "1")]
+    pub incoming_timelock: u32,
+    /// The timelock on the outgoing htlc.
+    #[prost(uint32, tag = "2")]
+    pub outgoing_timelock: u32,
+    /// The amount of the incoming htlc.
+    #[prost(uint64, tag = "3")]
+    pub incoming_amt_msat: u64,
+    /// The amount of the outgoing htlc.
+    #[prost(uint64, tag = "4")]
+    pub outgoing_amt_msat: u64,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ForwardEvent {
+    /// Info contains details about the htlc that was forwarded.
+    #[prost(message, optional, tag = "1")]
+    pub info: ::core::option::Option<HtlcInfo>,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ForwardFailEvent {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SettleEvent {
+    /// The revealed preimage.
+    #[prost(bytes = "vec", tag = "1")]
+    pub preimage: ::prost::alloc::vec::Vec<u8>,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribedEvent {}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LinkFailEvent {
+    /// Info contains details about the htlc that we failed.
+    #[prost(message, optional, tag = "1")]
+    pub info: ::core::option::Option<HtlcInfo>,
+    /// FailureCode is the BOLT error code for the failure.
+    #[prost(enumeration = "super::lnrpc::failure::FailureCode", tag = "2")]
+    pub wire_failure: i32,
+    ///
+    ///FailureDetail provides additional information about the reason for the
+    ///failure. This detail enriches the information provided by the wire message
+    ///and may be 'no detail' if the wire message requires no additional metadata.
+    #[prost(enumeration = "FailureDetail", tag = "3")]
+    pub failure_detail: i32,
+    /// A string representation of the link failure.
+    #[prost(string, tag = "4")]
+    pub failure_string: ::prost::alloc::string::String,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FailureDetail {
+    Unknown = 0,
+    NoDetail = 1,
+    OnionDecode = 2,
+    LinkNotEligible = 3,
+    OnChainTimeout = 4,
+    HtlcExceedMax = 5,
+    InsufficientBalance = 6,
+    IncompleteForward = 7,
+    HtlcAddFailed = 8,
+    ForwardsDisabled = 9,
+    InvoiceCanceled = 10,
+    InvoiceUnderpaid = 11,
+    InvoiceExpiryTooSoon = 12,
+    InvoiceNotOpen = 13,
+    MppInvoiceTimeout = 14,
+    AddressMismatch = 15,
+    SetTotalMismatch = 16,
+    SetTotalTooLow = 17,
+    SetOverpaid = 18,
+    UnknownInvoice = 19,
+    InvalidKeysend = 20,
+    MppInProgress = 21,
+    CircularRoute = 22,
+}
+#[doc = r" Generated client implementations."]
+pub mod router_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    #[doc = " Subset of lnd's routerrpc/router.proto covering the HTLC event stream."]
+    #[derive(Debug, Clone)]
+    pub struct RouterClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl RouterClient<tonic::transport::Channel> {
+        #[doc = r" Attempt to create a new client by connecting to a given endpoint."]
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> RouterClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        T::Error: Into<StdError>,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> RouterClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + Send + Sync,
+        {
+            RouterClient::new(InterceptedService::new(inner, interceptor))
+        }
+        #[doc = r" Compress requests with `gzip`."]
+        #[doc = r""]
+        #[doc = r" This requires the server to support it otherwise it might respond with an"]
+        #[doc = r" error."]
+        pub fn send_gzip(mut self) -> Self {
+            self.inner = self.inner.send_gzip();
+            self
+        }
+        #[doc = r" Enable decompressing responses with `gzip`."]
+        pub fn accept_gzip(mut self) -> Self {
+            self.inner = self.inner.accept_gzip();
+            self
+        }
+        #[doc = ""]
+        #[doc = "SubscribeHtlcEvents creates a uni-directional stream from the server to"]
+        #[doc = "the client which delivers a stream of htlc events."]
+        pub async fn subscribe_htlc_events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubscribeHtlcEventsRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::HtlcEvent>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/routerrpc.Router/SubscribeHtlcEvents");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+    }
+}
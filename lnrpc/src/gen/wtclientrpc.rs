@@ -0,0 +1,135 @@
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTowersRequest {
+    /// Whether we should include sessions with the watchtower in the response.
+    #[prost(bool, tag = "1")]
+    pub include_sessions: bool,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TowerSession {
+    ///
+    ///The total number of successful backups that have been made to the
+    ///watchtower session.
+    #[prost(uint32, tag = "1")]
+    pub num_backups: u32,
+    ///
+    ///The total number of backups in the session that are currently pending to
+    ///be acknowledged by the watchtower.
+    #[prost(uint32, tag = "2")]
+    pub num_pending_backups: u32,
+    /// The maximum number of backups allowed by the watchtower session.
+    #[prost(uint32, tag = "3")]
+    pub max_backups: u32,
+    ///
+    ///The fee rate, in satoshis per vbyte, that will be used by the watchtower for
+    ///the justice transaction in the event of a channel breach.
+    #[prost(uint32, tag = "5")]
+    pub sweep_sat_per_vbyte: u32,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Tower {
+    /// The identifying public key of the watchtower.
+    #[prost(bytes = "vec", tag = "1")]
+    pub pubkey: ::prost::alloc::vec::Vec<u8>,
+    /// The list of addresses the watchtower is reachable over.
+    #[prost(string, repeated, tag = "2")]
+    pub addresses: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Whether the watchtower is currently a candidate for new sessions.
+    #[prost(bool, tag = "3")]
+    pub active_session_candidate: bool,
+    /// The number of sessions that have been negotiated with the watchtower.
+    #[prost(uint32, tag = "4")]
+    pub num_sessions: u32,
+    /// The list of sessions that have been negotiated with the watchtower.
+    #[prost(message, repeated, tag = "5")]
+    pub sessions: ::prost::alloc::vec::Vec<TowerSession>,
+}
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTowersResponse {
+    /// The list of watchtowers available for new backups.
+    #[prost(message, repeated, tag = "1")]
+    pub towers: ::prost::alloc::vec::Vec<Tower>,
+}
+#[doc = r" Generated client implementations."]
+pub mod watchtower_client_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    #[doc = " Subset of lnd's wtclientrpc/wtclient.proto covering the registered towers."]
+    #[derive(Debug, Clone)]
+    pub struct WatchtowerClientClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl WatchtowerClientClient<tonic::transport::Channel> {
+        #[doc = r" Attempt to create a new client by connecting to a given endpoint."]
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> WatchtowerClientClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        T::Error: Into<StdError>,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> WatchtowerClientClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + Send + Sync,
+        {
+            WatchtowerClientClient::new(InterceptedService::new(inner, interceptor))
+        }
+        #[doc = r" Compress requests with `gzip`."]
+        #[doc = r""]
+        #[doc = r" This requires the server to support it otherwise it might respond with an"]
+        #[doc = r" error."]
+        pub fn send_gzip(mut self) -> Self {
+            self.inner = self.inner.send_gzip();
+            self
+        }
+        #[doc = r" Enable decompressing responses with `gzip`."]
+        pub fn accept_gzip(mut self) -> Self {
+            self.inner = self.inner.accept_gzip();
+            self
+        }
+        #[doc = ""]
+        #[doc = "ListTowers returns the list of watchtowers registered with the client."]
+        pub async fn list_towers(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListTowersRequest>,
+        ) -> Result<tonic::Response<super::ListTowersResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/wtclientrpc.WatchtowerClient/ListTowers");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
@@ -1,17 +1,61 @@
 mod gen;
+#[cfg(feature = "serde")]
+mod tape;
+mod transport;
 
+pub use gen::autopilotrpc;
+pub use gen::health;
 pub use gen::lnrpc::*;
+pub use gen::routerrpc;
+pub use gen::wtclientrpc;
+#[cfg(feature = "serde")]
+pub use tape::{Recorder, Replayer};
 use thiserror::Error;
-pub use tonic::transport::Endpoint;
+pub use tonic::{transport::Endpoint, Code, Status};
+pub use transport::{RequestObserver, Transport};
+
+#[cfg(feature = "server")]
+pub mod server {
+    include!(concat!(env!("OUT_DIR"), "/lnrpc.rs"));
+}
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("tonic transport error")]
     TonicTransport(#[from] tonic::transport::Error),
+    #[cfg(feature = "serde")]
+    #[error("recording io error")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "serde")]
+    #[error("recorded response json error")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "serde")]
+    #[error("recorded response decode error")]
+    Decode(#[from] prost::DecodeError),
+    #[cfg(feature = "serde")]
+    #[error("invalid recording: {0}")]
+    InvalidRecording(String),
 }
 
-pub type LndClient = lightning_client::LightningClient<
-    tonic::codegen::InterceptedService<tonic::transport::Channel, Interceptor>,
+pub type LndClient =
+    lightning_client::LightningClient<tonic::codegen::InterceptedService<Transport, Interceptor>>;
+
+pub type HealthClient = health::health_client::HealthClient<Transport>;
+
+/// Client of the State service, which lnd serves without a macaroon even while
+/// the wallet is locked.
+pub type StateClient = state_client::StateClient<Transport>;
+
+pub type RouterClient = routerrpc::router_client::RouterClient<
+    tonic::codegen::InterceptedService<Transport, Interceptor>,
+>;
+
+pub type WatchtowerClient = wtclientrpc::watchtower_client_client::WatchtowerClientClient<
+    tonic::codegen::InterceptedService<Transport, Interceptor>,
+>;
+
+pub type AutopilotClient = autopilotrpc::autopilot_client::AutopilotClient<
+    tonic::codegen::InterceptedService<Transport, Interceptor>,
 >;
 
 pub async fn new<B1: AsRef<[u8]>, B2: AsRef<[u8]>>(
@@ -19,31 +63,93 @@ pub async fn new<B1: AsRef<[u8]>, B2: AsRef<[u8]>>(
     macaroon: Option<B2>,
     endpoint: Endpoint,
 ) -> Result<LndClient, Error> {
+    let transport = connect(tls_cert, endpoint).await?;
+
+    Ok(with_macaroon(transport, macaroon))
+}
+
+pub async fn connect<B: AsRef<[u8]>>(
+    tls_cert: Option<B>,
+    endpoint: Endpoint,
+) -> Result<Transport, Error> {
     let mut tls_config = tonic::transport::ClientTlsConfig::new();
 
     if let Some(tls_cert) = tls_cert {
         tls_config = tls_config.ca_certificate(tonic::transport::Certificate::from_pem(tls_cert));
     }
 
-    let transport = endpoint
-        .tls_config(tls_config)?
-        .connect()
-        .await
-        .map_err(Error::TonicTransport)?;
+    let channel = endpoint.tls_config(tls_config)?.connect().await?;
 
-    Ok(lightning_client::LightningClient::with_interceptor(
-        transport,
-        Interceptor {
-            macaroon: macaroon.map(|mac| Vec::from(mac.as_ref())),
-        },
+    Ok(Transport::new(channel))
+}
+
+/// Like [`connect`], but defers dialing lnd to the first request, reconnecting
+/// as needed, so a node that is down does not prevent building clients.
+pub fn connect_lazy<B: AsRef<[u8]>>(
+    tls_cert: Option<B>,
+    endpoint: Endpoint,
+) -> Result<Transport, Error> {
+    let mut tls_config = tonic::transport::ClientTlsConfig::new();
+
+    if let Some(tls_cert) = tls_cert {
+        tls_config = tls_config.ca_certificate(tonic::transport::Certificate::from_pem(tls_cert));
+    }
+
+    Ok(Transport::new(
+        endpoint.tls_config(tls_config)?.connect_lazy(),
     ))
 }
 
+/// Builds a client over an already established transport, so multiple clients
+/// authenticated with different macaroons can share the same connection.
+pub fn with_macaroon<B: AsRef<[u8]>>(transport: Transport, macaroon: Option<B>) -> LndClient {
+    lightning_client::LightningClient::with_interceptor(transport, Interceptor::new(macaroon))
+}
+
+/// Like [`with_macaroon`], but builds a client for the router sub-server.
+pub fn router_with_macaroon<B: AsRef<[u8]>>(
+    transport: Transport,
+    macaroon: Option<B>,
+) -> RouterClient {
+    routerrpc::router_client::RouterClient::with_interceptor(transport, Interceptor::new(macaroon))
+}
+
+/// Like [`with_macaroon`], but builds a client for the watchtower client
+/// sub-server.
+pub fn watchtower_with_macaroon<B: AsRef<[u8]>>(
+    transport: Transport,
+    macaroon: Option<B>,
+) -> WatchtowerClient {
+    wtclientrpc::watchtower_client_client::WatchtowerClientClient::with_interceptor(
+        transport,
+        Interceptor::new(macaroon),
+    )
+}
+
+/// Like [`with_macaroon`], but builds a client for the autopilot sub-server.
+pub fn autopilot_with_macaroon<B: AsRef<[u8]>>(
+    transport: Transport,
+    macaroon: Option<B>,
+) -> AutopilotClient {
+    autopilotrpc::autopilot_client::AutopilotClient::with_interceptor(
+        transport,
+        Interceptor::new(macaroon),
+    )
+}
+
 #[derive(Clone)]
 pub struct Interceptor {
     macaroon: Option<Vec<u8>>,
 }
 
+impl Interceptor {
+    fn new<B: AsRef<[u8]>>(macaroon: Option<B>) -> Self {
+        Self {
+            macaroon: macaroon.map(|mac| Vec::from(mac.as_ref())),
+        }
+    }
+}
+
 impl tonic::service::Interceptor for Interceptor {
     fn call(&mut self, mut req: tonic::Request<()>) -> Result<tonic::Request<()>, tonic::Status> {
         if let Some(macaroon) = &self.macaroon {
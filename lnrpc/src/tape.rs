@@ -0,0 +1,320 @@
+//! Recording of lnd responses as JSON files, one directory per scrape, and
+//! replay of them in place of lnd.
+//!
+//! Responses are stored as `<scrape>/<service>.<method>-<call>.json`, where
+//! `call` counts the calls to the method within the scrape. Calls made before
+//! the first scrape, e.g. at startup, are recorded as scrape 0.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use prost::Message;
+use tonic::{codegen::http, Code, Status};
+
+use crate::Error;
+
+/// Unary methods whose responses are recorded, along with their messages.
+/// Other methods, e.g. streams, go untouched while recording and are answered
+/// Unimplemented on replay.
+macro_rules! recorded_methods {
+    ($($path:literal => $res:ty,)*) => {
+        pub(crate) fn is_recorded(method: &str) -> bool {
+            matches!(method, $($path)|*)
+        }
+
+        fn to_json(method: &str, buf: &[u8]) -> Result<String, Error> {
+            match method {
+                $($path => Ok(serde_json::to_string_pretty(&<$res>::decode(buf)?)?),)*
+                _ => unreachable!("unrecorded method {}", method),
+            }
+        }
+
+        fn from_json(method: &str, json: &str) -> Result<Vec<u8>, Error> {
+            match method {
+                $($path => Ok(serde_json::from_str::<$res>(json)?.encode_to_vec()),)*
+                _ => Err(Error::InvalidRecording(format!("unknown method {}", method))),
+            }
+        }
+    };
+}
+
+recorded_methods! {
+    "/lnrpc.Lightning/GetInfo" => crate::GetInfoResponse,
+    "/lnrpc.Lightning/ListChannels" => crate::ListChannelsResponse,
+    "/lnrpc.Lightning/ListPayments" => crate::ListPaymentsResponse,
+    "/lnrpc.Lightning/ListInvoices" => crate::ListInvoiceResponse,
+    "/lnrpc.Lightning/DescribeGraph" => crate::ChannelGraph,
+    "/lnrpc.Lightning/ExportAllChannelBackups" => crate::ChanBackupSnapshot,
+    "/lnrpc.Lightning/ForwardingHistory" => crate::ForwardingHistoryResponse,
+    "/lnrpc.Lightning/WalletBalance" => crate::WalletBalanceResponse,
+    "/lnrpc.Lightning/GetTransactions" => crate::TransactionDetails,
+    "/lnrpc.Lightning/PendingChannels" => crate::PendingChannelsResponse,
+    "/lnrpc.Lightning/ClosedChannels" => crate::ClosedChannelsResponse,
+    "/lnrpc.Lightning/ListPeers" => crate::ListPeersResponse,
+    "/lnrpc.Lightning/EstimateFee" => crate::EstimateFeeResponse,
+    "/wtclientrpc.WatchtowerClient/ListTowers" => crate::wtclientrpc::ListTowersResponse,
+    "/autopilotrpc.Autopilot/Status" => crate::autopilotrpc::StatusResponse,
+    "/lnrpc.Lightning/CheckMacaroonPermissions" => crate::CheckMacPermResponse,
+    "/lnrpc.State/GetState" => crate::GetStateResponse,
+    "/grpc.health.v1.Health/Check" => crate::health::HealthCheckResponse,
+}
+
+/// Position in the recording, shared by the recorder and the replayer.
+#[derive(Default)]
+struct Cursor {
+    scrape: u64,
+    /// Calls to each method within the scrape so far.
+    calls: HashMap<String, usize>,
+}
+
+impl Cursor {
+    fn next_call(&mut self, method: &str) -> usize {
+        let calls = self.calls.entry(method.to_string()).or_default();
+        *calls += 1;
+        *calls - 1
+    }
+}
+
+/// Writes the responses of recorded methods under a directory.
+pub struct Recorder {
+    dir: PathBuf,
+    cursor: Mutex<Cursor>,
+}
+
+impl Recorder {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        std::fs::create_dir_all(dir.as_ref())?;
+
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            cursor: Mutex::new(Cursor::default()),
+        })
+    }
+
+    /// Starts recording the responses of the next scrape.
+    pub fn next_scrape(&self) {
+        let mut cursor = self.cursor.lock().expect("recorder lock");
+        cursor.scrape += 1;
+        cursor.calls.clear();
+    }
+
+    /// Records the response of a successful unary call, given its gRPC body.
+    pub(crate) fn record(&self, method: &str, body: &[u8]) -> Result<(), Error> {
+        let message = decode_frame(body)
+            .ok_or_else(|| Error::InvalidRecording(format!("unexpected {} body", method)))?;
+        let json = to_json(method, message)?;
+
+        let path = {
+            let mut cursor = self.cursor.lock().expect("recorder lock");
+            let call = cursor.next_call(method);
+            self.dir
+                .join(scrape_dir_name(cursor.scrape))
+                .join(file_name(method, call))
+        };
+        std::fs::create_dir_all(path.parent().expect("scrape directory"))?;
+        std::fs::write(path, json)?;
+
+        Ok(())
+    }
+}
+
+/// Answers requests with responses recorded by a [`Recorder`], looping over the
+/// recorded scrapes.
+pub struct Replayer {
+    /// Encoded responses of each method, in call order, by scrape.
+    scrapes: BTreeMap<u64, HashMap<String, Vec<Vec<u8>>>>,
+    cursor: Mutex<Cursor>,
+}
+
+impl Replayer {
+    /// Loads every response recorded under the directory.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut scrapes = BTreeMap::new();
+
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let scrape = match entry
+                .file_name()
+                .to_str()
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                Some(scrape) => scrape,
+                None => continue,
+            };
+
+            let mut responses = BTreeMap::new();
+            for file in std::fs::read_dir(entry.path())? {
+                let file = file?;
+                let file_name = file.file_name();
+                let (method, call) =
+                    file_name
+                        .to_str()
+                        .and_then(parse_file_name)
+                        .ok_or_else(|| {
+                            Error::InvalidRecording(format!("unexpected file {:?}", file.path()))
+                        })?;
+
+                let json = std::fs::read_to_string(file.path())?;
+                responses.insert((method.clone(), call), from_json(&method, &json)?);
+            }
+
+            // Calls are ordered by their number, gaps in it being ignored
+            let mut methods: HashMap<String, Vec<Vec<u8>>> = HashMap::new();
+            for ((method, _), response) in responses {
+                methods.entry(method).or_default().push(response);
+            }
+            scrapes.insert(scrape, methods);
+        }
+
+        Ok(Self {
+            scrapes,
+            cursor: Mutex::new(Cursor::default()),
+        })
+    }
+
+    /// Moves on to the responses of the next recorded scrape, starting over
+    /// after the last one. Scrape 0 is only replayed until the first scrape.
+    pub fn next_scrape(&self) {
+        let mut cursor = self.cursor.lock().expect("replayer lock");
+
+        let mut scrapes = self.scrapes.keys().copied().filter(|scrape| *scrape > 0);
+        let first = scrapes.clone().next();
+        let next = scrapes.find(|scrape| *scrape > cursor.scrape).or(first);
+
+        if let Some(next) = next {
+            cursor.scrape = next;
+        }
+        cursor.calls.clear();
+    }
+
+    /// Returns the gRPC body of the response to the call. Calls beyond the
+    /// recorded ones get the last response recorded for the method.
+    pub(crate) fn respond(&self, method: &str) -> Result<Vec<u8>, Status> {
+        if !is_recorded(method) {
+            return Err(Status::unimplemented(format!("{} is not replayed", method)));
+        }
+
+        let mut cursor = self.cursor.lock().expect("replayer lock");
+        let call = cursor.next_call(method);
+        let responses = self
+            .scrapes
+            .get(&cursor.scrape)
+            .and_then(|methods| methods.get(method))
+            .ok_or_else(|| {
+                Status::not_found(format!(
+                    "no {} response recorded in scrape {}",
+                    method, cursor.scrape
+                ))
+            })?;
+        let response = responses
+            .get(call)
+            .or_else(|| responses.last())
+            .expect("recorded response");
+
+        Ok(encode_frame(response))
+    }
+}
+
+fn scrape_dir_name(scrape: u64) -> String {
+    format!("{:06}", scrape)
+}
+
+/// `/lnrpc.Lightning/GetInfo` is stored as `lnrpc.Lightning.GetInfo-<call>.json`.
+fn file_name(method: &str, call: usize) -> String {
+    format!(
+        "{}-{}.json",
+        method.trim_start_matches('/').replace('/', "."),
+        call
+    )
+}
+
+fn parse_file_name(file_name: &str) -> Option<(String, usize)> {
+    let (name, call) = file_name.strip_suffix(".json")?.rsplit_once('-')?;
+    let (service, method) = name.rsplit_once('.')?;
+
+    Some((format!("/{}/{}", service, method), call.parse().ok()?))
+}
+
+/// Returns the message of a gRPC body holding a single uncompressed one.
+fn decode_frame(body: &[u8]) -> Option<&[u8]> {
+    if body.len() < 5 || body[0] != 0 {
+        return None;
+    }
+
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    if body.len() != 5 + len {
+        return None;
+    }
+
+    Some(&body[5..])
+}
+
+fn encode_frame(message: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(5 + message.len());
+    body.push(0);
+    body.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    body.extend_from_slice(message);
+
+    body
+}
+
+/// Builds the response to a replayed call. Statuses are sent in the headers,
+/// like lnd does for calls failing right away.
+pub(crate) fn replay_response(res: Result<Vec<u8>, Status>) -> http::Response<hyper::Body> {
+    let (code, message, body) = match res {
+        Ok(body) => (Code::Ok, String::new(), body),
+        Err(status) => (status.code(), status.message().to_string(), vec![]),
+    };
+
+    let mut builder = http::Response::builder()
+        .header("content-type", "application/grpc")
+        .header("grpc-status", (code as i32).to_string());
+    if !message.is_empty() {
+        builder = builder.header("grpc-message", message);
+    }
+
+    builder
+        .body(hyper::Body::from(body))
+        .expect("valid replayed response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_round_trip() {
+        let file_name = file_name("/lnrpc.Lightning/ListPayments", 3);
+        assert_eq!(file_name, "lnrpc.Lightning.ListPayments-3.json");
+        assert_eq!(
+            parse_file_name(&file_name),
+            Some(("/lnrpc.Lightning/ListPayments".to_string(), 3))
+        );
+
+        assert_eq!(
+            parse_file_name("grpc.health.v1.Health.Check-0.json"),
+            Some(("/grpc.health.v1.Health/Check".to_string(), 0))
+        );
+    }
+
+    #[test]
+    fn responses_round_trip_through_json() {
+        let info = crate::GetInfoResponse {
+            alias: "node".to_string(),
+            block_height: 800_000,
+            ..crate::GetInfoResponse::default()
+        };
+        let body = encode_frame(&info.encode_to_vec());
+
+        let message = decode_frame(&body).expect("single message");
+        let json = to_json("/lnrpc.Lightning/GetInfo", message).expect("json response");
+        let message = from_json("/lnrpc.Lightning/GetInfo", &json).expect("encoded response");
+        assert_eq!(
+            crate::GetInfoResponse::decode(&*message).expect("decoded response"),
+            info
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use tonic::{
+    body::BoxBody,
+    codegen::{http, Service},
+    Code,
+};
+
+#[cfg(feature = "serde")]
+use crate::tape::{self, Recorder, Replayer};
+
+/// Called with the gRPC method path, resulting status code and duration of
+/// every request sent over a [`Transport`].
+pub type RequestObserver = Arc<dyn Fn(&str, Code, Duration) + Send + Sync>;
+
+/// Connection to lnd, shared by all clients built over it.
+#[derive(Clone)]
+pub struct Transport {
+    backend: Backend,
+    observer: Option<RequestObserver>,
+    #[cfg(feature = "serde")]
+    recorder: Option<Arc<Recorder>>,
+}
+
+#[derive(Clone)]
+enum Backend {
+    Lnd(tonic::transport::Channel),
+    /// Recorded responses answered in place of lnd.
+    #[cfg(feature = "serde")]
+    Replay(Arc<Replayer>),
+}
+
+impl Transport {
+    pub(crate) fn new(channel: tonic::transport::Channel) -> Self {
+        Self {
+            backend: Backend::Lnd(channel),
+            observer: None,
+            #[cfg(feature = "serde")]
+            recorder: None,
+        }
+    }
+
+    /// Answers every request with the recorded responses instead of dialing
+    /// lnd.
+    #[cfg(feature = "serde")]
+    pub fn replay(replayer: Arc<Replayer>) -> Self {
+        Self {
+            backend: Backend::Replay(replayer),
+            observer: None,
+            recorder: None,
+        }
+    }
+
+    /// Records the responses to the requests sent over the transport.
+    #[cfg(feature = "serde")]
+    pub fn with_recorder(self, recorder: Arc<Recorder>) -> Self {
+        Self {
+            recorder: Some(recorder),
+            ..self
+        }
+    }
+
+    /// Moves the recorder or replayer of the transport on to the next scrape.
+    #[cfg(feature = "serde")]
+    pub fn next_scrape(&self) {
+        if let Some(recorder) = &self.recorder {
+            recorder.next_scrape();
+        }
+        if let Backend::Replay(replayer) = &self.backend {
+            replayer.next_scrape();
+        }
+    }
+
+    /// Reports every request sent over the transport to the given observer.
+    ///
+    /// Requests are timed until the response headers arrive, which for unary
+    /// calls is when the response is received and for streaming calls is when
+    /// the stream is established.
+    pub fn with_observer(self, observer: RequestObserver) -> Self {
+        Self {
+            observer: Some(observer),
+            ..self
+        }
+    }
+}
+
+impl Service<http::Request<BoxBody>> for Transport {
+    type Response = http::Response<hyper::Body>;
+    type Error = tonic::transport::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match &mut self.backend {
+            Backend::Lnd(channel) => channel.poll_ready(cx),
+            #[cfg(feature = "serde")]
+            Backend::Replay(_) => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        let observer = self.observer.clone();
+        let method = req.uri().path().to_string();
+        let start_time = Instant::now();
+        let res: Self::Future = match &mut self.backend {
+            Backend::Lnd(channel) => Box::pin(channel.call(req)),
+            #[cfg(feature = "serde")]
+            Backend::Replay(replayer) => {
+                let res = tape::replay_response(replayer.respond(&method));
+                Box::pin(async move { Ok(res) })
+            }
+        };
+        #[cfg(feature = "serde")]
+        let recorder = self.recorder.clone().filter(|_| tape::is_recorded(&method));
+
+        Box::pin(async move {
+            let res = res.await;
+            #[cfg(feature = "serde")]
+            let res = match (recorder, res) {
+                (Some(recorder), Ok(res)) => Ok(record(&recorder, &method, res).await),
+                (_, res) => res,
+            };
+
+            if let Some(observer) = observer {
+                let code = match &res {
+                    Ok(res) => response_code(res),
+                    Err(_) => Code::Unavailable,
+                };
+
+                observer(&method, code, start_time.elapsed());
+            }
+
+            res
+        })
+    }
+}
+
+/// Reads the whole response to record it if successful, handing back an
+/// equivalent one with the trailers merged into the headers.
+#[cfg(feature = "serde")]
+async fn record(
+    recorder: &Recorder,
+    method: &str,
+    res: http::Response<hyper::Body>,
+) -> http::Response<hyper::Body> {
+    use hyper::body::HttpBody;
+
+    let (mut parts, mut body) = res.into_parts();
+
+    let read = async {
+        let mut data = vec![];
+        while let Some(chunk) = body.data().await {
+            data.extend_from_slice(&chunk?);
+        }
+
+        Ok::<_, hyper::Error>((data, body.trailers().await?))
+    };
+    let data = match read.await {
+        Ok((data, trailers)) => {
+            parts.headers.extend(trailers.unwrap_or_default());
+            data
+        }
+        Err(e) => {
+            let status = tonic::Status::unavailable(e.to_string());
+            return tape::replay_response(Err(status));
+        }
+    };
+
+    let res = http::Response::from_parts(parts, hyper::Body::from(data.clone()));
+    if response_code(&res) == Code::Ok {
+        if let Err(e) = recorder.record(method, &data) {
+            panic!("failed recording {} response: {}", method, e);
+        }
+    }
+
+    res
+}
+
+/// Failed calls usually get a trailers-only response, carrying the status in
+/// the headers. Otherwise the call is assumed to have succeeded so far.
+fn response_code(res: &http::Response<hyper::Body>) -> Code {
+    if let Some(status) = res.headers().get("grpc-status") {
+        return status
+            .to_str()
+            .ok()
+            .and_then(|status| status.parse::<i32>().ok())
+            .map_or(Code::Unknown, Code::from_i32);
+    }
+
+    if res.status() == http::StatusCode::OK {
+        Code::Ok
+    } else {
+        Code::Unknown
+    }
+}
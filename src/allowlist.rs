@@ -0,0 +1,167 @@
+use std::net::IpAddr;
+
+/// Network given in CIDR notation, like `10.0.0.0/8` or `fd00::/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                prefix_eq(&net.octets(), &addr.octets(), self.prefix_len)
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                prefix_eq(&net.octets(), &addr.octets(), self.prefix_len)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Cidr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl std::str::FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (s, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| format!("invalid address in {:?}", s))?;
+        let max_prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= max_prefix_len)
+                .ok_or_else(|| format!("invalid prefix length in {:?}", s))?,
+            None => max_prefix_len,
+        };
+
+        match canonical(addr) {
+            IpAddr::V4(v4) if addr.is_ipv6() => Ok(Self {
+                addr: IpAddr::V4(v4),
+                prefix_len: prefix_len
+                    .checked_sub(96)
+                    .ok_or_else(|| format!("prefix of {:?} spans beyond IPv4", s))?,
+            }),
+            addr => Ok(Self { addr, prefix_len }),
+        }
+    }
+}
+
+/// Source addresses allowed to reach the exporter.
+pub struct SourceAllowlist {
+    networks: Vec<Cidr>,
+    allow_loopback: bool,
+}
+
+impl SourceAllowlist {
+    pub fn new(networks: Vec<Cidr>, allow_loopback: bool) -> Self {
+        Self {
+            networks,
+            allow_loopback,
+        }
+    }
+
+    pub fn allows(&self, addr: IpAddr) -> bool {
+        let addr = canonical(addr);
+
+        (self.allow_loopback && addr.is_loopback())
+            || self.networks.iter().any(|network| network.contains(addr))
+    }
+}
+
+/// IPv4 clients of dual-stack listeners show up as IPv4-mapped IPv6
+/// addresses, which are matched as the IPv4 address they map.
+fn canonical(addr: IpAddr) -> IpAddr {
+    match addr {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => addr,
+        },
+        IpAddr::V4(_) => addr,
+    }
+}
+
+fn prefix_eq(a: &[u8], b: &[u8], prefix_len: u8) -> bool {
+    let full_bytes = usize::from(prefix_len / 8);
+    let rest_bits = prefix_len % 8;
+    if a[..full_bytes] != b[..full_bytes] {
+        return false;
+    }
+    if rest_bits == 0 {
+        return true;
+    }
+
+    let mask = 0xffu8 << (8 - rest_bits);
+    a[full_bytes] & mask == b[full_bytes] & mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist(networks: &[&str], allow_loopback: bool) -> SourceAllowlist {
+        SourceAllowlist::new(
+            networks
+                .iter()
+                .map(|network| network.parse().expect("valid cidr"))
+                .collect(),
+            allow_loopback,
+        )
+    }
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().expect("valid address")
+    }
+
+    #[test]
+    fn sources_are_matched_against_the_networks() {
+        let allowlist = allowlist(&["10.0.0.0/8", "192.168.1.5", "fd00::/12"], true);
+
+        assert!(allowlist.allows(ip("10.20.30.40")));
+        assert!(allowlist.allows(ip("192.168.1.5")));
+        assert!(!allowlist.allows(ip("192.168.1.6")));
+        assert!(allowlist.allows(ip("fd0f::1")));
+        assert!(!allowlist.allows(ip("fd10::1")));
+        // IPv4 clients of an IPv6 listener
+        assert!(allowlist.allows(ip("::ffff:10.0.0.1")));
+        assert!(!allowlist.allows(ip("::ffff:11.0.0.1")));
+
+        assert!(allowlist.allows(ip("127.0.0.1")));
+        assert!(allowlist.allows(ip("::1")));
+        assert!(allowlist.allows(ip("::ffff:127.0.0.1")));
+    }
+
+    #[test]
+    fn loopback_can_be_denied() {
+        let allowlist = allowlist(&["10.0.0.0/8"], false);
+
+        assert!(!allowlist.allows(ip("127.0.0.1")));
+        assert!(!allowlist.allows(ip("::1")));
+        assert!(allowlist.allows(ip("10.0.0.1")));
+    }
+
+    #[test]
+    fn invalid_networks_are_rejected() {
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("fd00::/129".parse::<Cidr>().is_err());
+        assert!("10.0.0/8".parse::<Cidr>().is_err());
+        assert_eq!("::/0".parse::<Cidr>().map(|cidr| cidr.prefix_len), Ok(0));
+        assert_eq!(
+            "::ffff:10.0.0.0/104".parse::<Cidr>(),
+            "10.0.0.0/8".parse::<Cidr>()
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use prometheus::{core::Collector, proto::MetricFamily};
+
+use crate::collector::MetricsConfig;
+
+/// Value of the WWW-Authenticate header of rejected requests.
+pub const WWW_AUTHENTICATE: &str = "Bearer realm=\"lnd-exporter\"";
+
+/// Requires a static bearer token on the endpoints exposing node data.
+pub struct TokenAuth {
+    token: Vec<u8>,
+    rejected_requests_total: prometheus::IntCounter,
+}
+
+impl TokenAuth {
+    pub fn new(token: &str, metrics_config: &MetricsConfig) -> Self {
+        Self {
+            token: token.as_bytes().to_vec(),
+            rejected_requests_total: prometheus::IntCounter::with_opts(metrics_config.opts(
+                "exporter_auth_rejected_requests_total",
+                "Number of requests rejected for lacking a valid bearer token",
+            ))
+            .expect("valid metric"),
+        }
+    }
+
+    /// Whether the request carries the token, counting it as rejected if not.
+    pub fn authorize(&self, headers: &hyper::HeaderMap) -> bool {
+        let authorized = headers
+            .get(hyper::header::AUTHORIZATION)
+            .and_then(|value| value.as_bytes().strip_prefix(b"Bearer "))
+            .map(|token| constant_time_eq(token, &self.token))
+            .unwrap_or(false);
+        if !authorized {
+            self.rejected_requests_total.inc();
+        }
+
+        authorized
+    }
+
+    pub fn collect(&self) -> Vec<MetricFamily> {
+        self.rejected_requests_total.collect()
+    }
+}
+
+/// Compares the bytes in time independent of their contents, so a wrong token
+/// doesn't reveal how much of it matched. Lengths are not hidden.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
@@ -0,0 +1,84 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use prometheus::{core::Collector, proto::MetricFamily};
+use tokio::sync::Mutex;
+
+use crate::collector::{self, MetricsConfig, ScraperSet};
+
+/// Families gathered, along with when.
+type Snapshot = (Instant, Vec<MetricFamily>);
+
+/// Caches the result of gathering a registry so scrapes arriving within
+/// `min_interval` of each other don't hit lnd again.
+pub struct GatherCache {
+    registry: prometheus::Registry,
+    min_interval: Duration,
+    /// Snapshots by the scrapers they were restricted to, if any.
+    // Holding the lock while gathering makes concurrent requests wait for and
+    // reuse the same snapshot instead of scraping lnd in parallel.
+    snapshots: Mutex<HashMap<Option<String>, Snapshot>>,
+    cache_hit_total: prometheus::IntCounter,
+    snapshot_age_seconds: prometheus::Gauge,
+}
+
+impl GatherCache {
+    /// The cache metrics keep the constant labels of `metrics_config`, even if
+    /// the collector later refreshes its node labels.
+    pub fn new(
+        registry: prometheus::Registry,
+        min_interval: Duration,
+        metrics_config: &MetricsConfig,
+    ) -> Self {
+        Self {
+            registry,
+            min_interval,
+            snapshots: Mutex::new(HashMap::new()),
+            cache_hit_total: prometheus::IntCounter::with_opts(metrics_config.opts(
+                "exporter_cache_hit_total",
+                "Number of scrapes served from a cached snapshot",
+            ))
+            .expect("valid metric"),
+            snapshot_age_seconds: prometheus::Gauge::with_opts(metrics_config.opts(
+                "exporter_cache_snapshot_age_seconds",
+                "Age of the snapshot served by the last scrape",
+            ))
+            .expect("valid metric"),
+        }
+    }
+
+    /// Gathers the registry, with its collectors restricted to the scrapers
+    /// of `filter` if any. Each filter gets its own snapshot.
+    pub async fn gather(&self, filter: Option<ScraperSet>) -> Vec<MetricFamily> {
+        let mut snapshots = self.snapshots.lock().await;
+        let key = filter.as_ref().map(ScraperSet::to_string);
+
+        match snapshots.get(&key) {
+            Some((gathered_at, _)) if gathered_at.elapsed() < self.min_interval => {
+                self.cache_hit_total.inc();
+            }
+            _ => {
+                let registry = self.registry.clone();
+                let ms = tokio::task::spawn_blocking(move || {
+                    collector::with_scraper_filter(filter, || registry.gather())
+                })
+                .await
+                .expect("gather");
+
+                snapshots.insert(key.clone(), (Instant::now(), ms));
+            }
+        }
+
+        let (gathered_at, ms) = snapshots.get(&key).expect("snapshot");
+        self.snapshot_age_seconds
+            .set(gathered_at.elapsed().as_secs_f64());
+
+        let mut ms = ms.clone();
+        ms.extend(self.cache_hit_total.collect());
+        ms.extend(self.snapshot_age_seconds.collect());
+
+        ms
+    }
+}
@@ -1,47 +1,1471 @@
+mod bitcoind;
+mod block_explorer;
+mod derived;
+mod distribution;
+mod filter;
+mod median;
 mod metrics;
-mod scappers;
+mod permissions;
+mod scrapers;
+mod subscriptions;
+mod wallet;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
+pub use bitcoind::BitcoindClient;
+pub use block_explorer::BlockExplorer;
+pub use filter::FamilyFilter;
 use lnrpc::LndClient;
+pub use metrics::MetricsConfig;
+pub use permissions::check_permissions;
 use prometheus::{
     core::{Collector, Desc},
     proto::MetricFamily,
 };
+pub use scrapers::{node_labels, NODE_LABELS};
+use serde::Serialize;
 use tokio::sync::Mutex;
 
+use distribution::PaymentAmountDistribution;
+use median::SlidingMedian;
+use metrics::{BlindedPathsMetrics, DerivedMetrics, ExporterMetrics, WalletMetrics};
+use scrapers::{ScrapeContext, ScrapeError, Scraper};
+
+/// Scrapers run by [`LndCollector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScraperName {
+    GetInfo,
+    Payments,
+    Channels,
+    Backup,
+    Graph,
+    Invoices,
+    Health,
+    Forwarding,
+    Policies,
+    WalletBalance,
+    Transactions,
+    PendingChannels,
+    Peers,
+    CustomMessages,
+    Watchtower,
+    Autopilot,
+    CloseFees,
+    HubDistances,
+    Mempool,
+}
+
+impl ScraperName {
+    pub const ALL: &'static [ScraperName] = &[
+        ScraperName::GetInfo,
+        ScraperName::Payments,
+        ScraperName::Channels,
+        ScraperName::Backup,
+        ScraperName::Graph,
+        ScraperName::Invoices,
+        ScraperName::Health,
+        ScraperName::Forwarding,
+        ScraperName::Policies,
+        ScraperName::WalletBalance,
+        ScraperName::Transactions,
+        ScraperName::PendingChannels,
+        ScraperName::Peers,
+        ScraperName::CustomMessages,
+        ScraperName::Watchtower,
+        ScraperName::Autopilot,
+        ScraperName::CloseFees,
+        ScraperName::HubDistances,
+        ScraperName::Mempool,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScraperName::GetInfo => "getinfo",
+            ScraperName::Payments => "payments",
+            ScraperName::Channels => "channels",
+            ScraperName::Backup => "backup",
+            ScraperName::Graph => "graph",
+            ScraperName::Invoices => "invoices",
+            ScraperName::Health => "health",
+            ScraperName::Forwarding => "forwarding",
+            ScraperName::Policies => "policies",
+            ScraperName::WalletBalance => "walletbalance",
+            ScraperName::Transactions => "transactions",
+            ScraperName::PendingChannels => "pendingchannels",
+            ScraperName::Peers => "peers",
+            ScraperName::CustomMessages => "custommessages",
+            ScraperName::Watchtower => "watchtower",
+            ScraperName::Autopilot => "autopilot",
+            ScraperName::CloseFees => "closefees",
+            ScraperName::HubDistances => "hubdistances",
+            ScraperName::Mempool => "mempool",
+        }
+    }
+
+    /// Whether the scraper runs unless explicitly disabled. Expensive scrapers
+    /// have to be explicitly enabled.
+    pub fn enabled_by_default(&self) -> bool {
+        !matches!(
+            self,
+            ScraperName::Backup
+                | ScraperName::Graph
+                | ScraperName::Health
+                | ScraperName::Forwarding
+                | ScraperName::Policies
+                | ScraperName::WalletBalance
+                | ScraperName::Transactions
+                | ScraperName::PendingChannels
+                | ScraperName::Peers
+                | ScraperName::CustomMessages
+                | ScraperName::Watchtower
+                | ScraperName::Autopilot
+                | ScraperName::CloseFees
+                | ScraperName::HubDistances
+                | ScraperName::Mempool
+        )
+    }
+
+    /// Whether the scraper may issue many or large requests per scrape.
+    pub fn is_bulk(&self) -> bool {
+        matches!(
+            self,
+            ScraperName::Payments
+                | ScraperName::Backup
+                | ScraperName::Graph
+                | ScraperName::Invoices
+                | ScraperName::Forwarding
+                | ScraperName::Policies
+                | ScraperName::Transactions
+                | ScraperName::HubDistances
+        )
+    }
+
+    /// Whether the scraper calls lnd, rather than only other services.
+    pub fn uses_lnd(&self) -> bool {
+        !matches!(self, ScraperName::Mempool)
+    }
+}
+
+impl std::fmt::Display for ScraperName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ScraperName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ScraperName::ALL
+            .iter()
+            .find(|name| name.as_str() == s)
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "unknown scraper {:?}, expected one of {:?}",
+                    s,
+                    ScraperName::ALL
+                        .iter()
+                        .map(ScraperName::as_str)
+                        .collect::<Vec<_>>()
+                )
+            })
+    }
+}
+
+/// Set of scrapers enabled in [`LndCollector`].
+#[derive(Debug, Clone)]
+pub struct ScraperSet(HashSet<ScraperName>);
+
+impl ScraperSet {
+    pub fn insert(&mut self, scraper: ScraperName) {
+        self.0.insert(scraper);
+    }
+
+    pub fn remove(&mut self, scraper: ScraperName) {
+        self.0.remove(&scraper);
+    }
+
+    pub fn contains(&self, scraper: ScraperName) -> bool {
+        self.0.contains(&scraper)
+    }
+
+    fn contains_name(&self, name: &str) -> bool {
+        self.0.iter().any(|scraper| scraper.as_str() == name)
+    }
+}
+
+impl std::iter::FromIterator<ScraperName> for ScraperSet {
+    fn from_iter<I: IntoIterator<Item = ScraperName>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Default for ScraperSet {
+    fn default() -> Self {
+        Self(
+            ScraperName::ALL
+                .iter()
+                .filter(|name| name.enabled_by_default())
+                .copied()
+                .collect(),
+        )
+    }
+}
+
+impl std::fmt::Display for ScraperSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = ScraperName::ALL
+            .iter()
+            .filter(|name| self.contains(**name))
+            .map(ScraperName::as_str)
+            .collect::<Vec<_>>();
+
+        f.write_str(&names.join(","))
+    }
+}
+
+thread_local! {
+    static SCRAPER_FILTER: RefCell<Option<ScraperSet>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, e.g. gathering a registry, with the collectors it runs on this
+/// thread restricted to the scrapers of `filter`, if any. Registries have no
+/// way to pass it down to the collectors otherwise.
+pub fn with_scraper_filter<T>(filter: Option<ScraperSet>, f: impl FnOnce() -> T) -> T {
+    let previous = SCRAPER_FILTER.with(|current| current.replace(filter));
+    let res = f();
+    SCRAPER_FILTER.with(|current| *current.borrow_mut() = previous);
+
+    res
+}
+
+fn scraper_filter() -> Option<ScraperSet> {
+    SCRAPER_FILTER.with(|current| current.borrow().clone())
+}
+
+/// Set of lnd clients, each one possibly authenticated with a different
+/// macaroon, indexed by the name of the scraper that uses it.
+///
+/// Scrapers without a scoped macaroon fall back to the default one. Bulk
+/// scrapers are spread over their own connections, when there is more than
+/// one, so that long paginated catch-ups do not starve the fast ones.
+pub struct ScopedLndClientSet {
+    transports: Vec<lnrpc::Transport>,
+    default: Option<Vec<u8>>,
+    scoped: HashMap<ScraperName, Vec<u8>>,
+}
+
+impl ScopedLndClientSet {
+    pub fn new(transports: Vec<lnrpc::Transport>, macaroon: Option<&[u8]>) -> Self {
+        assert!(!transports.is_empty(), "at least one lnd connection");
+
+        Self {
+            transports,
+            default: macaroon.map(Vec::from),
+            scoped: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, scraper: ScraperName, macaroon: &[u8]) {
+        self.scoped.insert(scraper, Vec::from(macaroon));
+    }
+
+    /// Reports the requests of every client built from now on to the observer.
+    pub fn observe_requests(&mut self, observer: lnrpc::RequestObserver) {
+        for transport in self.transports.iter_mut() {
+            *transport = transport.clone().with_observer(observer.clone());
+        }
+    }
+
+    /// Moves the recording or replay of lnd responses, if any, on to the next
+    /// scrape. Every transport shares the same one.
+    pub fn next_scrape(&self) {
+        self.transports[0].next_scrape();
+    }
+
+    /// Returns a client for the gRPC health service, which needs no macaroon.
+    pub fn health(&self) -> lnrpc::HealthClient {
+        lnrpc::HealthClient::new(self.transport(ScraperName::Health).clone())
+    }
+
+    /// Returns a client for the State service, which needs no macaroon.
+    pub fn state(&self) -> lnrpc::StateClient {
+        lnrpc::StateClient::new(self.transports[0].clone())
+    }
+
+    /// Returns the macaroon used by the given scraper, if any.
+    pub fn macaroon(&self, scraper: ScraperName) -> Option<&[u8]> {
+        self.scoped
+            .get(&scraper)
+            .or(self.default.as_ref())
+            .map(Vec::as_slice)
+    }
+
+    /// Returns a router sub-server client for the given scraper.
+    pub fn router(&self, scraper: ScraperName) -> lnrpc::RouterClient {
+        lnrpc::router_with_macaroon(self.transport(scraper).clone(), self.macaroon(scraper))
+    }
+
+    /// Returns a watchtower client sub-server client for the given scraper.
+    pub fn watchtower(&self, scraper: ScraperName) -> lnrpc::WatchtowerClient {
+        lnrpc::watchtower_with_macaroon(self.transport(scraper).clone(), self.macaroon(scraper))
+    }
+
+    /// Returns an autopilot sub-server client for the given scraper.
+    pub fn autopilot(&self, scraper: ScraperName) -> lnrpc::AutopilotClient {
+        lnrpc::autopilot_with_macaroon(self.transport(scraper).clone(), self.macaroon(scraper))
+    }
+
+    /// Returns a client for the given scraper. Clients are cheap to build since
+    /// they share the underlying HTTP/2 connections.
+    pub fn get(&self, scraper: ScraperName) -> LndClient {
+        lnrpc::with_macaroon(self.transport(scraper).clone(), self.macaroon(scraper))
+    }
+
+    /// Fast scrapers share the first connection and bulk scrapers round-robin
+    /// over the remaining ones.
+    fn transport(&self, scraper: ScraperName) -> &lnrpc::Transport {
+        let bulk_transports = &self.transports[1..];
+        if !scraper.is_bulk() || bulk_transports.is_empty() {
+            return &self.transports[0];
+        }
+
+        let bulk_index = ScraperName::ALL
+            .iter()
+            .filter(|name| name.is_bulk())
+            .position(|name| *name == scraper)
+            .expect("bulk scraper");
+
+        &bulk_transports[bulk_index % bulk_transports.len()]
+    }
+}
+
+/// Number of last succeeded payments the median fee is computed over.
+const MEDIAN_FEE_WINDOW: usize = 1000;
+
+/// Number of last succeeded payments the amount percentiles are computed over
+/// by default.
+const DEFAULT_PAYMENT_DISTRIBUTION_WINDOW: usize = 10_000;
+
 pub struct ListPaymentsCache {
     index_offset: u64,
     outgoing_payments: HashMap<lnrpc::payment::PaymentStatus, i64>,
     payment_failure_reasons: HashMap<lnrpc::PaymentFailureReason, i64>,
     total_fee_msat: i64,
+    /// Succeeded or failed payments.
+    completed_payments: i64,
+    /// Payments that succeeded with their first attempt.
+    single_attempt_payments: i64,
+    /// Succeeded payments by truncated destination pubkey.
+    destinations: HashMap<String, u64>,
+    /// Fee extremes of succeeded payments, `i64::MAX` and 0 until one succeeds.
+    min_fee_msat: i64,
+    max_fee_msat: i64,
+    recent_fees_msat: SlidingMedian,
+    /// Amounts of the last succeeded payments.
+    recent_amounts_msat: PaymentAmountDistribution,
+    /// Succeeded payments from the node to itself, with their fees and
+    /// amounts.
+    circular_rebalances: i64,
+    circular_rebalance_fee_msat: i64,
+    circular_rebalance_amount_msat: i64,
+}
+
+impl ListPaymentsCache {
+    /// Cache keeping the amounts of the given number of last succeeded
+    /// payments.
+    pub fn new(amount_window: usize) -> Self {
+        Self {
+            index_offset: 0,
+            outgoing_payments: HashMap::new(),
+            payment_failure_reasons: HashMap::new(),
+            total_fee_msat: 0,
+            completed_payments: 0,
+            single_attempt_payments: 0,
+            destinations: HashMap::new(),
+            min_fee_msat: i64::MAX,
+            max_fee_msat: 0,
+            recent_fees_msat: SlidingMedian::new(MEDIAN_FEE_WINDOW),
+            recent_amounts_msat: PaymentAmountDistribution::new(amount_window),
+            circular_rebalances: 0,
+            circular_rebalance_fee_msat: 0,
+            circular_rebalance_amount_msat: 0,
+        }
+    }
+}
+
+impl Default for ListPaymentsCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAYMENT_DISTRIBUTION_WINDOW)
+    }
+}
+
+/// Number of last invoices the creation rates are computed over.
+const INVOICE_CREATION_WINDOW: usize = 10_000;
+
+#[derive(Default)]
+pub struct ListInvoicesCache {
+    /// Highest invoice add index seen so far.
+    index_offset: u64,
+    /// Add indices of invoices that were neither settled nor cancelled yet.
+    open_invoices: BTreeSet<u64>,
+    settled_value_msat: i64,
+    settled_count: i64,
+    cancelled_count: i64,
+    /// Settlement proofs by hex payment hash, only tracked when enabled.
+    invoice_proofs: HashMap<String, InvoiceProof>,
+    /// Creation times of the last invoices, up to `INVOICE_CREATION_WINDOW`.
+    creation_dates: VecDeque<i64>,
+    /// Latest settle date of the settled invoices, in seconds since the epoch.
+    last_settled_timestamp: i64,
+}
+
+#[derive(Default)]
+pub struct TransactionCache {
+    /// Height listing resumes from, past the block of the last confirmed
+    /// transaction counted. Unconfirmed transactions are counted once confirmed.
+    start_height: i32,
+    /// Amounts of confirmed transactions by label and direction.
+    amount_sat: HashMap<(String, &'static str), i64>,
+    /// Fees of confirmed outgoing transactions by label.
+    fees_sat: HashMap<String, i64>,
+}
+
+/// Proof of settlement of an invoice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvoiceProof {
+    /// Whether the preimage of a settled regular invoice is known.
+    Preimage(bool),
+    /// Number of settled sub-invoices, i.e. HTLC sets, of an AMP invoice.
+    AmpSettled(u64),
+}
+
+pub struct ForwardingCache {
+    /// Unix timestamp forwarding history is counted from, so that forwards and
+    /// link failures, which are only seen while subscribed, cover the same period.
+    start_time: u64,
+    index_offset: u32,
+    /// Settled forwards by outgoing chan_id.
+    forwarded: HashMap<u64, u64>,
+    /// Forwards failed by the outgoing link, by outgoing chan_id.
+    link_failed: HashMap<u64, u64>,
+    /// Seconds from forward to settle of the HTLCs settled since the last
+    /// scrape, at most [`ForwardingCache::MAX_RESOLUTION_TIMES`] of them.
+    resolution_times: Vec<f64>,
+    /// Unix timestamp and fee of the forwards of the revenue windows, oldest
+    /// first.
+    fees: VecDeque<(u64, u64)>,
+    /// Whether the forwards from before `start_time` were added to `fees`.
+    fees_backfilled: bool,
+}
+
+impl ForwardingCache {
+    pub fn new() -> Self {
+        Self {
+            start_time: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system time after unix epoch")
+                .as_secs(),
+            index_offset: 0,
+            forwarded: HashMap::new(),
+            link_failed: HashMap::new(),
+            resolution_times: vec![],
+            fees: VecDeque::new(),
+            fees_backfilled: false,
+        }
+    }
+
+    /// Resolution times kept between scrapes, in case scrapes stop.
+    const MAX_RESOLUTION_TIMES: usize = 100_000;
+}
+
+/// Channels seen by the last listchannels scrape.
+#[derive(Default)]
+pub struct ChannelData {
+    /// Channel point by chan_id.
+    channel_points: HashMap<u64, String>,
+    /// Remote node of each channel.
+    remote_pubkeys: Vec<String>,
+}
+
+/// State scrapers share with each other and with the background tasks.
+struct SharedData {
+    channel_data: Arc<Mutex<ChannelData>>,
+    forwarding_cache: Arc<Mutex<ForwardingCache>>,
+    custom_message_cache: Arc<Mutex<CustomMessageCache>>,
+    peer_data: Arc<Mutex<PeerData>>,
+    node_info: Arc<Mutex<NodeInfo>>,
+    pending_open_cache: Arc<Mutex<PendingChannelOpenCache>>,
+}
+
+/// Identity of the node, from the last getinfo scrape.
+#[derive(Default)]
+pub struct NodeInfo {
+    self_pubkey: Option<String>,
+}
+
+/// Channel opens seen pending by the pendingchannels scrapes.
+#[derive(Default)]
+pub struct PendingChannelOpenCache {
+    /// When each pending open was first seen, by channel point. Opens already
+    /// pending at the first scrape started at an unknown time.
+    first_seen: HashMap<String, Option<Instant>>,
+    scraped: bool,
+    succeeded: u64,
+    failed: u64,
+}
+
+/// Peers seen by the last listpeers scrape.
+#[derive(Default)]
+pub struct PeerData {
+    /// Peers advertising route blinding in their init message.
+    blinded_paths_supported: HashSet<String>,
+}
+
+/// Custom peer messages received while subscribed, by message type.
+#[derive(Default)]
+pub struct CustomMessageCache {
+    /// Number of messages and unix timestamp of the last one by type, with the
+    /// types past [`CustomMessageCache::MAX_TYPES`] counted as `other`.
+    types: HashMap<String, (u64, u64)>,
+}
+
+impl CustomMessageCache {
+    /// Distinct message types tracked, since peers may send any type.
+    const MAX_TYPES: usize = 64;
+}
+
+#[derive(Default)]
+pub struct ChannelFlapCache {
+    /// Last seen active state and number of active state changes by chan_id.
+    channels: HashMap<u64, (bool, u64)>,
+}
+
+#[derive(Default)]
+pub struct HtlcAgeCache {
+    /// When each pending HTLC was first seen, by chan_id, direction and
+    /// htlc_index, since incoming and outgoing HTLCs are indexed separately.
+    first_seen: HashMap<(u64, bool, u64), Instant>,
+}
+
+/// Delay before the first retry of a scraper, doubled on each further retry.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Last failure of a scraper.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrapeFailure {
+    pub error: String,
+    /// Label of the failure in `exporter_scrape_errors_total`, or `timeout`.
+    pub code: &'static str,
+    pub timestamp_seconds: u64,
+}
+
+/// State of a collector served by the debug endpoint. It must not hold
+/// credentials, as the endpoint is only protected by the bearer token.
+#[derive(Debug, Serialize)]
+pub struct DebugStatus {
+    pub lnd_up: bool,
+    pub last_check_error: Option<String>,
+    pub reconnects: u64,
+    pub scrape_failures: BTreeMap<&'static str, ScrapeFailure>,
+    /// Last GetInfo response of lnd, as is.
+    pub getinfo: Option<lnrpc::GetInfoResponse>,
+}
+
+/// Health of a collector served by `/healthz`, which is open to probes and so
+/// only tells what lnd runs besides how it's doing.
+#[derive(Debug, Serialize)]
+pub struct HealthStatus {
+    pub lnd_reachable: bool,
+    pub last_check_latency_seconds: Option<f64>,
+    pub last_check_error: Option<String>,
+    pub lnd_version: Option<String>,
+    pub lnd_alias: Option<String>,
+    /// Last wallet state seen, only polled when waiting for unlock.
+    pub wallet_state: Option<&'static str>,
+    pub wallet_unlocked: bool,
+    pub last_scrape_success_timestamp_seconds: BTreeMap<&'static str, u64>,
+}
+
+impl HealthStatus {
+    pub fn healthy(&self) -> bool {
+        self.lnd_reachable && self.wallet_unlocked
+    }
+}
+
+/// Outcome of an lnd connectivity check.
+struct LastCheck {
+    checked_at: Instant,
+    latency: Duration,
+    res: Result<(), String>,
+}
+
+/// Options of [`LndCollector`] besides the lnd clients and metrics config.
+#[derive(Clone)]
+pub struct CollectorOptions {
+    pub enabled_scrapers: ScraperSet,
+    pub scraper_timeout: Duration,
+    pub scraper_retries: u32,
+    /// Keeps running the scrapers lnd refuses rather than disabling them.
+    pub strict_permissions: bool,
+    pub flap_threshold: u64,
+    pub top_destinations: usize,
+    /// Number of last succeeded payments the amount percentiles cover.
+    pub payment_distribution_window: usize,
+    pub invoice_proofs: bool,
+    pub block_explorer: Option<BlockExplorer>,
+    /// Client of the mempool scraper, which doesn't run without one.
+    pub bitcoind: Option<BitcoindClient>,
+    /// Interval to poll the wallet state at until lnd is unlocked, holding off
+    /// the scrapers meanwhile. Scrapers start right away if unset.
+    pub wait_unlock: Option<Duration>,
+    /// Whether the macaroon of each scraper was found to grant its
+    /// permissions at startup, for the scrapers lnd could check.
+    pub scraper_permissions: HashMap<ScraperName, bool>,
+    /// Scrapers whose every family is dropped don't run.
+    pub family_filter: Arc<FamilyFilter>,
+    /// Confirmation target of the close fee estimates.
+    pub close_fee_estimate_confs: i32,
+    /// Interval the close fee estimates are refreshed at.
+    pub close_fee_estimate_refresh: Duration,
+    /// Routing hubs to export the distance of.
+    pub hub_pubkeys: Vec<String>,
+    /// Time after which pending HTLCs are considered stuck.
+    pub stuck_htlc_threshold: Duration,
+    /// Dust relay fee rate of the Bitcoin network, in sat/vB.
+    pub dust_relay_fee_rate: u64,
+}
+
+/// Age up to which readiness checks reuse the outcome of the last GetInfo
+/// rather than calling lnd again.
+const READINESS_MAX_AGE: Duration = Duration::from_secs(15);
+
+/// Interval the wallet state is polled at while waiting for unlock.
+pub const WALLET_STATE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+impl Default for CollectorOptions {
+    fn default() -> Self {
+        Self {
+            enabled_scrapers: ScraperSet::default(),
+            scraper_timeout: Duration::from_secs(10),
+            scraper_retries: 2,
+            strict_permissions: false,
+            flap_threshold: 3,
+            top_destinations: 20,
+            payment_distribution_window: DEFAULT_PAYMENT_DISTRIBUTION_WINDOW,
+            invoice_proofs: false,
+            block_explorer: None,
+            bitcoind: None,
+            wait_unlock: Some(WALLET_STATE_POLL_INTERVAL),
+            scraper_permissions: HashMap::new(),
+            family_filter: Arc::new(FamilyFilter::default()),
+            close_fee_estimate_confs: 6,
+            close_fee_estimate_refresh: Duration::from_secs(300),
+            hub_pubkeys: vec![],
+            stuck_htlc_threshold: Duration::from_secs(3600),
+            dust_relay_fee_rate: 3,
+        }
+    }
+}
+
+/// Metrics of the collector along with the scrapers owning the rest of them,
+/// all built with the same constant labels.
+struct Instruments {
+    metrics_config: MetricsConfig,
+    exporter_metrics: ExporterMetrics,
+    derived_metrics: DerivedMetrics,
+    blinded_paths_metrics: BlindedPathsMetrics,
+    wallet_metrics: WalletMetrics,
+    /// Always run as the lnd connectivity check, but only exported if enabled.
+    getinfo: scrapers::GetInfoScraper,
+    scrapers: Vec<Box<dyn Scraper>>,
+}
+
+impl Instruments {
+    fn new(
+        options: &CollectorOptions,
+        shared: &SharedData,
+        denied_scrapers: &HashSet<&'static str>,
+        metrics_config: MetricsConfig,
+    ) -> Self {
+        let config = &metrics_config;
+        let SharedData {
+            channel_data,
+            forwarding_cache,
+            custom_message_cache,
+            peer_data,
+            node_info,
+            pending_open_cache,
+        } = shared;
+
+        let scrapers = ScraperName::ALL
+            .iter()
+            .filter(|name| options.enabled_scrapers.contains(**name))
+            .filter_map(|name| -> Option<Box<dyn Scraper>> {
+                match name {
+                    ScraperName::GetInfo => None,
+                    ScraperName::Payments => Some(Box::new(scrapers::PaymentsScraper::new(
+                        config,
+                        options.top_destinations,
+                        options.payment_distribution_window,
+                        node_info.clone(),
+                    ))),
+                    ScraperName::Channels => Some(Box::new(scrapers::ChannelsScraper::new(
+                        config,
+                        options.flap_threshold,
+                        options.stuck_htlc_threshold,
+                        options.dust_relay_fee_rate,
+                        channel_data.clone(),
+                    ))),
+                    ScraperName::Backup => Some(Box::new(scrapers::BackupScraper::new(config))),
+                    ScraperName::Graph => Some(Box::new(scrapers::GraphScraper::new(config))),
+                    ScraperName::Policies => Some(Box::new(scrapers::PoliciesScraper::new(config))),
+                    ScraperName::WalletBalance => {
+                        Some(Box::new(scrapers::WalletBalanceScraper::new(config)))
+                    }
+                    ScraperName::Transactions => {
+                        Some(Box::new(scrapers::TransactionsScraper::new(config)))
+                    }
+                    ScraperName::PendingChannels => Some(Box::new(
+                        scrapers::PendingChannelsScraper::new(config, pending_open_cache.clone()),
+                    )),
+                    ScraperName::Peers => Some(Box::new(scrapers::PeersScraper::new(
+                        config,
+                        peer_data.clone(),
+                    ))),
+                    ScraperName::Watchtower => {
+                        Some(Box::new(scrapers::WatchtowerScraper::new(config)))
+                    }
+                    ScraperName::Autopilot => {
+                        Some(Box::new(scrapers::AutopilotScraper::new(config)))
+                    }
+                    ScraperName::CloseFees => Some(Box::new(scrapers::CloseFeesScraper::new(
+                        config,
+                        options.close_fee_estimate_confs,
+                        options.close_fee_estimate_refresh,
+                    ))),
+                    ScraperName::HubDistances => Some(Box::new(
+                        scrapers::HubDistancesScraper::new(config, options.hub_pubkeys.clone()),
+                    )),
+                    ScraperName::CustomMessages => Some(Box::new(
+                        scrapers::CustomMessagesScraper::new(config, custom_message_cache.clone()),
+                    )),
+                    ScraperName::Invoices => Some(Box::new(scrapers::InvoicesScraper::new(
+                        config,
+                        options.invoice_proofs,
+                    ))),
+                    ScraperName::Health => Some(Box::new(scrapers::HealthScraper::new(config))),
+                    ScraperName::Forwarding => Some(Box::new(scrapers::ForwardingScraper::new(
+                        config,
+                        forwarding_cache.clone(),
+                    ))),
+                    ScraperName::Mempool => options.bitcoind.as_ref().map(|bitcoind| {
+                        Box::new(scrapers::MempoolScraper::new(config, bitcoind.clone()))
+                            as Box<dyn Scraper>
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let exporter_metrics = ExporterMetrics::new(config);
+        exporter_metrics
+            .build_info
+            .with_label_values(&[crate::VERSION, crate::REVISION, crate::RUSTC_VERSION])
+            .set(1);
+        for scraper in ScraperName::ALL {
+            let enabled = options.enabled_scrapers.contains(*scraper)
+                && !denied_scrapers.contains(scraper.as_str());
+            exporter_metrics
+                .scraper_enabled
+                .with_label_values(&[scraper.as_str()])
+                .set(enabled.into());
+
+            if let Some(permitted) = options.scraper_permissions.get(scraper) {
+                exporter_metrics
+                    .scraper_permitted
+                    .with_label_values(&[scraper.as_str()])
+                    .set((*permitted).into());
+            }
+
+            if options.enabled_scrapers.contains(*scraper) {
+                exporter_metrics
+                    .scrape_timeout_total
+                    .with_label_values(&[scraper.as_str()]);
+                exporter_metrics
+                    .rpc_retries_total
+                    .with_label_values(&[scraper.as_str()]);
+            }
+        }
+
+        Self {
+            exporter_metrics,
+            derived_metrics: DerivedMetrics::new(config),
+            blinded_paths_metrics: BlindedPathsMetrics::new(config),
+            wallet_metrics: WalletMetrics::new(config),
+            getinfo: scrapers::GetInfoScraper::new(
+                config,
+                options.block_explorer.clone(),
+                node_info.clone(),
+            ),
+            scrapers,
+            metrics_config,
+        }
+    }
+
+    fn metric_desc(&self, options: &CollectorOptions) -> Vec<Desc> {
+        let enabled_scrapers = &options.enabled_scrapers;
+
+        let mut metric_desc = self
+            .exporter_metrics
+            .desc()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        if enabled_scrapers.contains(ScraperName::GetInfo) {
+            metric_desc.extend(self.getinfo.desc().into_iter().cloned());
+        }
+        for scraper in self.scrapers.iter() {
+            metric_desc.extend(scraper.desc().into_iter().cloned());
+        }
+        if enabled_scrapers.contains(ScraperName::Channels)
+            && enabled_scrapers.contains(ScraperName::Forwarding)
+        {
+            metric_desc.extend(self.derived_metrics.desc().into_iter().cloned());
+        }
+        if enabled_scrapers.contains(ScraperName::Channels)
+            && enabled_scrapers.contains(ScraperName::Peers)
+        {
+            metric_desc.extend(self.blinded_paths_metrics.desc().into_iter().cloned());
+        }
+        if options.wait_unlock.is_some() {
+            metric_desc.extend(self.wallet_metrics.desc().into_iter().cloned());
+        }
+
+        metric_desc
+    }
 }
 
 pub struct LndCollector {
-    lnd_client: Arc<Mutex<LndClient>>,
+    lnd_clients: ScopedLndClientSet,
+    options: CollectorOptions,
+    /// Rebuilt when the node labels change, e.g. after reconnecting to another
+    /// node.
+    instruments: Arc<std::sync::RwLock<Arc<Instruments>>>,
     metric_desc: Vec<Desc>,
-    listpayments_cache: Arc<Mutex<ListPaymentsCache>>,
+    shared: SharedData,
+    /// Last wallet state seen while waiting for unlock.
+    wallet_state: Arc<std::sync::Mutex<Option<lnrpc::WalletState>>>,
+    /// Scrapers disabled after lnd refused their requests.
+    denied_scrapers: std::sync::Mutex<HashSet<&'static str>>,
+    /// Time and outcome of the last lnd connectivity check.
+    last_check: std::sync::Mutex<Option<LastCheck>>,
+    /// Number of times lnd answered again after failing a check.
+    reconnects: AtomicU64,
+    /// Last failure of each scraper, kept after it recovers.
+    scrape_failures: std::sync::Mutex<HashMap<&'static str, ScrapeFailure>>,
+    /// Time of the last success of each scraper, in seconds since the epoch.
+    scrape_successes: std::sync::Mutex<HashMap<&'static str, u64>>,
+    // Scrapers update shared metrics in place, so collects must not interleave
+    scrape_lock: Mutex<()>,
+    /// Background tasks feeding the collector, stopped when it is dropped.
+    tasks: Vec<tokio::task::JoinHandle<()>>,
 }
 
 impl LndCollector {
-    pub fn new(lnd_client: LndClient) -> Self {
+    pub fn new(
+        mut lnd_clients: ScopedLndClientSet,
+        options: CollectorOptions,
+        metrics_config: MetricsConfig,
+    ) -> Self {
+        let channel_data = Arc::new(Mutex::new(ChannelData::default()));
+        let forwarding_cache = Arc::new(Mutex::new(ForwardingCache::new()));
+        let mut tasks = vec![];
+        if options.enabled_scrapers.contains(ScraperName::Forwarding) {
+            tasks.push(tokio::spawn(subscriptions::subscribe_htlc_events(
+                lnd_clients.router(ScraperName::Forwarding),
+                forwarding_cache.clone(),
+            )));
+        }
+        let custom_message_cache = Arc::new(Mutex::new(CustomMessageCache::default()));
+        if options
+            .enabled_scrapers
+            .contains(ScraperName::CustomMessages)
+        {
+            tasks.push(tokio::spawn(subscriptions::subscribe_custom_messages(
+                lnd_clients.get(ScraperName::CustomMessages),
+                custom_message_cache.clone(),
+            )));
+        }
+
+        let wallet_state = Arc::new(std::sync::Mutex::new(None));
+        if let Some(poll_interval) = options.wait_unlock {
+            tasks.push(tokio::spawn(wallet::wait_for_unlock(
+                lnd_clients.state(),
+                poll_interval,
+                wallet_state.clone(),
+            )));
+        }
+
+        let shared = SharedData {
+            channel_data,
+            forwarding_cache,
+            custom_message_cache,
+            peer_data: Arc::new(Mutex::new(PeerData::default())),
+            node_info: Arc::new(Mutex::new(NodeInfo::default())),
+            pending_open_cache: Arc::new(Mutex::new(PendingChannelOpenCache::default())),
+        };
+        let instruments = Instruments::new(&options, &shared, &HashSet::new(), metrics_config);
+        let metric_desc = instruments.metric_desc(&options);
+        let instruments = Arc::new(std::sync::RwLock::new(Arc::new(instruments)));
+
+        let current = instruments.clone();
+        lnd_clients.observe_requests(Arc::new(move |method, code, duration| {
+            current
+                .read()
+                .expect("instruments lock")
+                .exporter_metrics
+                .grpc_request_duration_seconds
+                .with_label_values(&[method, &format!("{:?}", code)])
+                .observe(duration.as_secs_f64());
+        }));
+
         Self {
-            lnd_client: Arc::new(Mutex::new(lnd_client)),
-            metric_desc: vec![
-                metrics::num_peers_total().desc(),
-                metrics::block_height().desc(),
-                metrics::outgoing_payments().desc(),
-            ]
+            lnd_clients,
+            options,
+            instruments,
+            metric_desc,
+            shared,
+            wallet_state,
+            denied_scrapers: std::sync::Mutex::new(HashSet::new()),
+            last_check: std::sync::Mutex::new(None),
+            reconnects: AtomicU64::new(0),
+            scrape_failures: std::sync::Mutex::new(HashMap::new()),
+            scrape_successes: std::sync::Mutex::new(HashMap::new()),
+            scrape_lock: Mutex::new(()),
+            tasks,
+        }
+    }
+
+    fn current_instruments(&self) -> Arc<Instruments> {
+        self.instruments.read().expect("instruments lock").clone()
+    }
+
+    /// Whether lnd answered the last scrape.
+    pub fn lnd_up(&self) -> bool {
+        self.current_instruments().exporter_metrics.lnd_up.get() == 1
+    }
+
+    /// Alias of the node in its last GetInfo response, if any.
+    pub fn alias(&self) -> Option<String> {
+        self.current_instruments()
+            .getinfo
+            .node_labels()
+            .remove("node_alias")
+    }
+
+    /// Whether lnd answered the last connectivity check, with the reason if
+    /// not. The check only runs again, as a GetInfo-only scrape, once the last
+    /// one is older than [`READINESS_MAX_AGE`].
+    pub async fn ready(&self) -> Result<(), String> {
+        if let Some(last_check) = &*self.last_check.lock().expect("last check lock") {
+            if last_check.checked_at.elapsed() < READINESS_MAX_AGE {
+                return last_check.res.clone();
+            }
+        }
+
+        let getinfo_only = std::iter::once(ScraperName::GetInfo).collect();
+        self.collect_metrics(Some(&getinfo_only)).await;
+
+        match &*self.last_check.lock().expect("last check lock") {
+            Some(last_check) => last_check.res.clone(),
+            None => Err("lnd was not checked".to_string()),
+        }
+    }
+
+    fn set_last_check(&self, res: Result<(), String>, latency: Duration) {
+        let mut last_check = self.last_check.lock().expect("last check lock");
+        if matches!(&*last_check, Some(LastCheck { res: Err(_), .. })) && res.is_ok() {
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+        *last_check = Some(LastCheck {
+            checked_at: Instant::now(),
+            latency,
+            res,
+        });
+    }
+
+    /// State of the connection to lnd and of the scrapers, for debugging.
+    pub fn debug_status(&self) -> DebugStatus {
+        let last_check_error = match &*self.last_check.lock().expect("last check lock") {
+            Some(LastCheck { res: Err(e), .. }) => Some(e.clone()),
+            _ => None,
+        };
+
+        DebugStatus {
+            lnd_up: self.lnd_up(),
+            last_check_error,
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            scrape_failures: self
+                .scrape_failures
+                .lock()
+                .expect("scrape failures lock")
+                .iter()
+                .map(|(scraper, failure)| (*scraper, failure.clone()))
+                .collect(),
+            getinfo: self.current_instruments().getinfo.last_info(),
+        }
+    }
+
+    /// Health of lnd and of the scrapers, checking lnd again if the last check
+    /// is stale.
+    pub async fn health_status(&self) -> HealthStatus {
+        let lnd_reachable = self.ready().await.is_ok();
+        let (last_check_latency_seconds, last_check_error) =
+            match &*self.last_check.lock().expect("last check lock") {
+                Some(last_check) => (
+                    Some(last_check.latency.as_secs_f64()),
+                    last_check.res.clone().err(),
+                ),
+                None => (None, None),
+            };
+        let wallet_state = *self.wallet_state.lock().expect("wallet state lock");
+        let getinfo = self.current_instruments().getinfo.last_info();
+
+        HealthStatus {
+            lnd_reachable,
+            last_check_latency_seconds,
+            last_check_error,
+            lnd_version: getinfo.as_ref().map(|info| info.version.clone()),
+            lnd_alias: getinfo.map(|info| info.alias),
+            wallet_state: wallet_state.map(wallet::state_label),
+            // lnd only answers GetInfo once the wallet is unlocked
+            wallet_unlocked: match wallet_state {
+                Some(state) => wallet::is_active(state) && lnd_reachable,
+                None => lnd_reachable,
+            },
+            last_scrape_success_timestamp_seconds: self
+                .scrape_successes
+                .lock()
+                .expect("scrape successes lock")
+                .iter()
+                .map(|(scraper, timestamp)| (*scraper, *timestamp))
+                .collect(),
+        }
+    }
+
+    fn set_scrape_failure(&self, scraper: &'static str, error: String, code: &'static str) {
+        let timestamp_seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time after unix epoch")
+            .as_secs();
+
+        self.scrape_failures
+            .lock()
+            .expect("scrape failures lock")
+            .insert(
+                scraper,
+                ScrapeFailure {
+                    error,
+                    code,
+                    timestamp_seconds,
+                },
+            );
+    }
+
+    /// Runs a scraper, dropping (and thus cancelling) it if it takes longer than
+    /// the configured scraper timeout. Transient failures are retried with
+    /// exponential backoff within that same timeout. Returns the scraped
+    /// metrics on success.
+    async fn scrape(
+        &self,
+        instruments: &Instruments,
+        scraper: &dyn Scraper,
+    ) -> Option<Vec<MetricFamily>> {
+        let ctx = ScrapeContext {
+            lnd_clients: &self.lnd_clients,
+        };
+        let exporter_metrics = &instruments.exporter_metrics;
+        let scraper_timeout = self.options.scraper_timeout;
+
+        let start_time = std::time::Instant::now();
+        let res = tokio::time::timeout(scraper_timeout, async {
+            let mut backoff = RETRY_BACKOFF;
+
+            for _ in 0..self.options.scraper_retries {
+                match scraper.scrape(&ctx).await {
+                    Err(e) if is_transient(&e) => {
+                        log::warn!("Retrying {} scraper in {:?}", scraper.name(), backoff);
+                        exporter_metrics
+                            .scrape_errors_total
+                            .with_label_values(&[scraper.name(), error_code(&e)])
+                            .inc();
+                        exporter_metrics
+                            .rpc_retries_total
+                            .with_label_values(&[scraper.name()])
+                            .inc();
+
+                        tokio::time::sleep(backoff).await;
+                        backoff *= 2;
+                    }
+                    res => return res,
+                }
+            }
+
+            scraper.scrape(&ctx).await
+        })
+        .await;
+
+        exporter_metrics
+            .scrape_duration_seconds
+            .with_label_values(&[scraper.name()])
+            .set(start_time.elapsed().as_secs_f64());
+
+        let metrics = match res {
+            Ok(Ok(metrics)) => Some(metrics),
+            Ok(Err(e)) => {
+                exporter_metrics
+                    .scrape_errors_total
+                    .with_label_values(&[scraper.name(), error_code(&e)])
+                    .inc();
+                self.set_scrape_failure(scraper.name(), e.to_string(), error_code(&e));
+                if is_denied(&e) {
+                    self.deny(instruments, scraper.name(), &e);
+                }
+
+                None
+            }
+            Err(_) => {
+                log::error!(
+                    "Timed out collecting {} metrics after {}s",
+                    scraper.name(),
+                    scraper_timeout.as_secs_f64()
+                );
+                exporter_metrics
+                    .scrape_timeout_total
+                    .with_label_values(&[scraper.name()])
+                    .inc();
+                self.set_scrape_failure(
+                    scraper.name(),
+                    format!("timed out after {}s", scraper_timeout.as_secs_f64()),
+                    "timeout",
+                );
+
+                None
+            }
+        };
+
+        exporter_metrics
+            .scrape_success
+            .with_label_values(&[scraper.name()])
+            .set(metrics.is_some().into());
+        if metrics.is_some() {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system time after unix epoch");
+            exporter_metrics
+                .scraper_last_success_timestamp_seconds
+                .with_label_values(&[scraper.name()])
+                .set(now.as_secs_f64());
+            self.scrape_successes
+                .lock()
+                .expect("scrape successes lock")
+                .insert(scraper.name(), now.as_secs());
+        }
+
+        metrics
+    }
+
+    /// Runs the getinfo scraper, first rebuilding the instruments if the node
+    /// labels it reports differ from the ones they were built with.
+    async fn scrape_getinfo(
+        &self,
+        instruments: Arc<Instruments>,
+    ) -> (Arc<Instruments>, Option<Vec<MetricFamily>>) {
+        let getinfo = self.scrape(&instruments, &instruments.getinfo).await;
+        if getinfo.is_none() {
+            return (instruments, None);
+        }
+
+        let node_labels = instruments.getinfo.node_labels();
+        if instruments.metrics_config.has_const_labels(&node_labels) {
+            return (instruments, getinfo);
+        }
+
+        log::info!("lnd node labels changed to {:?}", node_labels);
+        let instruments = Arc::new(Instruments::new(
+            &self.options,
+            &self.shared,
+            &self.denied_scrapers.lock().expect("denied scrapers lock"),
+            instruments.metrics_config.with_const_labels(node_labels),
+        ));
+        *self.instruments.write().expect("instruments lock") = instruments.clone();
+
+        let getinfo = self.scrape(&instruments, &instruments.getinfo).await;
+        (instruments, getinfo)
+    }
+
+    /// Disables a scraper lnd refused to serve for the rest of the process
+    /// lifetime. Strict permissions are checked at startup instead, so the
+    /// refusals are left counted as scrape errors. GetInfo keeps running as
+    /// the connectivity check.
+    fn deny(&self, instruments: &Instruments, scraper: &'static str, e: &ScrapeError) {
+        if self.options.strict_permissions || scraper == ScraperName::GetInfo.as_str() {
+            return;
+        }
+
+        log::warn!(
+            "lnd refused {} scraper requests, disabling it: {}",
+            scraper,
+            e
+        );
+        self.denied_scrapers
+            .lock()
+            .expect("denied scrapers lock")
+            .insert(scraper);
+        instruments
+            .exporter_metrics
+            .scraper_enabled
+            .with_label_values(&[scraper])
+            .set(0);
+    }
+
+    /// Whether lnd serves macaroon authenticated requests, updating the wallet
+    /// state metric with the last state seen.
+    fn wallet_ready(&self) -> bool {
+        let wallet_state = *self.wallet_state.lock().expect("wallet state lock");
+        let instruments = self.current_instruments();
+
+        instruments.wallet_metrics.reset();
+        if let Some(state) = wallet_state {
+            instruments
+                .wallet_metrics
+                .wallet_state
+                .with_label_values(&[wallet::state_label(state)])
+                .set(1);
+        }
+
+        matches!(wallet_state, Some(state) if wallet::is_active(state))
+    }
+
+    fn is_denied(&self, scraper: &dyn Scraper) -> bool {
+        self.denied_scrapers
+            .lock()
+            .expect("denied scrapers lock")
+            .contains(scraper.name())
+    }
+
+    /// Whether any family of the scraper is kept by the family filter.
+    fn is_kept(&self, scraper: &dyn Scraper) -> bool {
+        scraper
+            .desc()
+            .iter()
+            .any(|desc| self.options.family_filter.keeps(&desc.fq_name))
+    }
+
+    /// Runs a scrape of lnd, returning the families collected. Only the
+    /// scrapers of `filter` run, if any, besides GetInfo.
+    async fn collect_metrics(&self, filter: Option<&ScraperSet>) -> Vec<MetricFamily> {
+        let selected = |name: &str| match filter {
+            Some(filter) => filter.contains_name(name),
+            None => true,
+        };
+
+        let _scrape_lock = self.scrape_lock.lock().await;
+        let start_time = std::time::Instant::now();
+        self.lnd_clients.next_scrape();
+
+        if self.options.wait_unlock.is_some() && !self.wallet_ready() {
+            log::warn!("lnd wallet is not ready yet, skipping scrapers");
+            self.set_last_check(
+                Err("lnd wallet is not ready".to_string()),
+                start_time.elapsed(),
+            );
+
+            let instruments = self.current_instruments();
+            return instruments
+                .wallet_metrics
+                .collect()
+                .into_iter()
+                .chain(instruments.exporter_metrics.collect())
+                .collect();
+        }
+
+        // GetInfo doubles as the lnd connectivity check, so it always runs
+        let (instruments, getinfo) = self.scrape_getinfo(self.current_instruments()).await;
+        let lnd_up = getinfo.is_some();
+        instruments.exporter_metrics.lnd_up.set(lnd_up.into());
+        self.set_last_check(
+            if lnd_up {
+                Ok(())
+            } else {
+                Err("lnd did not answer GetInfo".to_string())
+            },
+            start_time.elapsed(),
+        );
+
+        let scraped = if lnd_up {
+            futures_util::future::join_all(
+                instruments
+                    .scrapers
+                    .iter()
+                    .filter(|scraper| {
+                        selected(scraper.name())
+                            && self.is_kept(scraper.as_ref())
+                            && !self.is_denied(scraper.as_ref())
+                    })
+                    .map(|scraper| {
+                        let instruments = &instruments;
+                        async move {
+                            (
+                                scraper.name(),
+                                self.scrape(instruments, scraper.as_ref()).await,
+                            )
+                        }
+                    }),
+            )
+            .await
+        } else {
+            log::warn!("lnd is down, skipping scrapers");
+
+            vec![]
+        };
+
+        log::info!("Scraped lnd in {}s", start_time.elapsed().as_secs_f64());
+
+        let succeeded = |name: ScraperName| {
+            scraped
+                .iter()
+                .any(|(scraper, metrics)| *scraper == name.as_str() && metrics.is_some())
+        };
+
+        // Metrics joining the data of several scrapers
+        let derived = if succeeded(ScraperName::Channels) && succeeded(ScraperName::Forwarding) {
+            derived::compute_derived_metrics(
+                &instruments.derived_metrics,
+                &*self.shared.channel_data.lock().await,
+                &*self.shared.forwarding_cache.lock().await,
+            )
+        } else {
+            vec![]
+        };
+
+        let blinded_paths = if succeeded(ScraperName::Channels) && succeeded(ScraperName::Peers) {
+            derived::compute_blinded_paths_metrics(
+                &instruments.blinded_paths_metrics,
+                &*self.shared.channel_data.lock().await,
+                &*self.shared.peer_data.lock().await,
+            )
+        } else {
+            vec![]
+        };
+
+        let wallet = if self.options.wait_unlock.is_some() {
+            instruments.wallet_metrics.collect()
+        } else {
+            vec![]
+        };
+
+        // Families of failed scrapers are left out rather than exported stale
+        getinfo
+            .filter(|_| {
+                self.options.enabled_scrapers.contains(ScraperName::GetInfo)
+                    && selected(ScraperName::GetInfo.as_str())
+            })
             .into_iter()
+            .chain(scraped.into_iter().filter_map(|(_, metrics)| metrics))
             .flatten()
-            .cloned()
-            .collect(),
-            listpayments_cache: Arc::new(Mutex::new(ListPaymentsCache {
-                index_offset: 0,
-                outgoing_payments: HashMap::new(),
-                payment_failure_reasons: HashMap::new(),
-                total_fee_msat: 0,
-            })),
+            .chain(derived)
+            .chain(blinded_paths)
+            .chain(wallet)
+            .chain(instruments.exporter_metrics.collect())
+            .collect()
+    }
+}
+
+/// Whether lnd will keep refusing the request, either for lack of macaroon
+/// permissions or because the sub-server is not compiled in.
+fn is_denied(e: &ScrapeError) -> bool {
+    match e {
+        ScrapeError::Lnd(status) => is_denied_status(status),
+        ScrapeError::Bitcoind(_) => false,
+    }
+}
+
+fn is_denied_status(status: &lnrpc::Status) -> bool {
+    matches!(
+        status.code(),
+        lnrpc::Code::PermissionDenied | lnrpc::Code::Unimplemented
+    )
+}
+
+/// Label of the error in `exporter_scrape_errors_total`: the canonical name of
+/// the gRPC status code, or `transport` when lnd could not be reached at all.
+/// Bitcoin Core errors take the closest gRPC code.
+fn error_code(e: &ScrapeError) -> &'static str {
+    let status = match e {
+        ScrapeError::Lnd(status) => status,
+        ScrapeError::Bitcoind(e) => return bitcoind_error_code(e.as_ref()),
+    };
+
+    // Statuses built from a client side error rather than sent by lnd
+    if status.code() == lnrpc::Code::Unknown && std::error::Error::source(status).is_some() {
+        return "transport";
+    }
+
+    match status.code() {
+        lnrpc::Code::Ok => "ok",
+        lnrpc::Code::Cancelled => "cancelled",
+        lnrpc::Code::Unknown => "unknown",
+        lnrpc::Code::InvalidArgument => "invalid_argument",
+        lnrpc::Code::DeadlineExceeded => "deadline_exceeded",
+        lnrpc::Code::NotFound => "not_found",
+        lnrpc::Code::AlreadyExists => "already_exists",
+        lnrpc::Code::PermissionDenied => "permission_denied",
+        lnrpc::Code::ResourceExhausted => "resource_exhausted",
+        lnrpc::Code::FailedPrecondition => "failed_precondition",
+        lnrpc::Code::Aborted => "aborted",
+        lnrpc::Code::OutOfRange => "out_of_range",
+        lnrpc::Code::Unimplemented => "unimplemented",
+        lnrpc::Code::Internal => "internal",
+        lnrpc::Code::Unavailable => "unavailable",
+        lnrpc::Code::DataLoss => "data_loss",
+        lnrpc::Code::Unauthenticated => "unauthenticated",
+    }
+}
+
+fn bitcoind_error_code(e: &(dyn std::error::Error + 'static)) -> &'static str {
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(e) if e.is_timeout() => "deadline_exceeded",
+        Some(e) if e.is_connect() => "transport",
+        _ => "unknown",
+    }
+}
+
+/// Whether the error is likely to go away on its own, e.g. while lnd restarts.
+fn is_transient(e: &ScrapeError) -> bool {
+    match e {
+        ScrapeError::Lnd(status) => matches!(
+            status.code(),
+            lnrpc::Code::Unavailable | lnrpc::Code::DeadlineExceeded
+        ),
+        // The bitcoind client bounds its own requests, which leaves no time to retry
+        ScrapeError::Bitcoind(_) => false,
+    }
+}
+
+impl Drop for LndCollector {
+    fn drop(&mut self) {
+        for task in self.tasks.iter() {
+            task.abort();
         }
     }
 }
@@ -54,30 +1478,76 @@ impl Collector for LndCollector {
     fn collect(&self) -> Vec<MetricFamily> {
         log::info!("Collecting metrics");
 
-        let lnd_client = self.lnd_client.clone();
-        let listpayments_cache = self.listpayments_cache.clone();
-
         log::debug!("Building Tokio runtime");
         let rt = tokio::runtime::Handle::current();
 
-        let metrics = rt.block_on(async {
-            // Prevent concurrent collects
-            log::debug!("Acquiring collector locks");
-            let mut lnd_client_lock = lnd_client.lock().await;
-            let mut listpayments_cache_lock = listpayments_cache.lock().await;
-            let mut metrics = vec![];
-
-            metrics.extend(scappers::scrape_getinfo(&mut lnd_client_lock).await);
-            metrics.extend(
-                scappers::scrape_listpayments(&mut lnd_client_lock, &mut listpayments_cache_lock)
-                    .await,
-            );
-            metrics.extend(scappers::scrape_listchannels(&mut lnd_client_lock).await);
-
-            metrics
-        });
+        let filter = scraper_filter();
+        let metrics = rt.block_on(self.collect_metrics(filter.as_ref()));
 
         log::info!("Done collecting metrics");
         metrics
     }
 }
+
+/// Lets a registry collect a collector shared with the rest of the exporter.
+pub struct SharedCollector(pub Arc<LndCollector>);
+
+impl Collector for SharedCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.0.desc()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.0.collect()
+    }
+}
+
+/// Collects the metrics of several lnd nodes at once, each labeled by its own
+/// [`LndCollector`], so that a node failing leaves the others unaffected.
+pub struct MultiNodeCollector {
+    collectors: Vec<Arc<LndCollector>>,
+    /// Descriptors of the first node describing each family. Nodes may differ
+    /// in their node labels, e.g. when down at startup, which the registry
+    /// would refuse.
+    metric_desc: Vec<Desc>,
+}
+
+impl MultiNodeCollector {
+    pub fn new(collectors: Vec<Arc<LndCollector>>) -> Self {
+        let mut names = HashSet::new();
+        let metric_desc = collectors
+            .iter()
+            .flat_map(|collector| collector.metric_desc.iter())
+            .filter(|desc| names.insert(desc.fq_name.clone()))
+            .cloned()
+            .collect();
+
+        Self {
+            collectors,
+            metric_desc,
+        }
+    }
+}
+
+impl Collector for MultiNodeCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.metric_desc.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        log::info!("Collecting metrics of {} nodes", self.collectors.len());
+
+        let rt = tokio::runtime::Handle::current();
+        let filter = scraper_filter();
+
+        // Families of the same name are merged by the registry
+        let metrics = rt.block_on(futures_util::future::join_all(
+            self.collectors
+                .iter()
+                .map(|collector| collector.collect_metrics(filter.as_ref())),
+        ));
+
+        log::info!("Done collecting metrics");
+        metrics.into_iter().flatten().collect()
+    }
+}
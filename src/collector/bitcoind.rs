@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+/// Upper bound on Bitcoin Core RPC requests, so a busy node does not hold the
+/// mempool scraper until the scraper timeout.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Mempool state as reported by Bitcoin Core's `getmempoolinfo`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MempoolInfo {
+    pub tx_count: u64,
+    pub vbytes: u64,
+    /// Minimum fee rate for a transaction to be accepted, in BTC/kvB.
+    pub min_fee_btc_per_kvb: f64,
+}
+
+/// Client of a Bitcoin Core JSON-RPC endpoint.
+#[derive(Clone)]
+pub struct BitcoindClient {
+    client: reqwest::Client,
+    url: String,
+    user: Option<String>,
+    pass: Option<String>,
+}
+
+impl BitcoindClient {
+    pub fn new(url: String, user: Option<String>, pass: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("bitcoind http client"),
+            url,
+            user,
+            pass,
+        }
+    }
+
+    pub async fn mempool_info(&self) -> Result<MempoolInfo, Error> {
+        let result = self.call("getmempoolinfo").await?;
+
+        let field = |name: &str| {
+            result
+                .get(name)
+                .ok_or_else(|| format!("getmempoolinfo response missing {:?}", name))
+        };
+
+        Ok(MempoolInfo {
+            tx_count: field("size")?.as_u64().ok_or("invalid mempool size")?,
+            vbytes: field("bytes")?.as_u64().ok_or("invalid mempool bytes")?,
+            min_fee_btc_per_kvb: field("mempoolminfee")?
+                .as_f64()
+                .ok_or("invalid mempool min fee")?,
+        })
+    }
+
+    async fn call(&self, method: &str) -> Result<serde_json::Value, Error> {
+        let mut req = self.client.post(&self.url).json(&serde_json::json!({
+            "jsonrpc": "1.0",
+            "id": "lnd-exporter",
+            "method": method,
+            "params": [],
+        }));
+        if let Some(user) = &self.user {
+            req = req.basic_auth(user, self.pass.as_ref());
+        }
+
+        // Bitcoin Core answers RPC errors with a non-2xx status and a JSON body,
+        // but authentication errors with an empty one
+        let res = req.send().await?;
+        let status = res.status();
+
+        match res.json::<serde_json::Value>().await {
+            Ok(body) if !body["error"].is_null() => {
+                Err(format!("{} failed: {}", method, body["error"]).into())
+            }
+            Ok(mut body) if status.is_success() => Ok(body["result"].take()),
+            Err(e) if status.is_success() => Err(e.into()),
+            _ => Err(format!("bitcoind responded with {}", status).into()),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+/// Upper bound on block explorer requests, so a slow explorer does not make the
+/// getinfo scraper, and thus `lnd_up`, time out.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Client of a block explorer API endpoint returning the chain tip height as
+/// plain text, like mempool.space's `/api/blocks/tip/height`.
+#[derive(Clone)]
+pub struct BlockExplorer {
+    client: hyper::Client<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>,
+    tip_height_url: hyper::Uri,
+}
+
+impl BlockExplorer {
+    pub fn new(tip_height_url: hyper::Uri) -> Self {
+        Self {
+            client: hyper::Client::builder()
+                .build(hyper_rustls::HttpsConnector::with_native_roots()),
+            tip_height_url,
+        }
+    }
+
+    pub async fn tip_height(&self) -> Result<u32, Error> {
+        let res = tokio::time::timeout(
+            REQUEST_TIMEOUT,
+            self.client.get(self.tip_height_url.clone()),
+        )
+        .await??;
+
+        if !res.status().is_success() {
+            return Err(format!("block explorer responded with {}", res.status()).into());
+        }
+
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+
+        Ok(std::str::from_utf8(&body)?.trim().parse()?)
+    }
+}
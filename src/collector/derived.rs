@@ -0,0 +1,50 @@
+use prometheus::{core::Collector, proto::MetricFamily};
+
+use super::{
+    metrics::{BlindedPathsMetrics, DerivedMetrics},
+    ChannelData, ForwardingCache, PeerData,
+};
+
+/// Computes the metrics joining the channels and forwarding scrapers data. Must
+/// only be called after both scrapers succeeded.
+pub fn compute_derived_metrics(
+    metrics: &DerivedMetrics,
+    channels: &ChannelData,
+    forwarding: &ForwardingCache,
+) -> Vec<MetricFamily> {
+    metrics.reset();
+
+    for (chan_id, channel_point) in channels.channel_points.iter() {
+        let forwarded = forwarding.forwarded.get(chan_id).copied().unwrap_or(0);
+        let failed = forwarding.link_failed.get(chan_id).copied().unwrap_or(0);
+        if forwarded + failed == 0 {
+            continue;
+        }
+
+        metrics
+            .channel_routing_efficiency_ratio
+            .with_label_values(&[&chan_id.to_string(), channel_point])
+            .set(forwarded as f64 / (forwarded + failed) as f64);
+    }
+
+    metrics.collect()
+}
+
+/// Computes the metrics joining the channels and peers scrapers data. Must only
+/// be called after both scrapers succeeded.
+pub fn compute_blinded_paths_metrics(
+    metrics: &BlindedPathsMetrics,
+    channels: &ChannelData,
+    peers: &PeerData,
+) -> Vec<MetricFamily> {
+    let supported = channels
+        .remote_pubkeys
+        .iter()
+        .filter(|pubkey| peers.blinded_paths_supported.contains(*pubkey))
+        .count();
+    metrics
+        .channels_blinded_paths_supported_total
+        .set(supported as i64);
+
+    metrics.collect()
+}
@@ -0,0 +1,83 @@
+use std::collections::VecDeque;
+
+use hdrhistogram::Histogram;
+
+/// Significant figures the amounts are kept with, i.e. quantiles are off by
+/// at most 0.1%.
+const SIGNIFICANT_FIGURES: u8 = 3;
+
+/// Distribution of the amounts of the last payments, from which quantiles are
+/// read.
+///
+/// HDR histograms can't forget values, so the histogram is rebuilt from the
+/// window when read after it changed.
+pub struct PaymentAmountDistribution {
+    capacity: usize,
+    window: VecDeque<u64>,
+    histogram: Histogram<u64>,
+    stale: bool,
+}
+
+impl PaymentAmountDistribution {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "distribution of at least one amount");
+
+        Self {
+            capacity,
+            window: VecDeque::with_capacity(capacity),
+            histogram: Histogram::new(SIGNIFICANT_FIGURES).expect("valid histogram"),
+            stale: false,
+        }
+    }
+
+    pub fn push(&mut self, amount_msat: u64) {
+        if self.window.len() == self.capacity {
+            self.window.pop_front();
+        }
+        self.window.push_back(amount_msat);
+        self.stale = true;
+    }
+
+    /// Amount below which the given fraction of the window falls.
+    pub fn quantile(&mut self, quantile: f64) -> Option<u64> {
+        if self.window.is_empty() {
+            return None;
+        }
+
+        if self.stale {
+            self.histogram.reset();
+            for amount_msat in self.window.iter() {
+                // Resizes to fit the amount, only failing past i64::MAX / 2
+                if self.histogram.record(*amount_msat).is_err() {
+                    self.histogram.saturating_record(*amount_msat);
+                }
+            }
+            self.stale = false;
+        }
+
+        Some(self.histogram.value_at_quantile(quantile))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantiles_cover_the_window() {
+        let mut distribution = PaymentAmountDistribution::new(100);
+        assert_eq!(distribution.quantile(0.5), None);
+
+        // Amounts leaving the window no longer count
+        for _ in 0..100 {
+            distribution.push(1_000_000_000);
+        }
+        for amount_msat in 1..=100 {
+            distribution.push(amount_msat * 1_000);
+        }
+
+        assert_eq!(distribution.quantile(0.5), Some(50_015));
+        assert_eq!(distribution.quantile(0.9), Some(90_047));
+        assert_eq!(distribution.quantile(0.99), Some(99_007));
+    }
+}
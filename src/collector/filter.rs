@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use prometheus::proto::MetricFamily;
+use regex::Regex;
+
+/// Selects the families exported by name, from `--metric-drop` and
+/// `--metric-keep`. Like Prometheus relabeling, regexes match whole names.
+///
+/// Families matching the keep regex are always kept. Otherwise they are
+/// dropped if they match the drop regex or, without one, if there is a keep
+/// regex at all.
+#[derive(Default)]
+pub struct FamilyFilter {
+    drop: Option<Regex>,
+    keep: Option<Regex>,
+    /// Whether the dropped families were logged already.
+    logged: AtomicBool,
+}
+
+impl FamilyFilter {
+    pub fn new(drop: Option<&str>, keep: Option<&str>) -> Result<Self, regex::Error> {
+        let anchored = |re: &str| Regex::new(&format!("^(?:{})$", re));
+
+        Ok(Self {
+            drop: drop.map(anchored).transpose()?,
+            keep: keep.map(anchored).transpose()?,
+            logged: AtomicBool::new(false),
+        })
+    }
+
+    pub fn keeps(&self, name: &str) -> bool {
+        match (&self.drop, &self.keep) {
+            (_, Some(keep)) if keep.is_match(name) => true,
+            (Some(drop), _) => !drop.is_match(name),
+            (None, Some(_)) => false,
+            (None, None) => true,
+        }
+    }
+
+    /// Removes the dropped families, logging them the first time.
+    pub fn retain(&self, ms: &mut Vec<MetricFamily>) {
+        let mut dropped = vec![];
+        ms.retain(|mf| {
+            let keep = self.keeps(mf.get_name());
+            if !keep {
+                dropped.push(mf.get_name().to_string());
+            }
+            keep
+        });
+
+        if !dropped.is_empty() && !self.logged.swap(true, Ordering::Relaxed) {
+            log::debug!("Dropped metric families: {}", dropped.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_wins_over_drop() {
+        let filter = FamilyFilter::new(Some("lnd_channel_.*"), None).expect("valid regexes");
+        assert!(!filter.keeps("lnd_channel_liquidity_score"));
+        assert!(filter.keeps("lnd_up"));
+        // Names must match as a whole
+        let filter = FamilyFilter::new(Some("lnd_channel"), None).expect("valid regexes");
+        assert!(filter.keeps("lnd_channel_liquidity_score"));
+
+        let filter = FamilyFilter::new(None, Some("lnd_up|lnd_channel_.*")).expect("valid regexes");
+        assert!(filter.keeps("lnd_channel_liquidity_score"));
+        assert!(!filter.keeps("lnd_num_peers_total"));
+
+        let filter = FamilyFilter::new(Some("lnd_channel_.*"), Some("lnd_channel_balance_.*"))
+            .expect("valid regexes");
+        assert!(filter.keeps("lnd_channel_balance_total_sat"));
+        assert!(!filter.keeps("lnd_channel_liquidity_score"));
+        assert!(filter.keeps("lnd_num_peers_total"));
+
+        assert!(FamilyFilter::new(Some("lnd_("), None).is_err());
+    }
+}
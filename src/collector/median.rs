@@ -0,0 +1,170 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+/// Median of the last values pushed, kept in two heaps so that each push costs
+/// O(log n) rather than sorting the window on every scrape.
+///
+/// Values leaving the window are removed lazily, when they reach the top of
+/// their heap.
+pub struct SlidingMedian {
+    capacity: usize,
+    window: VecDeque<i64>,
+    /// Lower half of the window, holding the extra value when odd.
+    low: BinaryHeap<i64>,
+    high: BinaryHeap<Reverse<i64>>,
+    /// Number of values in each heap still in the window.
+    low_len: usize,
+    high_len: usize,
+    /// Values left the window but not removed from their heap yet.
+    removed: HashMap<i64, usize>,
+}
+
+impl SlidingMedian {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "sliding median of at least one value");
+
+        Self {
+            capacity,
+            window: VecDeque::with_capacity(capacity),
+            low: BinaryHeap::new(),
+            high: BinaryHeap::new(),
+            low_len: 0,
+            high_len: 0,
+            removed: HashMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: i64) {
+        if self.window.len() == self.capacity {
+            let oldest = self.window.pop_front().expect("full window");
+            self.remove(oldest);
+        }
+        self.window.push_back(value);
+
+        match self.low.peek() {
+            Some(low) if value > *low => {
+                self.high.push(Reverse(value));
+                self.high_len += 1;
+            }
+            _ => {
+                self.low.push(value);
+                self.low_len += 1;
+            }
+        }
+        self.rebalance();
+    }
+
+    /// Median of the window, rounded down to the closest integer when it holds
+    /// an even number of values.
+    pub fn median(&self) -> Option<i64> {
+        let low = *self.low.peek()?;
+        if self.low_len > self.high_len {
+            return Some(low);
+        }
+
+        let Reverse(high) = *self.high.peek().expect("balanced heaps");
+        Some(low + (high - low) / 2)
+    }
+
+    fn remove(&mut self, value: i64) {
+        *self.removed.entry(value).or_default() += 1;
+
+        if matches!(self.low.peek(), Some(low) if value <= *low) {
+            self.low_len -= 1;
+            if self.low.peek() == Some(&value) {
+                self.prune_low();
+            }
+        } else {
+            self.high_len -= 1;
+            if self.high.peek() == Some(&Reverse(value)) {
+                self.prune_high();
+            }
+        }
+        self.rebalance();
+    }
+
+    fn rebalance(&mut self) {
+        if self.low_len > self.high_len + 1 {
+            let value = self.low.pop().expect("non-empty low heap");
+            self.high.push(Reverse(value));
+            self.low_len -= 1;
+            self.high_len += 1;
+            self.prune_low();
+        } else if self.low_len < self.high_len {
+            let Reverse(value) = self.high.pop().expect("non-empty high heap");
+            self.low.push(value);
+            self.high_len -= 1;
+            self.low_len += 1;
+            self.prune_high();
+        }
+    }
+
+    fn prune_low(&mut self) {
+        while let Some(value) = self.low.peek() {
+            if !take_removed(&mut self.removed, *value) {
+                break;
+            }
+            self.low.pop();
+        }
+    }
+
+    fn prune_high(&mut self) {
+        while let Some(Reverse(value)) = self.high.peek() {
+            if !take_removed(&mut self.removed, *value) {
+                break;
+            }
+            self.high.pop();
+        }
+    }
+}
+
+/// Consumes one pending removal of the value, if any.
+fn take_removed(removed: &mut HashMap<i64, usize>, value: i64) -> bool {
+    match removed.get_mut(&value) {
+        Some(count) => {
+            *count -= 1;
+            if *count == 0 {
+                removed.remove(&value);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted_median(values: &[i64]) -> Option<i64> {
+        let mut values = values.to_vec();
+        values.sort_unstable();
+
+        let mid = values.len() / 2;
+        match values.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(values[mid]),
+            _ => Some(values[mid - 1] + (values[mid] - values[mid - 1]) / 2),
+        }
+    }
+
+    #[test]
+    fn median_matches_sorted_window() {
+        let capacity = 7;
+        let mut median = SlidingMedian::new(capacity);
+        assert_eq!(median.median(), None);
+
+        // Deterministic values with plenty of duplicates
+        let values = (0..200_i64)
+            .map(|i| (i * 7_919) % 23 - (i % 5) * 3)
+            .collect::<Vec<_>>();
+        for (i, value) in values.iter().enumerate() {
+            median.push(*value);
+
+            let window = &values[(i + 1).saturating_sub(capacity)..=i];
+            assert_eq!(median.median(), sorted_median(window), "at {}", i);
+        }
+    }
+}
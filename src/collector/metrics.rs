@@ -1,19 +1,408 @@
-pub fn num_peers_total() -> prometheus::IntGauge {
-    prometheus::IntGauge::new(
-        "lnd_num_peers_total",
+use std::collections::HashMap;
+
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+/// Name prefix and constant labels shared by every metric of the exporter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsConfig {
+    pub namespace: String,
+    pub const_labels: HashMap<String, String>,
+}
+
+impl MetricsConfig {
+    pub fn new(namespace: &str) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            const_labels: HashMap::new(),
+        }
+    }
+
+    /// Returns the config with the given labels added, replacing the values of
+    /// labels it already has.
+    pub fn with_const_labels(&self, labels: HashMap<String, String>) -> Self {
+        let mut config = self.clone();
+        config.const_labels.extend(labels);
+        config
+    }
+
+    /// Whether every one of the given labels has the same value in the config.
+    pub fn has_const_labels(&self, labels: &HashMap<String, String>) -> bool {
+        labels
+            .iter()
+            .all(|(name, value)| self.const_labels.get(name) == Some(value))
+    }
+
+    pub fn opts(&self, name: &str, help: &str) -> prometheus::Opts {
+        prometheus::Opts::new(name, help)
+            .namespace(self.namespace.clone())
+            .const_labels(self.const_labels.clone())
+    }
+}
+
+/// Clears the values set by a previous scrape.
+pub trait Reset {
+    fn reset(&self);
+}
+
+impl Reset for prometheus::IntGauge {
+    fn reset(&self) {
+        self.set(0);
+    }
+}
+
+impl Reset for prometheus::Gauge {
+    fn reset(&self) {
+        self.set(0.0);
+    }
+}
+
+// Histograms accumulate observations across scrapes
+impl Reset for prometheus::Histogram {
+    fn reset(&self) {}
+}
+
+impl<T: prometheus::core::MetricVecBuilder> Reset for prometheus::core::MetricVec<T> {
+    fn reset(&self) {
+        prometheus::core::MetricVec::reset(self);
+    }
+}
+
+/// Declares a struct grouping the metrics emitted by a scraper. The group is
+/// the single source of truth for both the descriptors the collector reports
+/// and the families the scraper emits.
+macro_rules! metric_group {
+    ($(#[$attr:meta])* $name:ident { $($field:ident: $ty:ty = $ctor:ident,)* }) => {
+        $(#[$attr])*
+        pub struct $name {
+            $(pub $field: $ty,)*
+        }
+
+        impl $name {
+            pub fn new(config: &MetricsConfig) -> Self {
+                Self {
+                    $($field: $ctor(config),)*
+                }
+            }
+
+            /// Clears every metric in the group. Not meant for groups holding
+            /// counters, which must never go backwards.
+            #[allow(dead_code)]
+            pub fn reset(&self) {
+                $(Reset::reset(&self.$field);)*
+            }
+        }
+
+        impl Collector for $name {
+            fn desc(&self) -> Vec<&Desc> {
+                let mut desc = vec![];
+                $(desc.extend(self.$field.desc());)*
+                desc
+            }
+
+            fn collect(&self) -> Vec<MetricFamily> {
+                let mut metrics = vec![];
+                $(metrics.extend(self.$field.collect());)*
+                metrics
+            }
+        }
+    };
+}
+
+metric_group!(ExporterMetrics {
+    build_info: prometheus::IntGaugeVec = build_info,
+    lnd_up: prometheus::IntGauge = lnd_up,
+    scrape_timeout_total: prometheus::IntCounterVec = scrape_timeout_total,
+    scrape_duration_seconds: prometheus::GaugeVec = scrape_duration_seconds,
+    scrape_success: prometheus::IntGaugeVec = scrape_success,
+    scraper_last_success_timestamp_seconds: prometheus::GaugeVec =
+        scraper_last_success_timestamp_seconds,
+    grpc_request_duration_seconds: prometheus::HistogramVec = grpc_request_duration_seconds,
+    rpc_retries_total: prometheus::IntCounterVec = rpc_retries_total,
+    scrape_errors_total: prometheus::IntCounterVec = scrape_errors_total,
+    scraper_enabled: prometheus::IntGaugeVec = scraper_enabled,
+    scraper_permitted: prometheus::IntGaugeVec = scraper_permitted,
+});
+
+metric_group!(
+    /// Emitted when waiting for the lnd wallet to be unlocked.
+    WalletMetrics {
+        wallet_state: prometheus::IntGaugeVec = wallet_state,
+    }
+);
+
+metric_group!(GetInfoMetrics {
+    num_peers_total: prometheus::IntGauge = num_peers_total,
+    block_height: prometheus::IntGauge = block_height,
+    node_feature_bit: prometheus::IntGaugeVec = node_feature_bit,
+    node_feature_count_total: prometheus::IntGauge = node_feature_count_total,
+    node_blinded_paths_supported: prometheus::IntGauge = node_blinded_paths_supported,
+});
+
+metric_group!(
+    /// Emitted along getinfo when a block explorer is configured.
+    SyncProgressMetrics {
+        chain_sync_progress_percent: prometheus::Gauge = chain_sync_progress_percent,
+    }
+);
+
+metric_group!(PaymentsMetrics {
+    outgoing_payments: prometheus::IntGaugeVec = outgoing_payments,
+    payment_failure_reasons: prometheus::IntGaugeVec = payment_failure_reasons,
+    total_fee_msat: prometheus::IntGauge = total_fee_msat,
+    payment_retry_count: prometheus::Histogram = payment_retry_count,
+    payment_single_attempt_ratio: prometheus::Gauge = payment_single_attempt_ratio,
+    payment_destination_total: prometheus::IntGaugeVec = payment_destination_total,
+    payment_min_fee_msat: prometheus::IntGauge = payment_min_fee_msat,
+    payment_max_fee_msat: prometheus::IntGauge = payment_max_fee_msat,
+    payment_median_fee_msat: prometheus::IntGauge = payment_median_fee_msat,
+    payment_amount_p50_msat: prometheus::IntGauge = payment_amount_p50_msat,
+    payment_amount_p90_msat: prometheus::IntGauge = payment_amount_p90_msat,
+    payment_amount_p99_msat: prometheus::IntGauge = payment_amount_p99_msat,
+    circular_rebalance_count_total: prometheus::IntGauge = circular_rebalance_count_total,
+    circular_rebalance_fee_msat_total: prometheus::IntGauge = circular_rebalance_fee_msat_total,
+    circular_rebalance_amount_msat_total: prometheus::IntGauge =
+        circular_rebalance_amount_msat_total,
+});
+
+metric_group!(ChannelsMetrics {
+    channel_balance_total_sat: prometheus::IntGaugeVec = channel_balance_total_sat,
+    channel_unsettled_inbound_msat: prometheus::IntGaugeVec = channel_unsettled_inbound_msat,
+    channel_unsettled_outbound_msat: prometheus::IntGaugeVec = channel_unsettled_outbound_msat,
+    channel_liquidity_score: prometheus::GaugeVec = channel_liquidity_score,
+    channel_push_amount_sat: prometheus::IntGaugeVec = channel_push_amount_sat,
+    channels_with_push_total: prometheus::IntGauge = channels_with_push_total,
+    channel_flap_count_total: prometheus::IntGaugeVec = channel_flap_count_total,
+    channels_flapping_total: prometheus::IntGauge = channels_flapping_total,
+    channel_anchor_reserve_sat: prometheus::IntGaugeVec = channel_anchor_reserve_sat,
+    total_anchor_reserve_sat: prometheus::IntGauge = total_anchor_reserve_sat,
+    channel_dust_limit_sat: prometheus::IntGaugeVec = channel_dust_limit_sat,
+    channel_htlc_min_sat: prometheus::IntGaugeVec = channel_htlc_min_sat,
+    channel_htlc_max_count: prometheus::IntGaugeVec = channel_htlc_max_count,
+    channel_htlc_utilization_ratio: prometheus::GaugeVec = channel_htlc_utilization_ratio,
+    channel_htlc_below_dust_threshold: prometheus::IntGaugeVec = channel_htlc_below_dust_threshold,
+    total_channels_with_dust_htlc_exposure: prometheus::IntGauge =
+        total_channels_with_dust_htlc_exposure,
+    batch_funded_channels_total: prometheus::IntGaugeVec = batch_funded_channels_total,
+    batch_funding_transactions_total: prometheus::IntGauge = batch_funding_transactions_total,
+    channels_total: prometheus::IntGaugeVec = channels_total,
+    channel_capacity_total_sat: prometheus::IntGaugeVec = channel_capacity_total_sat,
+    channel_count_by_capacity_bucket: prometheus::IntGaugeVec = channel_count_by_capacity_bucket,
+    channel_capacity_total_sat_by_bucket: prometheus::IntGaugeVec =
+        channel_capacity_total_sat_by_bucket,
+    channel_alias_scid_count: prometheus::IntGaugeVec = channel_alias_scid_count,
+    channel_zero_conf_confirmed: prometheus::IntGaugeVec = channel_zero_conf_confirmed,
+    stuck_htlc_count_total: prometheus::IntGaugeVec = stuck_htlc_count_total,
+    stuck_htlc_value_msat_total: prometheus::IntGaugeVec = stuck_htlc_value_msat_total,
+});
+
+metric_group!(BackupMetrics {
+    backup_channels_count: prometheus::IntGauge = backup_channels_count,
+    backup_size_bytes: prometheus::IntGauge = backup_size_bytes,
+    backup_last_export_timestamp_seconds: prometheus::IntGauge =
+        backup_last_export_timestamp_seconds,
+});
+
+metric_group!(GraphMetrics {
+    graph_local_edges_total: prometheus::IntGauge = graph_local_edges_total,
+    graph_local_edges_disabled_total: prometheus::IntGauge = graph_local_edges_disabled_total,
+    graph_local_channels_delta: prometheus::IntGauge = graph_local_channels_delta,
+});
+
+metric_group!(PoliciesMetrics {
+    channel_cltv_expiry_delta: prometheus::IntGaugeVec = channel_cltv_expiry_delta,
+    channel_effective_htlc_timeout_seconds: prometheus::IntGaugeVec =
+        channel_effective_htlc_timeout_seconds,
+    channel_policy_last_update_timestamp_seconds: prometheus::IntGaugeVec =
+        channel_policy_last_update_timestamp_seconds,
+    channel_policy_age_seconds: prometheus::IntGaugeVec = channel_policy_age_seconds,
+});
+
+metric_group!(WalletBalanceMetrics {
+    wallet_account_balance_sat: prometheus::IntGaugeVec = wallet_account_balance_sat,
+});
+
+metric_group!(TransactionsMetrics {
+    transaction_amount_sat_total: prometheus::IntGaugeVec = transaction_amount_sat_total,
+    transaction_fees_sat_total: prometheus::IntGaugeVec = transaction_fees_sat_total,
+});
+
+metric_group!(PendingChannelsMetrics {
+    pending_anchor_sweepable_count: prometheus::IntGauge = pending_anchor_sweepable_count,
+    pending_anchor_sweepable_value_sat: prometheus::IntGauge = pending_anchor_sweepable_value_sat,
+    pending_force_close_recovery_balance_sat: prometheus::IntGaugeVec =
+        pending_force_close_recovery_balance_sat,
+    channel_open_duration_seconds: prometheus::Histogram = channel_open_duration_seconds,
+    channel_open_success_total: prometheus::IntGaugeVec = channel_open_success_total,
+});
+
+metric_group!(PeersMetrics {
+    peer_flap_count_total: prometheus::IntGaugeVec = peer_flap_count_total,
+    peer_bytes_sent_total: prometheus::IntGaugeVec = peer_bytes_sent_total,
+    peer_bytes_recv_total: prometheus::IntGaugeVec = peer_bytes_recv_total,
+    peer_ping_time_ms: prometheus::GaugeVec = peer_ping_time_ms,
+    peer_blinded_paths_supported: prometheus::IntGaugeVec = peer_blinded_paths_supported,
+    peer_address: prometheus::IntGaugeVec = peer_address,
+    peers_onion_total: prometheus::IntGauge = peers_onion_total,
+    peers_ipv4_total: prometheus::IntGauge = peers_ipv4_total,
+    peers_ipv6_total: prometheus::IntGauge = peers_ipv6_total,
+});
+
+metric_group!(WatchtowerMetrics {
+    watchtower_session_count: prometheus::IntGaugeVec = watchtower_session_count,
+    watchtower_backup_count: prometheus::IntGaugeVec = watchtower_backup_count,
+    watchtower_tower_active: prometheus::IntGaugeVec = watchtower_tower_active,
+    watchtower_total_backups: prometheus::IntGauge = watchtower_total_backups,
+});
+
+metric_group!(AutopilotMetrics {
+    autopilot_enabled: prometheus::IntGauge = autopilot_enabled,
+    autopilot_managed_channels_total: prometheus::IntGauge = autopilot_managed_channels_total,
+    autopilot_wallet_balance_sat: prometheus::IntGauge = autopilot_wallet_balance_sat,
+});
+
+metric_group!(HubDistancesMetrics {
+    node_graph_distance_to_hub: prometheus::IntGaugeVec = node_graph_distance_to_hub,
+});
+
+metric_group!(CloseFeesMetrics {
+    channel_close_fee_estimate_sat: prometheus::IntGaugeVec = channel_close_fee_estimate_sat,
+});
+
+metric_group!(CustomMessagesMetrics {
+    custom_message_count_total: prometheus::IntGaugeVec = custom_message_count_total,
+    custom_message_last_received_timestamp_seconds: prometheus::IntGaugeVec =
+        custom_message_last_received_timestamp_seconds,
+});
+
+metric_group!(InvoicesMetrics {
+    invoice_settled_value_msat_total: prometheus::IntGauge = invoice_settled_value_msat_total,
+    invoice_settled_count_total: prometheus::IntGauge = invoice_settled_count_total,
+    invoice_cancelled_count_total: prometheus::IntGauge = invoice_cancelled_count_total,
+    invoice_last_settled_timestamp_seconds: prometheus::IntGauge =
+        invoice_last_settled_timestamp_seconds,
+    invoice_creation_rate_per_minute: prometheus::Gauge = invoice_creation_rate_per_minute,
+    invoice_creation_rate_per_hour: prometheus::Gauge = invoice_creation_rate_per_hour,
+});
+
+metric_group!(
+    /// Emitted along invoices when invoice proof tracking is enabled.
+    InvoiceProofMetrics {
+        invoice_has_preimage: prometheus::IntGaugeVec = invoice_has_preimage,
+        invoice_amp_settle_indices: prometheus::IntGaugeVec = invoice_amp_settle_indices,
+    }
+);
+
+metric_group!(HealthMetrics {
+    grpc_healthy: prometheus::IntGaugeVec = grpc_healthy,
+});
+
+metric_group!(ForwardingMetrics {
+    channel_forwarded_htlcs_total: prometheus::IntGaugeVec = channel_forwarded_htlcs_total,
+    channel_link_failed_htlcs_total: prometheus::IntGaugeVec = channel_link_failed_htlcs_total,
+    htlc_resolution_time_seconds: prometheus::Histogram = htlc_resolution_time_seconds,
+    routing_revenue_per_block_msat: prometheus::Gauge = routing_revenue_per_block_msat,
+    routing_revenue_7d_msat: prometheus::IntGauge = routing_revenue_7d_msat,
+    routing_revenue_30d_msat: prometheus::IntGauge = routing_revenue_30d_msat,
+});
+
+metric_group!(
+    /// Emitted when a Bitcoin Core node is configured.
+    MempoolMetrics {
+        mempool_tx_count: prometheus::IntGauge = mempool_tx_count,
+        mempool_vbytes: prometheus::IntGauge = mempool_vbytes,
+        mempool_min_fee_rate_sat_per_vb: prometheus::Gauge = mempool_min_fee_rate_sat_per_vb,
+    }
+);
+
+metric_group!(
+    /// Metrics computed from the data of more than one scraper.
+    DerivedMetrics {
+        channel_routing_efficiency_ratio: prometheus::GaugeVec = channel_routing_efficiency_ratio,
+    }
+);
+
+metric_group!(
+    /// Computed from the data of the channels and peers scrapers.
+    BlindedPathsMetrics {
+        channels_blinded_paths_supported_total: prometheus::IntGauge =
+            channels_blinded_paths_supported_total,
+    }
+);
+
+pub fn build_info(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "exporter_build_info",
+            "Always 1, labeled with the version of the exporter and what it was built from",
+        ),
+        &["version", "revision", "rustc"],
+    )
+    .expect("valid metric")
+}
+
+pub fn lnd_up(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "up",
+        "Whether the last GetInfo call to the lnd node succeeded",
+    ))
+    .expect("valid metric")
+}
+
+pub fn num_peers_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "num_peers_total",
         "Number of peers connected to the lnd node",
+    ))
+    .expect("valid metric")
+}
+
+pub fn block_height(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts("block_height", "Chain block height"))
+        .expect("valid metric")
+}
+
+pub fn node_feature_bit(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "node_feature_bit",
+            "Feature bits advertised by the lnd node",
+        ),
+        &["bit", "name", "is_required"],
     )
     .expect("valid metric")
 }
 
-pub fn block_height() -> prometheus::IntGauge {
-    prometheus::IntGauge::new("lnd_block_height", "Chain block height").expect("valid metric")
+pub fn node_feature_count_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "node_feature_count_total",
+        "Number of feature bits advertised by the lnd node",
+    ))
+    .expect("valid metric")
+}
+
+pub fn node_blinded_paths_supported(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "node_blinded_paths_supported",
+        "Whether the lnd node advertises route blinding",
+    ))
+    .expect("valid metric")
+}
+
+pub fn chain_sync_progress_percent(config: &MetricsConfig) -> prometheus::Gauge {
+    prometheus::Gauge::with_opts(config.opts(
+        "chain_sync_progress_percent",
+        "Block height of the lnd node relative to the block explorer chain tip",
+    ))
+    .expect("valid metric")
 }
 
-pub fn outgoing_payments() -> prometheus::IntGaugeVec {
+pub fn outgoing_payments(config: &MetricsConfig) -> prometheus::IntGaugeVec {
     prometheus::IntGaugeVec::new(
-        prometheus::Opts::new(
-            "lnd_outgoing_payments",
+        config.opts(
+            "outgoing_payments",
             "Number of outgoing payments on the lnd node",
         ),
         &["status"],
@@ -21,25 +410,1052 @@ pub fn outgoing_payments() -> prometheus::IntGaugeVec {
     .expect("valid metric")
 }
 
-pub fn payment_failure_reasons() -> prometheus::IntGaugeVec {
+pub fn payment_failure_reasons(config: &MetricsConfig) -> prometheus::IntGaugeVec {
     prometheus::IntGaugeVec::new(
-        prometheus::Opts::new("lnd_payment_failure_reasons", "Payment failure reasons"),
+        config.opts("payment_failure_reasons", "Payment failure reasons"),
         &["reason"],
     )
     .expect("valid metric")
 }
 
-pub fn channel_balance_total_sat() -> prometheus::IntGaugeVec {
+pub fn payment_retry_count(config: &MetricsConfig) -> prometheus::Histogram {
+    prometheus::Histogram::with_opts(
+        prometheus::HistogramOpts::from(config.opts(
+            "payment_retry_count",
+            "Number of failed attempts of completed payments",
+        ))
+        .buckets(vec![0.0, 1.0, 2.0, 3.0, 5.0, 10.0, 20.0]),
+    )
+    .expect("valid metric")
+}
+
+pub fn payment_min_fee_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "payment_min_fee_msat",
+        "Lowest fee paid by a succeeded payment",
+    ))
+    .expect("valid metric")
+}
+
+pub fn payment_max_fee_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "payment_max_fee_msat",
+        "Highest fee paid by a succeeded payment",
+    ))
+    .expect("valid metric")
+}
+
+pub fn payment_median_fee_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "payment_median_fee_msat",
+        "Median fee paid by the last 1000 succeeded payments",
+    ))
+    .expect("valid metric")
+}
+
+pub fn payment_amount_p50_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "payment_amount_p50_msat",
+        "Median amount of the last succeeded payments",
+    ))
+    .expect("valid metric")
+}
+
+pub fn payment_amount_p90_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "payment_amount_p90_msat",
+        "90th percentile amount of the last succeeded payments",
+    ))
+    .expect("valid metric")
+}
+
+pub fn payment_amount_p99_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "payment_amount_p99_msat",
+        "99th percentile amount of the last succeeded payments",
+    ))
+    .expect("valid metric")
+}
+
+pub fn payment_single_attempt_ratio(config: &MetricsConfig) -> prometheus::Gauge {
+    prometheus::Gauge::with_opts(config.opts(
+        "payment_single_attempt_ratio",
+        "Share of completed payments that succeeded with their first attempt",
+    ))
+    .expect("valid metric")
+}
+
+pub fn payment_destination_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
     prometheus::IntGaugeVec::new(
-        prometheus::Opts::new(
-            "lnd_channel_balance_total_sat",
-            "Individual channel balances",
+        config.opts(
+            "payment_destination_total",
+            "Number of succeeded payments to each of the most paid destination nodes",
         ),
+        &["pub_key"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_balance_total_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts("channel_balance_total_sat", "Individual channel balances"),
         &["chan_id", "active", "channel_point", "category"],
     )
     .expect("valid metric")
 }
 
-pub fn total_fee_msat() -> prometheus::IntGauge {
-    prometheus::IntGauge::new("lnd_total_fee_msat", "Total fee paid").expect("valid metric")
+pub fn total_fee_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts("total_fee_msat", "Total fee paid"))
+        .expect("valid metric")
+}
+
+pub fn circular_rebalance_count_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "circular_rebalance_count_total",
+        "Number of succeeded payments from the node to itself",
+    ))
+    .expect("valid metric")
+}
+
+pub fn circular_rebalance_fee_msat_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "circular_rebalance_fee_msat_total",
+        "Fees paid by succeeded payments from the node to itself",
+    ))
+    .expect("valid metric")
+}
+
+pub fn circular_rebalance_amount_msat_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "circular_rebalance_amount_msat_total",
+        "Amount moved by succeeded payments from the node to itself",
+    ))
+    .expect("valid metric")
+}
+
+pub fn channel_liquidity_score(config: &MetricsConfig) -> prometheus::GaugeVec {
+    prometheus::GaugeVec::new(
+        config.opts(
+            "channel_liquidity_score",
+            "Share of the channel's spendable capacity on the local side, from 0 to 1",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn scrape_timeout_total(config: &MetricsConfig) -> prometheus::IntCounterVec {
+    prometheus::IntCounterVec::new(
+        config.opts(
+            "scrape_timeout_total",
+            "Number of scrapes aborted for exceeding the scraper timeout",
+        ),
+        &["scraper"],
+    )
+    .expect("valid metric")
+}
+
+pub fn rpc_retries_total(config: &MetricsConfig) -> prometheus::IntCounterVec {
+    prometheus::IntCounterVec::new(
+        config.opts(
+            "exporter_rpc_retries_total",
+            "Number of scraper retries after transient lnd errors",
+        ),
+        &["scraper"],
+    )
+    .expect("valid metric")
+}
+
+pub fn scrape_errors_total(config: &MetricsConfig) -> prometheus::IntCounterVec {
+    prometheus::IntCounterVec::new(
+        config.opts(
+            "exporter_scrape_errors_total",
+            "Number of failed scraper attempts by gRPC status code",
+        ),
+        &["scraper", "grpc_code"],
+    )
+    .expect("valid metric")
+}
+
+pub fn scraper_enabled(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "exporter_scraper_enabled",
+            "Whether the scraper is enabled and was not refused by lnd",
+        ),
+        &["scraper"],
+    )
+    .expect("valid metric")
+}
+
+pub fn scraper_permitted(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "exporter_scraper_permitted",
+            "Whether the macaroon of the scraper grants its permissions, as checked at startup",
+        ),
+        &["scraper"],
+    )
+    .expect("valid metric")
+}
+
+pub fn wallet_state(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "wallet_state",
+            "State of the lnd wallet, set to 1 for the current state",
+        ),
+        &["state"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_unsettled_inbound_msat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_unsettled_inbound_msat",
+            "Amount of the pending incoming HTLCs of each channel",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_unsettled_outbound_msat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_unsettled_outbound_msat",
+            "Amount of the pending outgoing HTLCs of each channel",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_cltv_expiry_delta(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_cltv_expiry_delta",
+            "CLTV delta, in blocks, required by the local routing policy of the channel",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_effective_htlc_timeout_seconds(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_effective_htlc_timeout_seconds",
+            "CLTV delta of the local routing policy of the channel, assuming 10 minute blocks",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_policy_last_update_timestamp_seconds(
+    config: &MetricsConfig,
+) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_policy_last_update_timestamp_seconds",
+            "Time the local routing policy of the channel was last updated",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_policy_age_seconds(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_policy_age_seconds",
+            "Time since the local routing policy of the channel was last updated",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn wallet_account_balance_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "wallet_account_balance_sat",
+            "On-chain balance of the wallet account, confirmed or not",
+        ),
+        &["account", "confirmed"],
+    )
+    .expect("valid metric")
+}
+
+pub fn transaction_amount_sat_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "transaction_amount_sat_total",
+            "Amount moved by confirmed on-chain transactions, by label and direction",
+        ),
+        &["label", "tx_type"],
+    )
+    .expect("valid metric")
+}
+
+pub fn transaction_fees_sat_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "transaction_fees_sat_total",
+            "Fees paid by confirmed outgoing on-chain transactions, by label",
+        ),
+        &["label"],
+    )
+    .expect("valid metric")
+}
+
+pub fn pending_anchor_sweepable_count(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "pending_anchor_sweepable_count",
+        "Number of anchor outputs of pending force closed channels not swept yet",
+    ))
+    .expect("valid metric")
+}
+
+pub fn pending_anchor_sweepable_value_sat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "pending_anchor_sweepable_value_sat",
+        "Value of the anchor outputs of pending force closed channels not swept yet",
+    ))
+    .expect("valid metric")
+}
+
+pub fn pending_force_close_recovery_balance_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "pending_force_close_recovery_balance_sat",
+            "Balance still to be recovered from pending force closed channels, by closing party",
+        ),
+        &["close_type"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_open_duration_seconds(config: &MetricsConfig) -> prometheus::Histogram {
+    prometheus::Histogram::with_opts(
+        prometheus::HistogramOpts::from(config.opts(
+            "channel_open_duration_seconds",
+            "Time channel opens were seen pending for, from the first scrape they showed up in",
+        ))
+        .buckets(vec![60.0, 300.0, 600.0, 1800.0, 3600.0, 7200.0, 86400.0]),
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_open_success_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_open_success_total",
+            "Number of channel opens seen pending that confirmed or failed since the exporter started",
+        ),
+        &["result"],
+    )
+    .expect("valid metric")
+}
+
+pub fn peer_flap_count_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "peer_flap_count_total",
+            "Number of times lnd saw the peer disconnect",
+        ),
+        &["pub_key"],
+    )
+    .expect("valid metric")
+}
+
+pub fn peer_bytes_sent_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "peer_bytes_sent_total",
+            "Bytes sent to the peer over the current connection",
+        ),
+        &["pub_key"],
+    )
+    .expect("valid metric")
+}
+
+pub fn peer_bytes_recv_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "peer_bytes_recv_total",
+            "Bytes received from the peer over the current connection",
+        ),
+        &["pub_key"],
+    )
+    .expect("valid metric")
+}
+
+pub fn peer_ping_time_ms(config: &MetricsConfig) -> prometheus::GaugeVec {
+    prometheus::GaugeVec::new(
+        config.opts(
+            "peer_ping_time_ms",
+            "Round trip time of the last ping to the peer",
+        ),
+        &["pub_key"],
+    )
+    .expect("valid metric")
+}
+
+pub fn peer_blinded_paths_supported(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "peer_blinded_paths_supported",
+            "Whether the peer advertised route blinding in its init message",
+        ),
+        &["pub_key"],
+    )
+    .expect("valid metric")
+}
+
+pub fn peer_address(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "peer_address",
+            "Address the connection to the peer goes through, always 1",
+        ),
+        &["pub_key", "address"],
+    )
+    .expect("valid metric")
+}
+
+pub fn peers_onion_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "peers_onion_total",
+        "Number of peers connected through Tor onion services",
+    ))
+    .expect("valid metric")
+}
+
+pub fn peers_ipv4_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "peers_ipv4_total",
+        "Number of peers connected through IPv4 addresses",
+    ))
+    .expect("valid metric")
+}
+
+pub fn peers_ipv6_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "peers_ipv6_total",
+        "Number of peers connected through IPv6 addresses",
+    ))
+    .expect("valid metric")
+}
+
+pub fn watchtower_session_count(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "watchtower_session_count",
+            "Number of sessions negotiated with the watchtower",
+        ),
+        &["tower_pubkey"],
+    )
+    .expect("valid metric")
+}
+
+pub fn watchtower_backup_count(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "watchtower_backup_count",
+            "Number of backups acknowledged by the watchtower",
+        ),
+        &["tower_pubkey"],
+    )
+    .expect("valid metric")
+}
+
+pub fn watchtower_tower_active(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "watchtower_tower_active",
+            "Whether the watchtower has sessions and is a candidate for new ones",
+        ),
+        &["tower_pubkey"],
+    )
+    .expect("valid metric")
+}
+
+pub fn watchtower_total_backups(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "watchtower_total_backups",
+        "Number of backups acknowledged by all watchtowers",
+    ))
+    .expect("valid metric")
+}
+
+pub fn autopilot_enabled(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "autopilot_enabled",
+        "Whether the autopilot agent opens channels on its own",
+    ))
+    .expect("valid metric")
+}
+
+pub fn autopilot_managed_channels_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "autopilot_managed_channels_total",
+        "Number of open channels the autopilot agent counts towards its channel limit",
+    ))
+    .expect("valid metric")
+}
+
+pub fn autopilot_wallet_balance_sat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "autopilot_wallet_balance_sat",
+        "Confirmed on-chain balance the autopilot agent funds channels from",
+    ))
+    .expect("valid metric")
+}
+
+pub fn node_graph_distance_to_hub(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "node_graph_distance_to_hub",
+            "Number of hops from the lnd node to the routing hub through the public graph",
+        ),
+        &["hub_alias", "hub_pubkey"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_close_fee_estimate_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_close_fee_estimate_sat",
+            "Estimated on-chain fee of cooperatively closing the channel",
+        ),
+        &["chan_id", "channel_point", "target_confs"],
+    )
+    .expect("valid metric")
+}
+
+pub fn custom_message_count_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "custom_message_count_total",
+            "Number of custom peer messages received since the exporter started",
+        ),
+        &["type"],
+    )
+    .expect("valid metric")
+}
+
+pub fn custom_message_last_received_timestamp_seconds(
+    config: &MetricsConfig,
+) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "custom_message_last_received_timestamp_seconds",
+            "Unix timestamp of the last custom peer message received",
+        ),
+        &["type"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_alias_scid_count(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_alias_scid_count",
+            "Number of SCID aliases of the channel",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_zero_conf_confirmed(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_zero_conf_confirmed",
+            "Whether the funding transaction of the zero-conf channel confirmed",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn stuck_htlc_count_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "stuck_htlc_count_total",
+            "Number of HTLCs of the channel pending for longer than the stuck HTLC threshold",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn stuck_htlc_value_msat_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "stuck_htlc_value_msat_total",
+            "Value of the HTLCs of the channel pending for longer than the stuck HTLC threshold",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_push_amount_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_push_amount_sat",
+            "Amount pushed to the non-initiating party on channel open",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channels_with_push_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "channels_with_push_total",
+        "Number of channels opened with a push amount",
+    ))
+    .expect("valid metric")
+}
+
+pub fn channel_flap_count_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_flap_count_total",
+            "Number of times the channel toggled between active and inactive",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channels_flapping_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "channels_flapping_total",
+        "Number of channels that flapped more times than the flap threshold",
+    ))
+    .expect("valid metric")
+}
+
+pub fn channel_anchor_reserve_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_anchor_reserve_sat",
+            "Amount locked in the anchor output of anchor channels",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn total_anchor_reserve_sat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "total_anchor_reserve_sat",
+        "Amount locked in anchor outputs across all channels",
+    ))
+    .expect("valid metric")
+}
+
+pub fn channels_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts("channels_total", "Number of open channels"),
+        &["visibility"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_capacity_total_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_capacity_total_sat",
+            "Capacity of all open channels",
+        ),
+        &["visibility"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_count_by_capacity_bucket(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_count_by_capacity_bucket",
+            "Number of open channels by capacity range",
+        ),
+        &["bucket"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_capacity_total_sat_by_bucket(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_capacity_total_sat_by_bucket",
+            "Capacity of the open channels by capacity range",
+        ),
+        &["bucket"],
+    )
+    .expect("valid metric")
+}
+
+pub fn batch_funded_channels_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "batch_funded_channels_total",
+            "Number of open channels funded by the same transaction, for transactions funding several",
+        ),
+        &["txid_prefix"],
+    )
+    .expect("valid metric")
+}
+
+pub fn batch_funding_transactions_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "batch_funding_transactions_total",
+        "Number of transactions funding more than one open channel",
+    ))
+    .expect("valid metric")
+}
+
+pub fn channel_dust_limit_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_dust_limit_sat",
+            "Dust limit of the local or remote commitment of the channel",
+        ),
+        &["chan_id", "channel_point", "side"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_htlc_below_dust_threshold(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_htlc_below_dust_threshold",
+            "Whether the channel accepts HTLCs too small for their output to be relayed on-chain",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn total_channels_with_dust_htlc_exposure(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "total_channels_with_dust_htlc_exposure",
+        "Number of channels accepting HTLCs too small for their output to be relayed on-chain",
+    ))
+    .expect("valid metric")
+}
+
+pub fn channel_htlc_min_sat(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_htlc_min_sat",
+            "Smallest HTLC the local or remote side of the channel accepts",
+        ),
+        &["chan_id", "channel_point", "side"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_htlc_max_count(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_htlc_max_count",
+            "Maximum number of pending HTLCs the channel accepts",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_htlc_utilization_ratio(config: &MetricsConfig) -> prometheus::GaugeVec {
+    prometheus::GaugeVec::new(
+        config.opts(
+            "channel_htlc_utilization_ratio",
+            "Pending HTLCs of the channel over the maximum it accepts",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn backup_channels_count(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "backup_channels_count",
+        "Number of channels in the multi-channel backup",
+    ))
+    .expect("valid metric")
+}
+
+pub fn backup_last_export_timestamp_seconds(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "backup_last_export_timestamp_seconds",
+        "Unix timestamp of the last successful channel backup export",
+    ))
+    .expect("valid metric")
+}
+
+pub fn backup_size_bytes(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "backup_size_bytes",
+        "Size of the encrypted multi-channel backup",
+    ))
+    .expect("valid metric")
+}
+
+pub fn scrape_duration_seconds(config: &MetricsConfig) -> prometheus::GaugeVec {
+    prometheus::GaugeVec::new(
+        config.opts(
+            "exporter_scrape_duration_seconds",
+            "Duration of the last scrape of each scraper",
+        ),
+        &["scraper"],
+    )
+    .expect("valid metric")
+}
+
+pub fn scrape_success(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "exporter_scrape_success",
+            "Whether the last scrape of each scraper succeeded",
+        ),
+        &["scraper"],
+    )
+    .expect("valid metric")
+}
+
+pub fn scraper_last_success_timestamp_seconds(config: &MetricsConfig) -> prometheus::GaugeVec {
+    prometheus::GaugeVec::new(
+        config.opts(
+            "exporter_scraper_last_success_timestamp_seconds",
+            "Unix timestamp of the last successful scrape of each scraper",
+        ),
+        &["scraper"],
+    )
+    .expect("valid metric")
+}
+
+pub fn grpc_request_duration_seconds(config: &MetricsConfig) -> prometheus::HistogramVec {
+    prometheus::HistogramVec::new(
+        prometheus::HistogramOpts::from(config.opts(
+            "grpc_request_duration_seconds",
+            "Duration of the gRPC requests to lnd, up to the response headers",
+        ))
+        .buckets(vec![
+            0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+        ]),
+        &["method", "code"],
+    )
+    .expect("valid metric")
+}
+
+pub fn graph_local_edges_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "graph_local_edges_total",
+        "Number of the local node's channels in the announced channel graph",
+    ))
+    .expect("valid metric")
+}
+
+pub fn graph_local_edges_disabled_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "graph_local_edges_disabled_total",
+        "Number of the local node's channels announced as disabled by the local node",
+    ))
+    .expect("valid metric")
+}
+
+pub fn graph_local_channels_delta(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "graph_local_channels_delta",
+        "Local channels in the graph minus active public channels",
+    ))
+    .expect("valid metric")
+}
+
+pub fn invoice_settled_value_msat_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "invoice_settled_value_msat_total",
+        "Total amount received by settled invoices",
+    ))
+    .expect("valid metric")
+}
+
+pub fn invoice_settled_count_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(
+        config.opts("invoice_settled_count_total", "Number of settled invoices"),
+    )
+    .expect("valid metric")
+}
+
+pub fn invoice_last_settled_timestamp_seconds(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "invoice_last_settled_timestamp_seconds",
+        "Time the last invoice was settled at, in seconds since the epoch",
+    ))
+    .expect("valid metric")
+}
+
+pub fn invoice_cancelled_count_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "invoice_cancelled_count_total",
+        "Number of cancelled invoices",
+    ))
+    .expect("valid metric")
+}
+
+pub fn invoice_creation_rate_per_minute(config: &MetricsConfig) -> prometheus::Gauge {
+    prometheus::Gauge::with_opts(config.opts(
+        "invoice_creation_rate_per_minute",
+        "Number of invoices created in the last minute",
+    ))
+    .expect("valid metric")
+}
+
+pub fn invoice_creation_rate_per_hour(config: &MetricsConfig) -> prometheus::Gauge {
+    prometheus::Gauge::with_opts(config.opts(
+        "invoice_creation_rate_per_hour",
+        "Number of invoices created in the last hour",
+    ))
+    .expect("valid metric")
+}
+
+pub fn invoice_has_preimage(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "invoice_has_preimage",
+            "Whether the preimage proving the settlement of the invoice is known",
+        ),
+        &["payment_hash"],
+    )
+    .expect("valid metric")
+}
+
+pub fn invoice_amp_settle_indices(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "invoice_amp_settle_indices",
+            "Number of settled sub-invoices of the AMP invoice",
+        ),
+        &["payment_hash"],
+    )
+    .expect("valid metric")
+}
+
+pub fn grpc_healthy(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "exporter_grpc_healthy",
+            "Whether lnd reports the service as serving through the gRPC health check",
+        ),
+        &["service"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_forwarded_htlcs_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_forwarded_htlcs_total",
+            "Number of HTLCs forwarded out through the channel since the exporter started",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channel_link_failed_htlcs_total(config: &MetricsConfig) -> prometheus::IntGaugeVec {
+    prometheus::IntGaugeVec::new(
+        config.opts(
+            "channel_link_failed_htlcs_total",
+            "Number of forwards failed by the outgoing channel since the exporter started",
+        ),
+        &["chan_id"],
+    )
+    .expect("valid metric")
+}
+
+pub fn htlc_resolution_time_seconds(config: &MetricsConfig) -> prometheus::Histogram {
+    prometheus::Histogram::with_opts(
+        prometheus::HistogramOpts::from(config.opts(
+            "htlc_resolution_time_seconds",
+            "Time from forwarding an HTLC to its settlement, while subscribed",
+        ))
+        .buckets(vec![0.001, 0.01, 0.1, 1.0, 10.0, 60.0, 300.0]),
+    )
+    .expect("valid metric")
+}
+
+pub fn routing_revenue_per_block_msat(config: &MetricsConfig) -> prometheus::Gauge {
+    prometheus::Gauge::with_opts(config.opts(
+        "routing_revenue_per_block_msat",
+        "Routing fees earned per block over the last 100 blocks, at 10 minutes per block",
+    ))
+    .expect("valid metric")
+}
+
+pub fn routing_revenue_7d_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "routing_revenue_7d_msat",
+        "Routing fees earned over the last 7 days",
+    ))
+    .expect("valid metric")
+}
+
+pub fn routing_revenue_30d_msat(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "routing_revenue_30d_msat",
+        "Routing fees earned over the last 30 days",
+    ))
+    .expect("valid metric")
+}
+
+pub fn mempool_tx_count(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "mempool_tx_count",
+        "Number of transactions in the Bitcoin Core mempool",
+    ))
+    .expect("valid metric")
+}
+
+pub fn mempool_vbytes(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "mempool_vbytes",
+        "Total virtual size of the transactions in the Bitcoin Core mempool",
+    ))
+    .expect("valid metric")
+}
+
+pub fn mempool_min_fee_rate_sat_per_vb(config: &MetricsConfig) -> prometheus::Gauge {
+    prometheus::Gauge::with_opts(config.opts(
+        "mempool_min_fee_rate_sat_per_vb",
+        "Minimum fee rate for a transaction to enter the Bitcoin Core mempool",
+    ))
+    .expect("valid metric")
+}
+
+pub fn channel_routing_efficiency_ratio(config: &MetricsConfig) -> prometheus::GaugeVec {
+    prometheus::GaugeVec::new(
+        config.opts(
+            "channel_routing_efficiency_ratio",
+            "Share of the forwards through the channel not failed by the channel itself",
+        ),
+        &["chan_id", "channel_point"],
+    )
+    .expect("valid metric")
+}
+
+pub fn channels_blinded_paths_supported_total(config: &MetricsConfig) -> prometheus::IntGauge {
+    prometheus::IntGauge::with_opts(config.opts(
+        "channels_blinded_paths_supported_total",
+        "Number of channels whose remote peer advertised route blinding",
+    ))
+    .expect("valid metric")
 }
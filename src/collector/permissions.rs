@@ -0,0 +1,170 @@
+use std::{collections::HashMap, time::Duration};
+
+use super::{ScopedLndClientSet, ScraperName, ScraperSet};
+
+/// Macaroon permissions, as entity and action, needed by each scraper along
+/// with the main method it calls.
+fn required_permissions(
+    scraper: ScraperName,
+) -> (&'static str, &'static [(&'static str, &'static str)]) {
+    match scraper {
+        ScraperName::GetInfo => ("/lnrpc.Lightning/GetInfo", &[("info", "read")]),
+        ScraperName::Payments => ("/lnrpc.Lightning/ListPayments", &[("offchain", "read")]),
+        ScraperName::Channels => ("/lnrpc.Lightning/ListChannels", &[("offchain", "read")]),
+        ScraperName::Backup => (
+            "/lnrpc.Lightning/ExportAllChannelBackups",
+            &[("offchain", "read")],
+        ),
+        ScraperName::Graph => (
+            "/lnrpc.Lightning/DescribeGraph",
+            &[("info", "read"), ("offchain", "read")],
+        ),
+        ScraperName::Invoices => ("/lnrpc.Lightning/ListInvoices", &[("invoices", "read")]),
+        // The gRPC health service takes no macaroon
+        ScraperName::Health => ("/grpc.health.v1.Health/Check", &[]),
+        ScraperName::Forwarding => (
+            "/lnrpc.Lightning/ForwardingHistory",
+            &[("offchain", "read")],
+        ),
+        ScraperName::Policies => ("/lnrpc.Lightning/DescribeGraph", &[("info", "read")]),
+        ScraperName::WalletBalance => ("/lnrpc.Lightning/WalletBalance", &[("onchain", "read")]),
+        ScraperName::Transactions => ("/lnrpc.Lightning/GetTransactions", &[("onchain", "read")]),
+        ScraperName::PendingChannels => {
+            ("/lnrpc.Lightning/PendingChannels", &[("offchain", "read")])
+        }
+        ScraperName::Peers => ("/lnrpc.Lightning/ListPeers", &[("peers", "read")]),
+        ScraperName::Watchtower => (
+            "/wtclientrpc.WatchtowerClient/ListTowers",
+            &[("offchain", "read")],
+        ),
+        ScraperName::Autopilot => (
+            "/autopilotrpc.Autopilot/Status",
+            &[("info", "read"), ("onchain", "read"), ("offchain", "read")],
+        ),
+        ScraperName::CloseFees => (
+            "/lnrpc.Lightning/EstimateFee",
+            &[("onchain", "read"), ("offchain", "read")],
+        ),
+        ScraperName::HubDistances => ("/lnrpc.Lightning/DescribeGraph", &[("info", "read")]),
+        ScraperName::CustomMessages => (
+            "/lnrpc.Lightning/SubscribeCustomMessages",
+            &[("offchain", "read")],
+        ),
+        // Never checked, bitcoind takes no macaroon
+        ScraperName::Mempool => ("", &[]),
+    }
+}
+
+/// Outcome of checking the macaroon of a scraper.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PermissionCheck {
+    Permitted,
+    /// Permissions the macaroon lacks, as `entity:action`.
+    Missing(Vec<String>),
+    /// lnd could not tell, e.g. because it is down or predates the check.
+    Unknown(String),
+}
+
+/// Permission checks of the enabled scrapers, in [`ScraperName::ALL`] order.
+pub struct PermissionReport(Vec<(ScraperName, PermissionCheck)>);
+
+impl PermissionReport {
+    /// Whether the macaroon of some scraper is known to lack permissions.
+    pub fn has_missing(&self) -> bool {
+        self.0
+            .iter()
+            .any(|(_, check)| matches!(check, PermissionCheck::Missing(_)))
+    }
+
+    /// Whether each scraper is permitted, for the scrapers lnd could check.
+    pub fn permitted(&self) -> HashMap<ScraperName, bool> {
+        self.0
+            .iter()
+            .filter_map(|(scraper, check)| match check {
+                PermissionCheck::Permitted => Some((*scraper, true)),
+                PermissionCheck::Missing(_) => Some((*scraper, false)),
+                PermissionCheck::Unknown(_) => None,
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for PermissionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let checks = self
+            .0
+            .iter()
+            .map(|(scraper, check)| match check {
+                PermissionCheck::Permitted => format!("{}=ok", scraper),
+                PermissionCheck::Missing(permissions) => {
+                    format!("{}=missing {}", scraper, permissions.join(","))
+                }
+                PermissionCheck::Unknown(reason) => format!("{}=unknown ({})", scraper, reason),
+            })
+            .collect::<Vec<_>>();
+
+        f.write_str(&checks.join(" "))
+    }
+}
+
+/// Asks lnd whether the macaroon of each enabled scraper grants the
+/// permissions it needs, each check bounded by `timeout`.
+pub async fn check_permissions(
+    lnd_clients: &ScopedLndClientSet,
+    enabled_scrapers: &ScraperSet,
+    timeout: Duration,
+) -> PermissionReport {
+    let checks = ScraperName::ALL
+        .iter()
+        .filter(|scraper| enabled_scrapers.contains(**scraper) && scraper.uses_lnd())
+        .map(|scraper| async move {
+            let check = tokio::time::timeout(timeout, check_scraper(lnd_clients, *scraper))
+                .await
+                .unwrap_or_else(|_| PermissionCheck::Unknown("timed out".to_string()));
+
+            (*scraper, check)
+        });
+
+    PermissionReport(futures_util::future::join_all(checks).await)
+}
+
+async fn check_scraper(lnd_clients: &ScopedLndClientSet, scraper: ScraperName) -> PermissionCheck {
+    let (full_method, permissions) = required_permissions(scraper);
+    let macaroon = match lnd_clients.macaroon(scraper) {
+        Some(macaroon) => macaroon.to_vec(),
+        None if permissions.is_empty() => return PermissionCheck::Permitted,
+        None => return PermissionCheck::Missing(vec!["macaroon".to_string()]),
+    };
+    let mut lnd_client = lnd_clients.get(scraper);
+
+    // Permissions are checked one at a time to report which ones are missing
+    let mut missing = vec![];
+    for (entity, action) in permissions.iter() {
+        let res = lnd_client
+            .check_macaroon_permissions(lnrpc::CheckMacPermRequest {
+                macaroon: macaroon.clone(),
+                permissions: vec![lnrpc::MacaroonPermission {
+                    entity: entity.to_string(),
+                    action: action.to_string(),
+                }],
+                full_method: full_method.to_string(),
+            })
+            .await;
+
+        match res {
+            Ok(res) if res.get_ref().valid => {}
+            // lnd answers invalid macaroons with an error rather than valid=false
+            Ok(_) => missing.push(format!("{}:{}", entity, action)),
+            Err(e) if e.code() == lnrpc::Code::InvalidArgument => {
+                missing.push(format!("{}:{}", entity, action))
+            }
+            Err(e) => return PermissionCheck::Unknown(e.message().to_string()),
+        }
+    }
+
+    if missing.is_empty() {
+        PermissionCheck::Permitted
+    } else {
+        PermissionCheck::Missing(missing)
+    }
+}
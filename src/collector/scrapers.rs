@@ -0,0 +1,108 @@
+mod autopilot;
+mod backup;
+mod channels;
+mod closefees;
+mod custommessages;
+mod forwarding;
+mod getinfo;
+mod graph;
+mod health;
+mod hubdistances;
+mod invoices;
+mod mempool;
+mod payments;
+mod peers;
+mod pendingchannels;
+mod policies;
+mod transactions;
+mod walletbalance;
+mod watchtower;
+
+pub use autopilot::AutopilotScraper;
+pub use backup::BackupScraper;
+pub use channels::ChannelsScraper;
+pub use closefees::CloseFeesScraper;
+pub use custommessages::CustomMessagesScraper;
+pub use forwarding::ForwardingScraper;
+pub use getinfo::{node_labels, GetInfoScraper, NODE_LABELS};
+pub use graph::GraphScraper;
+pub use health::HealthScraper;
+pub use hubdistances::HubDistancesScraper;
+pub use invoices::InvoicesScraper;
+pub use mempool::MempoolScraper;
+pub use payments::PaymentsScraper;
+pub use peers::PeersScraper;
+pub use pendingchannels::PendingChannelsScraper;
+pub use policies::PoliciesScraper;
+pub use transactions::TransactionsScraper;
+pub use walletbalance::WalletBalanceScraper;
+pub use watchtower::WatchtowerScraper;
+
+use async_trait::async_trait;
+use prometheus::{core::Desc, proto::MetricFamily};
+
+use super::ScopedLndClientSet;
+
+/// State shared by all scrapers during a scrape.
+pub struct ScrapeContext<'a> {
+    pub lnd_clients: &'a ScopedLndClientSet,
+}
+
+#[derive(Debug)]
+pub enum ScrapeError {
+    Lnd(lnrpc::Status),
+    Bitcoind(super::bitcoind::Error),
+}
+
+impl From<lnrpc::Status> for ScrapeError {
+    fn from(status: lnrpc::Status) -> Self {
+        ScrapeError::Lnd(status)
+    }
+}
+
+impl std::fmt::Display for ScrapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrapeError::Lnd(status) => write!(f, "{}", status.message()),
+            ScrapeError::Bitcoind(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Source of a set of metrics, run by [`super::LndCollector`] on every scrape.
+///
+/// Scrapers own their metrics, which persist across scrapes, and return the
+/// collected families on success.
+#[async_trait]
+pub trait Scraper: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Descriptors of every family the scraper may return.
+    fn desc(&self) -> Vec<&Desc>;
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError>;
+}
+
+/// Whether the feature bits include route blinding, either as required (24) or
+/// optional (25).
+fn supports_route_blinding(features: &std::collections::HashMap<u32, lnrpc::Feature>) -> bool {
+    features.contains_key(&24) || features.contains_key(&25)
+}
+
+/// Renders the metrics like the `/metrics` endpoint does.
+#[cfg(test)]
+fn encode(metrics: impl prometheus::core::Collector + 'static) -> String {
+    use prometheus::Encoder;
+
+    let registry = prometheus::Registry::new();
+    registry
+        .register(Box::new(metrics))
+        .expect("registered metrics");
+
+    let mut buf = vec![];
+    prometheus::TextEncoder::new()
+        .encode(&registry.gather(), &mut buf)
+        .expect("encoded metrics");
+
+    String::from_utf8(buf).expect("utf-8 metrics")
+}
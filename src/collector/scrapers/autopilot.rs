@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{AutopilotMetrics, MetricsConfig},
+    ScraperName,
+};
+
+/// Scrapes whether the autopilot agent opens channels on its own, along with
+/// what it works with.
+pub struct AutopilotScraper {
+    metrics: AutopilotMetrics,
+    /// Whether lnd was found to be built without the autopilot sub-server.
+    unimplemented: AtomicBool,
+}
+
+impl AutopilotScraper {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            metrics: AutopilotMetrics::new(config),
+            unimplemented: AtomicBool::new(false),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for AutopilotScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Autopilot.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut autopilot_client = ctx.lnd_clients.autopilot(ScraperName::Autopilot);
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Autopilot);
+        scrape_autopilot(
+            &mut autopilot_client,
+            &mut lnd_client,
+            &self.metrics,
+            &self.unimplemented,
+        )
+        .await?;
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_autopilot(
+    autopilot_client: &mut lnrpc::AutopilotClient,
+    lnd_client: &mut lnrpc::LndClient,
+    metrics: &AutopilotMetrics,
+    unimplemented: &AtomicBool,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping autopilot status");
+
+    let active = match autopilot_client
+        .status(lnrpc::autopilotrpc::StatusRequest {})
+        .await
+    {
+        Ok(res) => res.get_ref().active,
+
+        // lnd is only built with the autopilot sub-server on request, without
+        // which the agent can't run
+        Err(e) if e.code() == lnrpc::Code::Unimplemented => {
+            if !unimplemented.swap(true, Ordering::Relaxed) {
+                log::warn!("lnd has no autopilot sub-server, reporting autopilot as disabled");
+            }
+            false
+        }
+
+        Err(e) => {
+            log::error!("Failed to collect autopilot metrics ERROR={:?}", e);
+            return Err(e.into());
+        }
+    };
+
+    if !active {
+        record_autopilot(metrics, None);
+        return Ok(());
+    }
+
+    let channels = lnd_client
+        .list_channels(lnrpc::ListChannelsRequest::default())
+        .await;
+    let balance = lnd_client
+        .wallet_balance(lnrpc::WalletBalanceRequest {})
+        .await;
+    match (channels, balance) {
+        (Ok(channels), Ok(balance)) => {
+            record_autopilot(metrics, Some((channels.get_ref(), balance.get_ref())));
+            Ok(())
+        }
+
+        (Err(e), _) | (_, Err(e)) => {
+            log::error!("Failed to collect autopilot metrics ERROR={:?}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Records the state of the agent, given the channels and wallet balance of
+/// the node when it's active. An inactive agent manages nothing.
+fn record_autopilot(
+    metrics: &AutopilotMetrics,
+    active: Option<(&lnrpc::ListChannelsResponse, &lnrpc::WalletBalanceResponse)>,
+) {
+    metrics.reset();
+
+    let (channels, balance) = match active {
+        Some(active) => active,
+        None => return,
+    };
+
+    // lnd doesn't tell the channels the agent opened apart, it counts every
+    // channel towards its limit and allocates from the confirmed balance
+    metrics.autopilot_enabled.set(1);
+    metrics
+        .autopilot_managed_channels_total
+        .set(channels.channels.len() as i64);
+    metrics
+        .autopilot_wallet_balance_sat
+        .set(balance.confirmed_balance);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inactive_autopilot_manages_nothing() {
+        let metrics = AutopilotMetrics::new(&MetricsConfig::new("lnd"));
+
+        record_autopilot(
+            &metrics,
+            Some((
+                &lnrpc::ListChannelsResponse {
+                    channels: vec![lnrpc::Channel::default(), lnrpc::Channel::default()],
+                },
+                &lnrpc::WalletBalanceResponse {
+                    confirmed_balance: 1_500_000,
+                    unconfirmed_balance: 20_000,
+                    ..lnrpc::WalletBalanceResponse::default()
+                },
+            )),
+        );
+        assert_eq!(metrics.autopilot_enabled.get(), 1);
+        assert_eq!(metrics.autopilot_managed_channels_total.get(), 2);
+        assert_eq!(metrics.autopilot_wallet_balance_sat.get(), 1_500_000);
+
+        record_autopilot(&metrics, None);
+        assert_eq!(metrics.autopilot_enabled.get(), 0);
+        assert_eq!(metrics.autopilot_managed_channels_total.get(), 0);
+        assert_eq!(metrics.autopilot_wallet_balance_sat.get(), 0);
+    }
+}
@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{BackupMetrics, MetricsConfig},
+    ScraperName,
+};
+
+pub struct BackupScraper {
+    metrics: BackupMetrics,
+}
+
+impl BackupScraper {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            metrics: BackupMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for BackupScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Backup.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Backup);
+
+        log::debug!("Scraping chanbackup");
+
+        let res = lnd_client
+            .export_all_channel_backups(lnrpc::ChanBackupExportRequest {})
+            .await;
+
+        match res {
+            Ok(res) => {
+                record_chanbackup(&self.metrics, res.get_ref());
+                Ok(self.metrics.collect())
+            }
+
+            Err(e) => {
+                log::error!("Failed to collect chanbackup metrics ERROR={:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
+
+fn record_chanbackup(metrics: &BackupMetrics, res: &lnrpc::ChanBackupSnapshot) {
+    metrics.reset();
+    let multi_chan_backup = res.multi_chan_backup.as_ref();
+
+    metrics
+        .backup_channels_count
+        .set(multi_chan_backup.map_or(0, |b| b.chan_points.len() as i64));
+    metrics
+        .backup_size_bytes
+        .set(multi_chan_backup.map_or(0, |b| b.multi_chan_backup.len() as i64));
+    metrics.backup_last_export_timestamp_seconds.set(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time after unix epoch")
+            .as_secs() as i64,
+    );
+}
@@ -0,0 +1,733 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{ChannelsMetrics, MetricsConfig},
+    ChannelData, ChannelFlapCache, HtlcAgeCache, ScraperName,
+};
+
+/// Value of each anchor output of anchor channels.
+const ANCHOR_AMOUNT_SAT: i64 = 330;
+
+/// Hex digits of the funding txid labeling batch funding transactions.
+const BATCH_TXID_PREFIX_LEN: usize = 16;
+
+/// Capacity ranges channels are counted by, with the smallest capacity in
+/// each.
+const CAPACITY_BUCKETS: &[(&str, i64)] = &[
+    ("<100k", 0),
+    ("100k-1M", 100_000),
+    ("1M-5M", 1_000_000),
+    (">5M", 5_000_001),
+];
+
+/// Size of a P2WSH HTLC output along with the input spending it, which
+/// Bitcoin Core prices the dust limit of the output by.
+const HTLC_OUTPUT_SPEND_VBYTES: u64 = 110;
+
+pub struct ChannelsScraper {
+    flap_cache: Mutex<ChannelFlapCache>,
+    flap_threshold: u64,
+    htlc_age_cache: Mutex<HtlcAgeCache>,
+    stuck_htlc_threshold: Duration,
+    dust_relay_fee_rate: u64,
+    channel_data: Arc<Mutex<ChannelData>>,
+    metrics: ChannelsMetrics,
+}
+
+impl ChannelsScraper {
+    pub fn new(
+        config: &MetricsConfig,
+        flap_threshold: u64,
+        stuck_htlc_threshold: Duration,
+        dust_relay_fee_rate: u64,
+        channel_data: Arc<Mutex<ChannelData>>,
+    ) -> Self {
+        Self {
+            flap_cache: Mutex::new(ChannelFlapCache::default()),
+            flap_threshold,
+            htlc_age_cache: Mutex::new(HtlcAgeCache::default()),
+            stuck_htlc_threshold,
+            dust_relay_fee_rate,
+            channel_data,
+            metrics: ChannelsMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for ChannelsScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Channels.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Channels);
+
+        log::debug!("Scraping listchannels");
+
+        let res = lnd_client
+            .list_channels(lnrpc::ListChannelsRequest::default())
+            .await;
+
+        match res {
+            Ok(res) => {
+                record_listchannels(
+                    &self.metrics,
+                    &mut *self.flap_cache.lock().await,
+                    self.flap_threshold,
+                    &mut *self.channel_data.lock().await,
+                    res.get_ref(),
+                );
+                record_stuck_htlcs(
+                    &self.metrics,
+                    &mut *self.htlc_age_cache.lock().await,
+                    self.stuck_htlc_threshold,
+                    Instant::now(),
+                    res.get_ref(),
+                );
+                record_dust_htlc_exposure(&self.metrics, self.dust_relay_fee_rate, res.get_ref());
+                Ok(self.metrics.collect())
+            }
+
+            Err(e) => {
+                log::error!("Failed to collect listchannels metrics ERROR={:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
+
+fn record_listchannels(
+    metrics: &ChannelsMetrics,
+    flap_cache: &mut ChannelFlapCache,
+    flap_threshold: u64,
+    channel_data: &mut ChannelData,
+    res: &lnrpc::ListChannelsResponse,
+) {
+    // Drop the series of channels closed since the last scrape
+    metrics.reset();
+    flap_cache
+        .channels
+        .retain(|chan_id, _| res.channels.iter().any(|c| c.chan_id == *chan_id));
+    channel_data.channel_points = res
+        .channels
+        .iter()
+        .map(|channel| (channel.chan_id, channel.channel_point.clone()))
+        .collect();
+    channel_data.remote_pubkeys = res
+        .channels
+        .iter()
+        .map(|channel| channel.remote_pubkey.clone())
+        .collect();
+    // Both visibilities are exported even without channels of that kind
+    for visibility in ["public", "private"] {
+        metrics.channels_total.with_label_values(&[visibility]);
+        metrics
+            .channel_capacity_total_sat
+            .with_label_values(&[visibility]);
+    }
+    for (bucket, _) in CAPACITY_BUCKETS.iter() {
+        metrics
+            .channel_count_by_capacity_bucket
+            .with_label_values(&[bucket]);
+        metrics
+            .channel_capacity_total_sat_by_bucket
+            .with_label_values(&[bucket]);
+    }
+
+    for channel in res.channels.iter() {
+        let chan_id = channel.chan_id.to_string();
+        let active = if channel.active { "true" } else { "false" };
+        let channel_point = &channel.channel_point;
+        let visibility = if channel.private { "private" } else { "public" };
+
+        metrics
+            .channels_total
+            .with_label_values(&[visibility])
+            .inc();
+        metrics
+            .channel_capacity_total_sat
+            .with_label_values(&[visibility])
+            .add(channel.capacity);
+
+        let bucket = capacity_bucket(channel.capacity);
+        metrics
+            .channel_count_by_capacity_bucket
+            .with_label_values(&[bucket])
+            .inc();
+        metrics
+            .channel_capacity_total_sat_by_bucket
+            .with_label_values(&[bucket])
+            .add(channel.capacity);
+
+        metrics
+            .channel_balance_total_sat
+            .with_label_values(&[&chan_id, active, channel_point, "local"])
+            .set(channel.local_balance);
+        metrics
+            .channel_balance_total_sat
+            .with_label_values(&[&chan_id, active, channel_point, "remote"])
+            .set(channel.remote_balance);
+        metrics
+            .channel_balance_total_sat
+            .with_label_values(&[&chan_id, active, channel_point, "unsettled"])
+            .set(channel.unsettled_balance);
+
+        // HTLC amounts are in sat
+        let (inbound, outbound): (Vec<_>, Vec<_>) =
+            channel.pending_htlcs.iter().partition(|htlc| htlc.incoming);
+        metrics
+            .channel_unsettled_inbound_msat
+            .with_label_values(&[&chan_id, channel_point])
+            .set(inbound.iter().map(|htlc| htlc.amount * 1_000).sum());
+        metrics
+            .channel_unsettled_outbound_msat
+            .with_label_values(&[&chan_id, channel_point])
+            .set(outbound.iter().map(|htlc| htlc.amount * 1_000).sum());
+
+        if let Some(score) = liquidity_score(channel) {
+            metrics
+                .channel_liquidity_score
+                .with_label_values(&[&chan_id, channel_point])
+                .set(score);
+        }
+
+        metrics
+            .channel_push_amount_sat
+            .with_label_values(&[&chan_id, channel_point])
+            .set(channel.push_amount_sat as i64);
+        if channel.push_amount_sat > 0 {
+            metrics.channels_with_push_total.inc();
+        }
+
+        metrics
+            .channel_alias_scid_count
+            .with_label_values(&[&chan_id])
+            .set(channel.alias_scids.len() as i64);
+        // lnd only assigns the confirmed SCID once the funding transaction
+        // confirmed
+        if channel.zero_conf {
+            metrics
+                .channel_zero_conf_confirmed
+                .with_label_values(&[&chan_id])
+                .set((channel.zero_conf_confirmed_scid != 0).into());
+        }
+
+        let anchor_reserve_sat = if channel.commitment_type() == lnrpc::CommitmentType::Anchors {
+            ANCHOR_AMOUNT_SAT
+        } else {
+            0
+        };
+        metrics
+            .channel_anchor_reserve_sat
+            .with_label_values(&[&chan_id, channel_point])
+            .set(anchor_reserve_sat);
+        metrics.total_anchor_reserve_sat.add(anchor_reserve_sat);
+
+        for (side, constraints) in [
+            ("local", &channel.local_constraints),
+            ("remote", &channel.remote_constraints),
+        ] {
+            if let Some(constraints) = constraints {
+                metrics
+                    .channel_dust_limit_sat
+                    .with_label_values(&[&chan_id, channel_point, side])
+                    .set(constraints.dust_limit_sat as i64);
+                metrics
+                    .channel_htlc_min_sat
+                    .with_label_values(&[&chan_id, channel_point, side])
+                    .set((constraints.min_htlc_msat / 1_000) as i64);
+            }
+        }
+
+        // Payments can't be routed through the channel once the limit is hit
+        let max_htlcs = channel
+            .local_constraints
+            .as_ref()
+            .map_or(0, |c| c.max_accepted_htlcs);
+        if max_htlcs > 0 {
+            metrics
+                .channel_htlc_max_count
+                .with_label_values(&[&chan_id])
+                .set(max_htlcs.into());
+            metrics
+                .channel_htlc_utilization_ratio
+                .with_label_values(&[&chan_id])
+                .set(channel.pending_htlcs.len() as f64 / f64::from(max_htlcs));
+        }
+
+        let (last_active, flap_count) = flap_cache
+            .channels
+            .entry(channel.chan_id)
+            .or_insert((channel.active, 0));
+        if *last_active != channel.active {
+            *last_active = channel.active;
+            *flap_count += 1;
+        }
+
+        metrics
+            .channel_flap_count_total
+            .with_label_values(&[&chan_id, channel_point])
+            .set(*flap_count as i64);
+        if *flap_count > flap_threshold {
+            metrics.channels_flapping_total.inc();
+        }
+    }
+
+    record_batch_funding(metrics, res);
+}
+
+fn capacity_bucket(capacity: i64) -> &'static str {
+    CAPACITY_BUCKETS
+        .iter()
+        .rev()
+        .find(|(_, min_capacity)| capacity >= *min_capacity)
+        .map_or(CAPACITY_BUCKETS[0].0, |(bucket, _)| bucket)
+}
+
+/// Flags the channels whose smallest accepted HTLC is below the dust limit of
+/// HTLC outputs at the dust relay fee rate, as such HTLCs can't be enforced
+/// on-chain.
+fn record_dust_htlc_exposure(
+    metrics: &ChannelsMetrics,
+    dust_relay_fee_rate: u64,
+    res: &lnrpc::ListChannelsResponse,
+) {
+    let dust_limit_sat = HTLC_OUTPUT_SPEND_VBYTES * dust_relay_fee_rate;
+
+    let mut exposed = 0;
+    for channel in res.channels.iter() {
+        let min_htlc_msat = match &channel.local_constraints {
+            Some(constraints) => constraints.min_htlc_msat,
+            None => continue,
+        };
+
+        let below_dust = min_htlc_msat / 1_000 < dust_limit_sat;
+        exposed += i64::from(below_dust);
+        metrics
+            .channel_htlc_below_dust_threshold
+            .with_label_values(&[&channel.chan_id.to_string()])
+            .set(below_dust.into());
+    }
+    metrics.total_channels_with_dust_htlc_exposure.set(exposed);
+}
+
+/// Counts the pending HTLCs first seen more than `threshold` before `now`,
+/// forgetting the HTLCs that resolved.
+fn record_stuck_htlcs(
+    metrics: &ChannelsMetrics,
+    cache: &mut HtlcAgeCache,
+    threshold: Duration,
+    now: Instant,
+    res: &lnrpc::ListChannelsResponse,
+) {
+    let mut pending = HashMap::new();
+    for channel in res.channels.iter() {
+        let chan_id = channel.chan_id.to_string();
+        let (mut count, mut value_msat) = (0, 0);
+
+        for htlc in channel.pending_htlcs.iter() {
+            let key = (channel.chan_id, htlc.incoming, htlc.htlc_index);
+            let first_seen = cache.first_seen.get(&key).copied().unwrap_or(now);
+            pending.insert(key, first_seen);
+
+            if now.duration_since(first_seen) > threshold {
+                count += 1;
+                // HTLC amounts are in sat
+                value_msat += htlc.amount * 1_000;
+            }
+        }
+
+        metrics
+            .stuck_htlc_count_total
+            .with_label_values(&[&chan_id])
+            .set(count);
+        metrics
+            .stuck_htlc_value_msat_total
+            .with_label_values(&[&chan_id])
+            .set(value_msat);
+    }
+
+    cache.first_seen = pending;
+}
+
+/// Groups the channels by the txid of their channel point, exporting the
+/// transactions that funded several of them at once.
+fn record_batch_funding(metrics: &ChannelsMetrics, res: &lnrpc::ListChannelsResponse) {
+    let mut channels_by_txid = HashMap::<&str, i64>::new();
+    for channel in res.channels.iter() {
+        let txid = channel.channel_point.split(':').next().unwrap_or_default();
+        *channels_by_txid.entry(txid).or_default() += 1;
+    }
+
+    for (txid, channels) in channels_by_txid.into_iter().filter(|(_, n)| *n > 1) {
+        let txid_prefix = txid.get(..BATCH_TXID_PREFIX_LEN).unwrap_or(txid);
+        metrics
+            .batch_funded_channels_total
+            .with_label_values(&[txid_prefix])
+            .set(channels);
+        metrics.batch_funding_transactions_total.inc();
+    }
+}
+
+/// Computes `(local_balance - local_reserve) / (capacity - local_reserve - remote_reserve)`
+/// clamped to [0, 1]. Returns `None` when the channel has no spendable capacity.
+fn liquidity_score(channel: &lnrpc::Channel) -> Option<f64> {
+    let local_reserve = channel
+        .local_constraints
+        .as_ref()
+        .map_or(0, |c| c.chan_reserve_sat) as f64;
+    let remote_reserve = channel
+        .remote_constraints
+        .as_ref()
+        .map_or(0, |c| c.chan_reserve_sat) as f64;
+
+    let spendable_capacity = channel.capacity as f64 - local_reserve - remote_reserve;
+    if spendable_capacity <= 0.0 {
+        return None;
+    }
+
+    Some(((channel.local_balance as f64 - local_reserve) / spendable_capacity).clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn family<'a>(families: &'a [MetricFamily], name: &str) -> &'a MetricFamily {
+        families
+            .iter()
+            .find(|family| family.get_name() == name)
+            .unwrap_or_else(|| panic!("{} not collected", name))
+    }
+
+    fn chan_ids(family: &MetricFamily) -> Vec<&str> {
+        let mut chan_ids = family
+            .get_metric()
+            .iter()
+            .flat_map(|metric| metric.get_label())
+            .filter(|label| label.get_name() == "chan_id")
+            .map(|label| label.get_value())
+            .collect::<Vec<_>>();
+        chan_ids.sort_unstable();
+        chan_ids.dedup();
+        chan_ids
+    }
+
+    fn channel(chan_id: u64) -> lnrpc::Channel {
+        lnrpc::Channel {
+            active: true,
+            chan_id,
+            channel_point: format!("txid:{}", chan_id),
+            capacity: 1_000_000,
+            local_balance: 400_000,
+            remote_balance: 600_000,
+            push_amount_sat: 10_000,
+            unsettled_balance: 3_500,
+            pending_htlcs: vec![
+                lnrpc::Htlc {
+                    incoming: true,
+                    amount: 1_000,
+                    ..lnrpc::Htlc::default()
+                },
+                lnrpc::Htlc {
+                    incoming: true,
+                    amount: 2_000,
+                    ..lnrpc::Htlc::default()
+                },
+                lnrpc::Htlc {
+                    incoming: false,
+                    amount: 500,
+                    ..lnrpc::Htlc::default()
+                },
+            ],
+            local_constraints: Some(lnrpc::ChannelConstraints {
+                chan_reserve_sat: 10_000,
+                dust_limit_sat: 354,
+                min_htlc_msat: 1,
+                max_accepted_htlcs: 483,
+                ..lnrpc::ChannelConstraints::default()
+            }),
+            remote_constraints: Some(lnrpc::ChannelConstraints {
+                chan_reserve_sat: 10_000,
+                dust_limit_sat: 573,
+                min_htlc_msat: 1_000,
+                ..lnrpc::ChannelConstraints::default()
+            }),
+            ..lnrpc::Channel::default()
+        }
+    }
+
+    #[test]
+    fn output_is_unchanged() {
+        let metrics = ChannelsMetrics::new(&MetricsConfig::new("lnd"));
+        let anchor_channel = lnrpc::Channel {
+            active: false,
+            private: true,
+            commitment_type: lnrpc::CommitmentType::Anchors as i32,
+            ..channel(2)
+        };
+
+        record_listchannels(
+            &metrics,
+            &mut ChannelFlapCache::default(),
+            3,
+            &mut ChannelData::default(),
+            &lnrpc::ListChannelsResponse {
+                channels: vec![channel(1), anchor_channel],
+            },
+        );
+
+        assert_eq!(
+            super::super::encode(metrics),
+            include_str!("testdata/channels.prom")
+        );
+    }
+
+    #[test]
+    fn batch_funding_transactions_are_counted() {
+        let metrics = ChannelsMetrics::new(&MetricsConfig::new("lnd"));
+        let batch_txid = "5f0c1e0d8a7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a3b2c1d";
+        let funded_by = |chan_id: u64, channel_point: String| lnrpc::Channel {
+            channel_point,
+            ..channel(chan_id)
+        };
+
+        record_listchannels(
+            &metrics,
+            &mut ChannelFlapCache::default(),
+            3,
+            &mut ChannelData::default(),
+            &lnrpc::ListChannelsResponse {
+                channels: vec![
+                    funded_by(1, format!("{}:0", batch_txid)),
+                    funded_by(2, format!("{}:1", batch_txid)),
+                    funded_by(3, format!("{}:2", batch_txid)),
+                    funded_by(4, format!("{}:0", "ab".repeat(32))),
+                ],
+            },
+        );
+
+        let families = metrics.collect();
+        let batches = family(&families, "lnd_batch_funded_channels_total").get_metric();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].get_label()[0].get_value(), "5f0c1e0d8a7b6c5d");
+        assert_eq!(batches[0].get_gauge().get_value(), 3.0);
+        assert_eq!(metrics.batch_funding_transactions_total.get(), 1);
+    }
+
+    #[test]
+    fn zero_conf_channels_track_their_aliases() {
+        let metrics = ChannelsMetrics::new(&MetricsConfig::new("lnd"));
+        let zero_conf = |chan_id: u64, zero_conf_confirmed_scid: u64| lnrpc::Channel {
+            alias_scids: vec![17_592_186_044_416_000_001, 17_592_186_044_416_000_002],
+            zero_conf: true,
+            zero_conf_confirmed_scid,
+            ..channel(chan_id)
+        };
+
+        record_listchannels(
+            &metrics,
+            &mut ChannelFlapCache::default(),
+            3,
+            &mut ChannelData::default(),
+            &lnrpc::ListChannelsResponse {
+                channels: vec![zero_conf(1, 0), zero_conf(2, 800_000 << 40), channel(3)],
+            },
+        );
+
+        let alias_count = |chan_id: &str| {
+            metrics
+                .channel_alias_scid_count
+                .with_label_values(&[chan_id])
+                .get()
+        };
+        assert_eq!(alias_count("1"), 2);
+        assert_eq!(alias_count("3"), 0);
+
+        let families = metrics.collect();
+        let confirmed = family(&families, "lnd_channel_zero_conf_confirmed");
+        assert_eq!(chan_ids(confirmed), ["1", "2"]);
+        assert_eq!(
+            metrics
+                .channel_zero_conf_confirmed
+                .with_label_values(&["1"])
+                .get(),
+            0
+        );
+        assert_eq!(
+            metrics
+                .channel_zero_conf_confirmed
+                .with_label_values(&["2"])
+                .get(),
+            1
+        );
+    }
+
+    #[test]
+    fn htlcs_pending_past_the_threshold_are_stuck() {
+        let metrics = ChannelsMetrics::new(&MetricsConfig::new("lnd"));
+        let mut cache = HtlcAgeCache::default();
+        let threshold = Duration::from_secs(3600);
+        let start = Instant::now();
+        let with_htlcs = |htlc_indexes: &[u64]| lnrpc::ListChannelsResponse {
+            channels: vec![lnrpc::Channel {
+                pending_htlcs: htlc_indexes
+                    .iter()
+                    .map(|htlc_index| lnrpc::Htlc {
+                        amount: 2_000,
+                        htlc_index: *htlc_index,
+                        ..lnrpc::Htlc::default()
+                    })
+                    .collect(),
+                ..channel(1)
+            }],
+        };
+        let stuck = || {
+            (
+                metrics
+                    .stuck_htlc_count_total
+                    .with_label_values(&["1"])
+                    .get(),
+                metrics
+                    .stuck_htlc_value_msat_total
+                    .with_label_values(&["1"])
+                    .get(),
+            )
+        };
+
+        record_stuck_htlcs(&metrics, &mut cache, threshold, start, &with_htlcs(&[1]));
+        assert_eq!(stuck(), (0, 0));
+
+        let later = start + Duration::from_secs(1800);
+        record_stuck_htlcs(&metrics, &mut cache, threshold, later, &with_htlcs(&[1, 2]));
+        assert_eq!(stuck(), (0, 0));
+
+        let much_later = start + Duration::from_secs(4000);
+        record_stuck_htlcs(
+            &metrics,
+            &mut cache,
+            threshold,
+            much_later,
+            &with_htlcs(&[1, 2]),
+        );
+        assert_eq!(stuck(), (1, 2_000_000));
+
+        // The stuck HTLC resolved and is forgotten
+        record_stuck_htlcs(
+            &metrics,
+            &mut cache,
+            threshold,
+            much_later,
+            &with_htlcs(&[2]),
+        );
+        assert_eq!(stuck(), (0, 0));
+        assert_eq!(cache.first_seen.len(), 1);
+    }
+
+    #[test]
+    fn capacities_are_bucketed() {
+        assert_eq!(capacity_bucket(20_000), "<100k");
+        assert_eq!(capacity_bucket(100_000), "100k-1M");
+        assert_eq!(capacity_bucket(999_999), "100k-1M");
+        assert_eq!(capacity_bucket(5_000_000), "1M-5M");
+        assert_eq!(capacity_bucket(16_777_215), ">5M");
+    }
+
+    #[test]
+    fn htlcs_below_the_dust_limit_are_flagged() {
+        let metrics = ChannelsMetrics::new(&MetricsConfig::new("lnd"));
+        let with_min_htlc = |chan_id, min_htlc_msat| lnrpc::Channel {
+            local_constraints: Some(lnrpc::ChannelConstraints {
+                min_htlc_msat,
+                ..lnrpc::ChannelConstraints::default()
+            }),
+            ..channel(chan_id)
+        };
+
+        // 110 vB at 3 sat/vB
+        record_dust_htlc_exposure(
+            &metrics,
+            3,
+            &lnrpc::ListChannelsResponse {
+                channels: vec![
+                    with_min_htlc(1, 1_000),
+                    with_min_htlc(2, 329_999),
+                    with_min_htlc(3, 330_000),
+                ],
+            },
+        );
+
+        let below_dust = |chan_id| {
+            metrics
+                .channel_htlc_below_dust_threshold
+                .with_label_values(&[chan_id])
+                .get()
+        };
+        assert_eq!(below_dust("1"), 1);
+        assert_eq!(below_dust("2"), 1);
+        assert_eq!(below_dust("3"), 0);
+        assert_eq!(metrics.total_channels_with_dust_htlc_exposure.get(), 2);
+    }
+
+    #[test]
+    fn closed_channels_are_dropped_on_rescrape() {
+        let metrics = ChannelsMetrics::new(&MetricsConfig::new("lnd"));
+        let mut flap_cache = ChannelFlapCache::default();
+
+        record_listchannels(
+            &metrics,
+            &mut flap_cache,
+            0,
+            &mut ChannelData::default(),
+            &lnrpc::ListChannelsResponse {
+                channels: vec![channel(1), channel(2)],
+            },
+        );
+        let families = metrics.collect();
+        assert_eq!(
+            chan_ids(family(&families, "lnd_channel_liquidity_score")),
+            ["1", "2"]
+        );
+        assert_eq!(metrics.channels_with_push_total.get(), 2);
+
+        record_listchannels(
+            &metrics,
+            &mut flap_cache,
+            0,
+            &mut ChannelData::default(),
+            &lnrpc::ListChannelsResponse {
+                channels: vec![channel(2)],
+            },
+        );
+        let families = metrics.collect();
+        for name in [
+            "lnd_channel_balance_total_sat",
+            "lnd_channel_unsettled_inbound_msat",
+            "lnd_channel_unsettled_outbound_msat",
+            "lnd_channel_liquidity_score",
+            "lnd_channel_push_amount_sat",
+            "lnd_channel_flap_count_total",
+            "lnd_channel_dust_limit_sat",
+            "lnd_channel_htlc_min_sat",
+        ] {
+            assert_eq!(chan_ids(family(&families, name)), ["2"], "{}", name);
+        }
+        assert_eq!(metrics.channels_with_push_total.get(), 1);
+    }
+}
@@ -0,0 +1,268 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{CloseFeesMetrics, MetricsConfig},
+    ScraperName,
+};
+
+/// Virtual size of a cooperative close without outputs: the version, locktime,
+/// counts and segwit marker, plus the 2-of-2 multisig funding input.
+const CLOSE_BASE_VBYTES: u64 = 107;
+/// Virtual size of each P2WPKH output of a cooperative close.
+const CLOSE_OUTPUT_VBYTES: u64 = 31;
+
+/// Amount the fee rate is estimated for, above the dust limit of any output.
+const ESTIMATE_AMOUNT_SAT: i64 = 10_000;
+
+/// Estimates the fee of cooperatively closing each channel now, from the fee
+/// rate lnd would pay to confirm within the target number of blocks.
+///
+/// Estimates are refreshed at most once per refresh interval, since the fee
+/// estimate has lnd build a transaction from the wallet.
+pub struct CloseFeesScraper {
+    target_confs: i32,
+    refresh_interval: Duration,
+    last_refresh: Mutex<Option<Instant>>,
+    metrics: CloseFeesMetrics,
+}
+
+impl CloseFeesScraper {
+    pub fn new(config: &MetricsConfig, target_confs: i32, refresh_interval: Duration) -> Self {
+        Self {
+            target_confs,
+            refresh_interval,
+            last_refresh: Mutex::new(None),
+            metrics: CloseFeesMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for CloseFeesScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::CloseFees.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut last_refresh = self.last_refresh.lock().await;
+        if matches!(*last_refresh, Some(t) if t.elapsed() < self.refresh_interval) {
+            return Ok(self.metrics.collect());
+        }
+
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::CloseFees);
+        scrape_close_fee_estimates(&mut lnd_client, self.target_confs, &self.metrics).await?;
+        *last_refresh = Some(Instant::now());
+
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_close_fee_estimates(
+    lnd_client: &mut lnrpc::LndClient,
+    target_confs: i32,
+    metrics: &CloseFeesMetrics,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping estimatefee");
+
+    let info = lnd_client.get_info(lnrpc::GetInfoRequest {}).await?;
+    let network = info
+        .get_ref()
+        .chains
+        .first()
+        .map(|chain| chain.network.as_str())
+        .unwrap_or_default();
+    let address = estimate_address(network).ok_or_else(|| {
+        lnrpc::Status::failed_precondition(format!("unknown network {:?}", network))
+    })?;
+
+    let estimate = lnd_client
+        .estimate_fee(lnrpc::EstimateFeeRequest {
+            addr_to_amount: std::iter::once((address, ESTIMATE_AMOUNT_SAT)).collect(),
+            target_conf: target_confs,
+            ..lnrpc::EstimateFeeRequest::default()
+        })
+        .await;
+    let sat_per_vbyte = match estimate {
+        Ok(res) => res.get_ref().sat_per_vbyte,
+        Err(e) => {
+            log::error!("Failed to estimate close fees ERROR={:?}", e);
+            return Err(e.into());
+        }
+    };
+
+    let channels = lnd_client
+        .list_channels(lnrpc::ListChannelsRequest::default())
+        .await?;
+    record_close_fee_estimates(metrics, target_confs, sat_per_vbyte, channels.get_ref());
+
+    Ok(())
+}
+
+fn record_close_fee_estimates(
+    metrics: &CloseFeesMetrics,
+    target_confs: i32,
+    sat_per_vbyte: u64,
+    res: &lnrpc::ListChannelsResponse,
+) {
+    // Drop the series of channels closed since the last refresh
+    metrics.reset();
+
+    let target_confs = target_confs.to_string();
+    for channel in res.channels.iter() {
+        let fee_sat = sat_per_vbyte * close_vbytes(channel);
+
+        metrics
+            .channel_close_fee_estimate_sat
+            .with_label_values(&[
+                &channel.chan_id.to_string(),
+                &channel.channel_point,
+                &target_confs,
+            ])
+            .set(fee_sat as i64);
+    }
+}
+
+/// Virtual size of the cooperative close of the channel, which pays out each
+/// side with a balance above its dust limit.
+fn close_vbytes(channel: &lnrpc::Channel) -> u64 {
+    let dust_limit = |constraints: &Option<lnrpc::ChannelConstraints>| {
+        constraints.as_ref().map_or(0, |c| c.dust_limit_sat as i64)
+    };
+    let outputs = [
+        (
+            channel.local_balance,
+            dust_limit(&channel.local_constraints),
+        ),
+        (
+            channel.remote_balance,
+            dust_limit(&channel.remote_constraints),
+        ),
+    ]
+    .iter()
+    .filter(|(balance, dust_limit)| *balance > 0 && balance >= dust_limit)
+    .count() as u64;
+
+    CLOSE_BASE_VBYTES + outputs * CLOSE_OUTPUT_VBYTES
+}
+
+/// P2WPKH address of an all-zero key hash on the network, for estimating fee
+/// rates with. Nothing is ever sent to it.
+fn estimate_address(network: &str) -> Option<String> {
+    let hrp = match network {
+        "mainnet" => "bc",
+        "testnet" | "signet" => "tb",
+        "regtest" => "bcrt",
+        "simnet" => "sb",
+        _ => return None,
+    };
+
+    Some(segwit_v0_address(hrp, &[0; 20]))
+}
+
+/// Encodes a version 0 witness program as a bech32 address, per BIP 173.
+fn segwit_v0_address(hrp: &str, program: &[u8]) -> String {
+    const CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+    // Witness version followed by the program regrouped into 5-bit words
+    let mut data = vec![0u8];
+    let (mut acc, mut bits) = (0u32, 0);
+    for byte in program {
+        acc = (acc << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            data.push(((acc >> bits) & 31) as u8);
+        }
+    }
+    if bits > 0 {
+        data.push(((acc << (5 - bits)) & 31) as u8);
+    }
+
+    let polymod = |values: &[u8]| {
+        values.iter().fold(1u32, |chk, value| {
+            let top = chk >> 25;
+            let chk = ((chk & 0x1ffffff) << 5) ^ u32::from(*value);
+            (0..5)
+                .filter(|i| (top >> i) & 1 == 1)
+                .fold(chk, |chk, i| chk ^ GENERATOR[i])
+        })
+    };
+    let mut values = hrp.bytes().map(|c| c >> 5).collect::<Vec<_>>();
+    values.push(0);
+    values.extend(hrp.bytes().map(|c| c & 31));
+    values.extend(&data);
+    values.extend([0; 6]);
+    let checksum = polymod(&values) ^ 1;
+    data.extend((0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8));
+
+    let mut address = format!("{}1", hrp);
+    address.extend(data.iter().map(|d| CHARSET[*d as usize] as char));
+    address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_are_bech32_encoded() {
+        // BIP 173 test vector
+        let program = hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6").expect("hex");
+        assert_eq!(
+            segwit_v0_address("bc", &program),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert!(estimate_address("regtest")
+            .expect("known network")
+            .starts_with("bcrt1q"));
+        assert_eq!(estimate_address("liquid"), None);
+    }
+
+    #[test]
+    fn close_fees_follow_the_outputs() {
+        let metrics = CloseFeesMetrics::new(&MetricsConfig::new("lnd"));
+        let channel = |chan_id: u64, remote_balance: i64| lnrpc::Channel {
+            chan_id,
+            channel_point: format!("txid:{}", chan_id),
+            local_balance: 400_000,
+            remote_balance,
+            remote_constraints: Some(lnrpc::ChannelConstraints {
+                dust_limit_sat: 354,
+                ..lnrpc::ChannelConstraints::default()
+            }),
+            ..lnrpc::Channel::default()
+        };
+
+        record_close_fee_estimates(
+            &metrics,
+            6,
+            10,
+            &lnrpc::ListChannelsResponse {
+                channels: vec![channel(1, 600_000), channel(2, 300)],
+            },
+        );
+
+        let fee = |chan_id: &str| {
+            metrics
+                .channel_close_fee_estimate_sat
+                .with_label_values(&[chan_id, &format!("txid:{}", chan_id), "6"])
+                .get()
+        };
+        assert_eq!(fee("1"), 1_690);
+        // The remote balance is below its dust limit, leaving a single output
+        assert_eq!(fee("2"), 1_380);
+    }
+}
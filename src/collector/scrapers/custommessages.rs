@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{CustomMessagesMetrics, MetricsConfig},
+    CustomMessageCache, ScraperName,
+};
+
+/// Exports the custom peer messages counted by the background subscription,
+/// e.g. the LSPS requests (types 37000 and up) of the clients of an LSP.
+pub struct CustomMessagesScraper {
+    cache: Arc<Mutex<CustomMessageCache>>,
+    metrics: CustomMessagesMetrics,
+}
+
+impl CustomMessagesScraper {
+    pub fn new(config: &MetricsConfig, cache: Arc<Mutex<CustomMessageCache>>) -> Self {
+        Self {
+            cache,
+            metrics: CustomMessagesMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for CustomMessagesScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::CustomMessages.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, _: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        record_custom_messages(&self.metrics, &*self.cache.lock().await);
+        Ok(self.metrics.collect())
+    }
+}
+
+fn record_custom_messages(metrics: &CustomMessagesMetrics, cache: &CustomMessageCache) {
+    for (message_type, (count, last_received)) in cache.types.iter() {
+        metrics
+            .custom_message_count_total
+            .with_label_values(&[message_type])
+            .set(*count as i64);
+        metrics
+            .custom_message_last_received_timestamp_seconds
+            .with_label_values(&[message_type])
+            .set(*last_received as i64);
+    }
+}
@@ -0,0 +1,232 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{ForwardingMetrics, MetricsConfig},
+    subscriptions::unix_now,
+    ForwardingCache, ScraperName,
+};
+
+const FORWARDING_HISTORY_PAGE_SIZE: u32 = 1000;
+
+/// Blocks the revenue per block is averaged over.
+const REVENUE_BLOCKS: u64 = 100;
+/// Expected time between blocks.
+const BLOCK_INTERVAL_SECS: u64 = 10 * 60;
+const DAY_SECS: u64 = 24 * 60 * 60;
+/// Longest revenue window, past which forwards are forgotten.
+const REVENUE_WINDOW_SECS: u64 = 30 * DAY_SECS;
+
+pub struct ForwardingScraper {
+    cache: Arc<Mutex<ForwardingCache>>,
+    metrics: ForwardingMetrics,
+}
+
+impl ForwardingScraper {
+    pub fn new(config: &MetricsConfig, cache: Arc<Mutex<ForwardingCache>>) -> Self {
+        Self {
+            cache,
+            metrics: ForwardingMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for ForwardingScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Forwarding.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut cache = self.cache.lock().await;
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Forwarding);
+
+        log::debug!("Scraping forwardingevents");
+
+        loop {
+            let res = lnd_client
+                .forwarding_history(lnrpc::ForwardingHistoryRequest {
+                    start_time: cache.start_time,
+                    index_offset: cache.index_offset,
+                    num_max_events: FORWARDING_HISTORY_PAGE_SIZE,
+                    ..lnrpc::ForwardingHistoryRequest::default()
+                })
+                .await;
+
+            match res {
+                Ok(res) => {
+                    record_forwardingevents_page(&mut cache, res.get_ref());
+
+                    if (res.get_ref().forwarding_events.len() as u32) < FORWARDING_HISTORY_PAGE_SIZE
+                    {
+                        break;
+                    }
+                }
+
+                Err(e) => {
+                    log::error!("Failed to collect forwardingevents metrics ERROR={:?}", e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        record_forwardingevents(&self.metrics, &mut cache);
+
+        // The windows reach back before the exporter started, which only
+        // needs fetching once. Until it succeeds, the scrape fails rather than
+        // exporting windows missing those forwards, and the next one retries.
+        let now = unix_now();
+        if !cache.fees_backfilled {
+            match forwarding_fees(lnd_client, now - REVENUE_WINDOW_SECS, cache.start_time).await {
+                Ok(fees) => {
+                    for fee in fees.into_iter().rev() {
+                        cache.fees.push_front(fee);
+                    }
+                    cache.fees_backfilled = true;
+                }
+                Err(e) => {
+                    log::error!("Failed to collect routing revenue metrics ERROR={:?}", e);
+                    return Err(e.into());
+                }
+            }
+        }
+        record_revenue(&self.metrics, &mut cache, now);
+
+        Ok(self.metrics.collect())
+    }
+}
+
+/// Timestamp and fee of the forwards from `start_time` until before
+/// `end_time`, oldest first.
+async fn forwarding_fees(
+    mut lnd_client: lnrpc::LndClient,
+    start_time: u64,
+    end_time: u64,
+) -> Result<Vec<(u64, u64)>, lnrpc::Status> {
+    let mut fees = vec![];
+    let mut index_offset = 0;
+
+    loop {
+        let res = lnd_client
+            .forwarding_history(lnrpc::ForwardingHistoryRequest {
+                start_time,
+                end_time: end_time.saturating_sub(1),
+                index_offset,
+                num_max_events: FORWARDING_HISTORY_PAGE_SIZE,
+            })
+            .await?
+            .into_inner();
+        fees.extend(
+            res.forwarding_events
+                .iter()
+                .map(|event| (event_time(event), event.fee_msat)),
+        );
+
+        if (res.forwarding_events.len() as u32) < FORWARDING_HISTORY_PAGE_SIZE {
+            return Ok(fees);
+        }
+        index_offset = res.last_offset_index;
+    }
+}
+
+fn event_time(event: &lnrpc::ForwardingEvent) -> u64 {
+    event.timestamp_ns / 1_000_000_000
+}
+
+fn record_forwardingevents_page(
+    cache: &mut ForwardingCache,
+    res: &lnrpc::ForwardingHistoryResponse,
+) {
+    for event in res.forwarding_events.iter() {
+        *cache.forwarded.entry(event.chan_id_out).or_default() += 1;
+        cache.fees.push_back((event_time(event), event.fee_msat));
+    }
+
+    cache.index_offset = cache.index_offset.max(res.last_offset_index);
+}
+
+fn record_forwardingevents(metrics: &ForwardingMetrics, cache: &mut ForwardingCache) {
+    metrics.reset();
+
+    for (chan_id, count) in cache.forwarded.iter() {
+        metrics
+            .channel_forwarded_htlcs_total
+            .with_label_values(&[&chan_id.to_string()])
+            .set(*count as i64);
+    }
+
+    for (chan_id, count) in cache.link_failed.iter() {
+        metrics
+            .channel_link_failed_htlcs_total
+            .with_label_values(&[&chan_id.to_string()])
+            .set(*count as i64);
+    }
+
+    for seconds in cache.resolution_times.drain(..) {
+        metrics.htlc_resolution_time_seconds.observe(seconds);
+    }
+}
+
+/// Sums the fees of the windows, dropping the forwards older than all of them.
+fn record_revenue(metrics: &ForwardingMetrics, cache: &mut ForwardingCache, now: u64) {
+    let window_start = now.saturating_sub(REVENUE_WINDOW_SECS);
+    while matches!(cache.fees.front(), Some((timestamp, _)) if *timestamp < window_start) {
+        cache.fees.pop_front();
+    }
+
+    let since = |secs: u64| {
+        let start = now.saturating_sub(secs);
+        cache
+            .fees
+            .iter()
+            .filter(|(timestamp, _)| *timestamp >= start)
+            .map(|(_, fee_msat)| fee_msat)
+            .sum::<u64>()
+    };
+
+    metrics
+        .routing_revenue_per_block_msat
+        .set(since(REVENUE_BLOCKS * BLOCK_INTERVAL_SECS) as f64 / REVENUE_BLOCKS as f64);
+    metrics
+        .routing_revenue_7d_msat
+        .set(since(7 * DAY_SECS) as i64);
+    metrics
+        .routing_revenue_30d_msat
+        .set(since(REVENUE_WINDOW_SECS) as i64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revenue_is_summed_over_the_windows() {
+        let metrics = ForwardingMetrics::new(&MetricsConfig::new("lnd"));
+        let now = 100 * DAY_SECS;
+        let mut cache = ForwardingCache::new();
+        cache.fees = vec![
+            (now - 31 * DAY_SECS, 1_000_000),
+            (now - 20 * DAY_SECS, 1_500_000),
+            (now - 2 * DAY_SECS, 487_655),
+            (now - 60, 12_345),
+        ]
+        .into_iter()
+        .collect();
+
+        record_revenue(&metrics, &mut cache, now);
+        assert_eq!(metrics.routing_revenue_per_block_msat.get(), 123.45);
+        assert_eq!(metrics.routing_revenue_7d_msat.get(), 500_000);
+        assert_eq!(metrics.routing_revenue_30d_msat.get(), 2_000_000);
+        assert_eq!(cache.fees.len(), 3);
+    }
+}
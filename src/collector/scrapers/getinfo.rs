@@ -0,0 +1,211 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use tokio::sync::Mutex;
+
+use super::{supports_route_blinding, ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    block_explorer::BlockExplorer,
+    metrics::{GetInfoMetrics, MetricsConfig, SyncProgressMetrics},
+    NodeInfo, ScraperName,
+};
+
+/// Names of the constant labels identifying the lnd node.
+pub const NODE_LABELS: &[&str] = &["node_pubkey", "node_alias", "chain", "network"];
+
+pub struct GetInfoScraper {
+    metrics: GetInfoMetrics,
+    block_explorer: Option<BlockExplorer>,
+    sync_progress_metrics: SyncProgressMetrics,
+    /// Node labels of the last successful scrape.
+    node_labels: std::sync::Mutex<HashMap<String, String>>,
+    /// Response of the last successful scrape.
+    last_info: std::sync::Mutex<Option<lnrpc::GetInfoResponse>>,
+    node_info: Arc<Mutex<NodeInfo>>,
+}
+
+impl GetInfoScraper {
+    pub fn new(
+        config: &MetricsConfig,
+        block_explorer: Option<BlockExplorer>,
+        node_info: Arc<Mutex<NodeInfo>>,
+    ) -> Self {
+        Self {
+            metrics: GetInfoMetrics::new(config),
+            block_explorer,
+            sync_progress_metrics: SyncProgressMetrics::new(config),
+            node_labels: std::sync::Mutex::new(HashMap::new()),
+            last_info: std::sync::Mutex::new(None),
+            node_info,
+        }
+    }
+
+    pub fn node_labels(&self) -> HashMap<String, String> {
+        self.node_labels.lock().expect("node labels lock").clone()
+    }
+
+    pub fn last_info(&self) -> Option<lnrpc::GetInfoResponse> {
+        self.last_info.lock().expect("last info lock").clone()
+    }
+}
+
+#[async_trait]
+impl Scraper for GetInfoScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::GetInfo.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        let mut desc = self.metrics.desc();
+        if self.block_explorer.is_some() {
+            desc.extend(self.sync_progress_metrics.desc());
+        }
+        desc
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::GetInfo);
+
+        log::debug!("Scraping getinfo");
+
+        let res = lnd_client.get_info(lnrpc::GetInfoRequest {}).await;
+
+        match res {
+            Ok(res) => {
+                record_getinfo(&self.metrics, res.get_ref());
+                *self.node_labels.lock().expect("node labels lock") = node_labels(res.get_ref());
+                *self.last_info.lock().expect("last info lock") = Some(res.get_ref().clone());
+                self.node_info.lock().await.self_pubkey =
+                    Some(res.get_ref().identity_pubkey.clone());
+
+                let mut metrics = self.metrics.collect();
+                if let Some(block_explorer) = &self.block_explorer {
+                    scrape_sync_progress(
+                        block_explorer,
+                        res.get_ref(),
+                        &self.sync_progress_metrics,
+                    )
+                    .await;
+                    metrics.extend(self.sync_progress_metrics.collect());
+                }
+
+                Ok(metrics)
+            }
+
+            Err(e) => {
+                log::error!("Failed to collect getinfo metrics ERROR={:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
+
+fn record_getinfo(metrics: &GetInfoMetrics, res: &lnrpc::GetInfoResponse) {
+    metrics.reset();
+
+    metrics.num_peers_total.set(res.num_peers.into());
+    metrics.block_height.set(res.block_height.into());
+
+    for (bit, feature) in res.features.iter() {
+        metrics
+            .node_feature_bit
+            .with_label_values(&[
+                &bit.to_string(),
+                &feature.name,
+                &feature.is_required.to_string(),
+            ])
+            .set(1);
+    }
+    metrics
+        .node_feature_count_total
+        .set(res.features.len() as i64);
+    metrics
+        .node_blinded_paths_supported
+        .set(supports_route_blinding(&res.features).into());
+}
+
+/// Constant labels identifying the node, see [`NODE_LABELS`]. Labels lnd left
+/// empty are omitted.
+pub fn node_labels(info: &lnrpc::GetInfoResponse) -> HashMap<String, String> {
+    let chain = info.chains.first().cloned().unwrap_or_default();
+
+    NODE_LABELS
+        .iter()
+        .map(|name| name.to_string())
+        .zip(vec![
+            info.identity_pubkey.clone(),
+            info.alias.clone(),
+            chain.chain,
+            chain.network,
+        ])
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+}
+
+/// Sets the sync progress against the block explorer's chain tip. Failures keep
+/// the last known progress, since they say nothing about lnd itself.
+async fn scrape_sync_progress(
+    block_explorer: &BlockExplorer,
+    info: &lnrpc::GetInfoResponse,
+    metrics: &SyncProgressMetrics,
+) {
+    if info.synced_to_chain {
+        metrics.chain_sync_progress_percent.set(100.0);
+        return;
+    }
+
+    match block_explorer.tip_height().await {
+        Ok(tip_height) if tip_height > 0 => metrics
+            .chain_sync_progress_percent
+            .set((info.block_height as f64 / tip_height as f64 * 100.0).min(100.0)),
+        Ok(_) => log::warn!("Block explorer reported an empty chain"),
+        Err(e) => log::error!("Failed to fetch chain tip height ERROR={:?}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_is_unchanged() {
+        let metrics = GetInfoMetrics::new(&MetricsConfig::new("lnd"));
+        record_getinfo(
+            &metrics,
+            &lnrpc::GetInfoResponse {
+                num_peers: 3,
+                block_height: 700_000,
+                features: vec![
+                    (
+                        0,
+                        lnrpc::Feature {
+                            name: "data-loss-protect".to_string(),
+                            is_required: true,
+                            is_known: true,
+                        },
+                    ),
+                    (
+                        23,
+                        lnrpc::Feature {
+                            name: "anchors-zero-fee-htlc-tx".to_string(),
+                            is_required: false,
+                            is_known: true,
+                        },
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                ..lnrpc::GetInfoResponse::default()
+            },
+        );
+
+        assert_eq!(
+            super::super::encode(metrics),
+            include_str!("testdata/getinfo.prom")
+        );
+    }
+}
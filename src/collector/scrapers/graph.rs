@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{GraphMetrics, MetricsConfig},
+    ScraperName,
+};
+
+pub struct GraphScraper {
+    metrics: GraphMetrics,
+}
+
+impl GraphScraper {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            metrics: GraphMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for GraphScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Graph.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Graph);
+
+        log::debug!("Scraping local graph view");
+
+        let res = async {
+            let info = lnd_client.get_info(lnrpc::GetInfoRequest {}).await?;
+            let graph = lnd_client
+                .describe_graph(lnrpc::ChannelGraphRequest {
+                    include_unannounced: false,
+                })
+                .await?;
+            // The graph only holds announced channels, so compare against public ones
+            let channels = lnd_client
+                .list_channels(lnrpc::ListChannelsRequest {
+                    active_only: true,
+                    public_only: true,
+                    ..lnrpc::ListChannelsRequest::default()
+                })
+                .await?;
+
+            Ok::<_, lnrpc::Status>((info, graph, channels))
+        }
+        .await;
+
+        match res {
+            Ok((info, graph, channels)) => {
+                record_local_graph_view(
+                    &self.metrics,
+                    info.get_ref(),
+                    graph.get_ref(),
+                    channels.get_ref(),
+                );
+                Ok(self.metrics.collect())
+            }
+
+            Err(e) => {
+                log::error!("Failed to collect local graph view metrics ERROR={:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
+
+fn record_local_graph_view(
+    metrics: &GraphMetrics,
+    info: &lnrpc::GetInfoResponse,
+    graph: &lnrpc::ChannelGraph,
+    channels: &lnrpc::ListChannelsResponse,
+) {
+    metrics.reset();
+    let self_pubkey = &info.identity_pubkey;
+
+    for edge in graph.edges.iter() {
+        let local_policy = if &edge.node1_pub == self_pubkey {
+            &edge.node1_policy
+        } else if &edge.node2_pub == self_pubkey {
+            &edge.node2_policy
+        } else {
+            continue;
+        };
+
+        metrics.graph_local_edges_total.inc();
+        if local_policy.as_ref().is_some_and(|p| p.disabled) {
+            metrics.graph_local_edges_disabled_total.inc();
+        }
+    }
+
+    metrics
+        .graph_local_channels_delta
+        .set(metrics.graph_local_edges_total.get() - channels.channels.len() as i64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph_counts_reset_between_scrapes() {
+        let metrics = GraphMetrics::new(&MetricsConfig::new("lnd"));
+        let info = lnrpc::GetInfoResponse {
+            identity_pubkey: "self".to_string(),
+            ..lnrpc::GetInfoResponse::default()
+        };
+        let graph = lnrpc::ChannelGraph {
+            edges: vec![lnrpc::ChannelEdge {
+                node1_pub: "self".to_string(),
+                ..lnrpc::ChannelEdge::default()
+            }],
+            ..lnrpc::ChannelGraph::default()
+        };
+
+        for _ in 0..2 {
+            record_local_graph_view(
+                &metrics,
+                &info,
+                &graph,
+                &lnrpc::ListChannelsResponse::default(),
+            );
+        }
+
+        assert_eq!(metrics.graph_local_edges_total.get(), 1);
+        assert_eq!(metrics.graph_local_channels_delta.get(), 1);
+    }
+}
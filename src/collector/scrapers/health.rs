@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{HealthMetrics, MetricsConfig},
+    ScraperName,
+};
+
+/// Services checked through the gRPC health check, where the empty service name
+/// stands for the server as a whole.
+const HEALTH_CHECK_SERVICES: &[&str] = &["", "lnrpc.Lightning"];
+
+pub struct HealthScraper {
+    metrics: HealthMetrics,
+}
+
+impl HealthScraper {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            metrics: HealthMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for HealthScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Health.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut health_client = ctx.lnd_clients.health();
+
+        log::debug!("Scraping health");
+
+        let mut statuses = vec![];
+
+        for service in HEALTH_CHECK_SERVICES {
+            let res = health_client
+                .check(lnrpc::health::HealthCheckRequest {
+                    service: service.to_string(),
+                })
+                .await;
+
+            let serving = match res {
+                Ok(res) => {
+                    res.get_ref().status()
+                        == lnrpc::health::health_check_response::ServingStatus::Serving
+                }
+
+                // Unknown services are reported as NotFound
+                Err(e) if e.code() == lnrpc::Code::NotFound => false,
+
+                Err(e) => {
+                    log::error!("Failed to collect health metrics ERROR={:?}", e);
+                    return Err(e.into());
+                }
+            };
+
+            statuses.push((*service, serving));
+        }
+
+        record_health(&self.metrics, &statuses);
+        Ok(self.metrics.collect())
+    }
+}
+
+fn record_health(metrics: &HealthMetrics, statuses: &[(&str, bool)]) {
+    metrics.reset();
+
+    for (service, serving) in statuses {
+        metrics
+            .grpc_healthy
+            .with_label_values(&[service])
+            .set((*serving).into());
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{HubDistancesMetrics, MetricsConfig},
+    ScraperName,
+};
+
+/// Interval the distances are recomputed at, since the whole graph is fetched
+/// and they rarely change.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Exports the number of hops from the node to each configured routing hub
+/// through the public graph. Unreachable hubs are left out.
+pub struct HubDistancesScraper {
+    hub_pubkeys: Vec<String>,
+    last_refresh: Mutex<Option<Instant>>,
+    metrics: HubDistancesMetrics,
+}
+
+impl HubDistancesScraper {
+    pub fn new(config: &MetricsConfig, hub_pubkeys: Vec<String>) -> Self {
+        Self {
+            hub_pubkeys,
+            last_refresh: Mutex::new(None),
+            metrics: HubDistancesMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for HubDistancesScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::HubDistances.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut last_refresh = self.last_refresh.lock().await;
+        if matches!(*last_refresh, Some(t) if t.elapsed() < REFRESH_INTERVAL) {
+            return Ok(self.metrics.collect());
+        }
+
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::HubDistances);
+        scrape_hub_distances(&mut lnd_client, &self.hub_pubkeys, &self.metrics).await?;
+        *last_refresh = Some(Instant::now());
+
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_hub_distances(
+    lnd_client: &mut lnrpc::LndClient,
+    hub_pubkeys: &[String],
+    metrics: &HubDistancesMetrics,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping hub distances");
+
+    let res = async {
+        let info = lnd_client.get_info(lnrpc::GetInfoRequest {}).await?;
+        let graph = lnd_client
+            .describe_graph(lnrpc::ChannelGraphRequest {
+                include_unannounced: false,
+            })
+            .await?;
+
+        Ok::<_, lnrpc::Status>((info.into_inner(), graph.into_inner()))
+    }
+    .await;
+
+    match res {
+        Ok((info, graph)) => {
+            // The search walks the whole graph, keep it off the runtime threads
+            let hub_pubkeys = hub_pubkeys.to_vec();
+            let distances = tokio::task::spawn_blocking(move || {
+                hub_distances(&info.identity_pubkey, &graph, &hub_pubkeys)
+            })
+            .await
+            .expect("hub distances");
+
+            record_hub_distances(metrics, &distances);
+            Ok(())
+        }
+
+        Err(e) => {
+            log::error!("Failed to collect hub distance metrics ERROR={:?}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Alias, pubkey and hop distance of each hub reachable from the node.
+type HubDistances = Vec<(String, String, u32)>;
+
+/// Runs a breadth first search of the graph from the node, returning the hubs
+/// it reached.
+fn hub_distances(
+    self_pubkey: &str,
+    graph: &lnrpc::ChannelGraph,
+    hub_pubkeys: &[String],
+) -> HubDistances {
+    let mut neighbors = HashMap::<&str, Vec<&str>>::new();
+    for edge in graph.edges.iter() {
+        neighbors
+            .entry(&edge.node1_pub)
+            .or_default()
+            .push(&edge.node2_pub);
+        neighbors
+            .entry(&edge.node2_pub)
+            .or_default()
+            .push(&edge.node1_pub);
+    }
+
+    let mut distances = HashMap::<&str, u32>::new();
+    distances.insert(self_pubkey, 0);
+    let mut queue = VecDeque::from([self_pubkey]);
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[node];
+        for neighbor in neighbors.get(node).into_iter().flatten() {
+            if !distances.contains_key(neighbor) {
+                distances.insert(neighbor, distance + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    let aliases = graph
+        .nodes
+        .iter()
+        .map(|node| (node.pub_key.as_str(), node.alias.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    hub_pubkeys
+        .iter()
+        .filter_map(|pubkey| {
+            let distance = *distances.get(pubkey.as_str())?;
+            let alias = aliases.get(pubkey.as_str()).copied().unwrap_or_default();
+
+            Some((alias.to_string(), pubkey.clone(), distance))
+        })
+        .collect()
+}
+
+fn record_hub_distances(metrics: &HubDistancesMetrics, distances: &HubDistances) {
+    // Drop the series of hubs no longer reachable
+    metrics.reset();
+
+    for (alias, pubkey, distance) in distances.iter() {
+        metrics
+            .node_graph_distance_to_hub
+            .with_label_values(&[alias, pubkey])
+            .set((*distance).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hubs_are_found_by_shortest_path() {
+        let edge = |node1_pub: &str, node2_pub: &str| lnrpc::ChannelEdge {
+            node1_pub: node1_pub.to_string(),
+            node2_pub: node2_pub.to_string(),
+            ..lnrpc::ChannelEdge::default()
+        };
+        let graph = lnrpc::ChannelGraph {
+            nodes: vec![lnrpc::LightningNode {
+                pub_key: "hub".to_string(),
+                alias: "ACINQ".to_string(),
+                ..lnrpc::LightningNode::default()
+            }],
+            // self - a - b - hub, with a shortcut self - c - hub
+            edges: vec![
+                edge("self", "a"),
+                edge("a", "b"),
+                edge("hub", "b"),
+                edge("self", "c"),
+                edge("c", "hub"),
+                edge("island", "other"),
+            ],
+        };
+
+        let distances = hub_distances(
+            "self",
+            &graph,
+            &["hub".to_string(), "island".to_string(), "a".to_string()],
+        );
+        assert_eq!(
+            distances,
+            vec![
+                ("ACINQ".to_string(), "hub".to_string(), 2),
+                ("".to_string(), "a".to_string(), 1),
+            ]
+        );
+
+        let metrics = HubDistancesMetrics::new(&MetricsConfig::new("lnd"));
+        record_hub_distances(&metrics, &distances);
+        assert_eq!(
+            metrics
+                .node_graph_distance_to_hub
+                .with_label_values(&["ACINQ", "hub"])
+                .get(),
+            2
+        );
+    }
+}
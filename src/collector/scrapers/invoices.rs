@@ -0,0 +1,339 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{InvoiceProofMetrics, InvoicesMetrics, MetricsConfig},
+    subscriptions::unix_now,
+    InvoiceProof, ListInvoicesCache, ScraperName, INVOICE_CREATION_WINDOW,
+};
+
+const LIST_INVOICES_PAGE_SIZE: u64 = 1000;
+
+/// Length of the payment address AMP invoices must have.
+const AMP_PAYMENT_ADDR_LEN: usize = 32;
+
+pub struct InvoicesScraper {
+    cache: Mutex<ListInvoicesCache>,
+    metrics: InvoicesMetrics,
+    /// Exports per-invoice settlement proofs, one series per settled invoice.
+    proof_metrics: Option<InvoiceProofMetrics>,
+}
+
+impl InvoicesScraper {
+    pub fn new(config: &MetricsConfig, invoice_proofs: bool) -> Self {
+        Self {
+            cache: Mutex::new(ListInvoicesCache::default()),
+            metrics: InvoicesMetrics::new(config),
+            proof_metrics: invoice_proofs.then(|| InvoiceProofMetrics::new(config)),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for InvoicesScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Invoices.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        let mut desc = self.metrics.desc();
+        if let Some(proof_metrics) = &self.proof_metrics {
+            desc.extend(proof_metrics.desc());
+        }
+        desc
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut cache = self.cache.lock().await;
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Invoices);
+
+        log::debug!("Scraping listinvoices");
+
+        // Invoices still open may be settled or cancelled later, so resume from the
+        // oldest of them instead of the last seen invoice.
+        let mut index_offset = cache
+            .open_invoices
+            .iter()
+            .next()
+            .map_or(cache.index_offset, |add_index| add_index - 1);
+
+        loop {
+            let res = lnd_client
+                .list_invoices(lnrpc::ListInvoiceRequest {
+                    index_offset,
+                    num_max_invoices: LIST_INVOICES_PAGE_SIZE,
+                    ..lnrpc::ListInvoiceRequest::default()
+                })
+                .await;
+
+            match res {
+                Ok(res) => {
+                    record_listinvoices_page(
+                        &mut cache,
+                        res.get_ref(),
+                        self.proof_metrics.is_some(),
+                    );
+
+                    if (res.get_ref().invoices.len() as u64) < LIST_INVOICES_PAGE_SIZE {
+                        break;
+                    }
+                    index_offset = res.get_ref().last_index_offset;
+                }
+
+                Err(e) => {
+                    log::error!("Failed to collect listinvoices metrics ERROR={:?}", e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        record_listinvoices(&self.metrics, &cache, unix_now() as i64);
+        let mut metrics = self.metrics.collect();
+        if let Some(proof_metrics) = &self.proof_metrics {
+            record_invoice_proofs(proof_metrics, &cache);
+            metrics.extend(proof_metrics.collect());
+        }
+
+        Ok(metrics)
+    }
+}
+
+fn record_listinvoices_page(
+    cache: &mut ListInvoicesCache,
+    res: &lnrpc::ListInvoiceResponse,
+    track_proofs: bool,
+) {
+    for invoice in res.invoices.iter() {
+        let is_new = invoice.add_index > cache.index_offset;
+        if !is_new && !cache.open_invoices.contains(&invoice.add_index) {
+            continue;
+        }
+
+        if track_proofs {
+            if let Some(proof) = invoice_proof(invoice) {
+                cache
+                    .invoice_proofs
+                    .insert(hex::encode(&invoice.r_hash), proof);
+            }
+        }
+
+        if is_new {
+            if cache.creation_dates.len() == INVOICE_CREATION_WINDOW {
+                cache.creation_dates.pop_front();
+            }
+            cache.creation_dates.push_back(invoice.creation_date);
+        }
+
+        match invoice.state() {
+            lnrpc::invoice::InvoiceState::Settled => {
+                cache.settled_value_msat += invoice.amt_paid_msat;
+                cache.settled_count += 1;
+                cache.last_settled_timestamp =
+                    cache.last_settled_timestamp.max(invoice.settle_date);
+                cache.open_invoices.remove(&invoice.add_index);
+            }
+            lnrpc::invoice::InvoiceState::Canceled => {
+                cache.cancelled_count += 1;
+                cache.open_invoices.remove(&invoice.add_index);
+            }
+            lnrpc::invoice::InvoiceState::Open | lnrpc::invoice::InvoiceState::Accepted => {
+                cache.open_invoices.insert(invoice.add_index);
+            }
+        }
+
+        cache.index_offset = cache.index_offset.max(invoice.add_index);
+    }
+}
+
+fn record_listinvoices(metrics: &InvoicesMetrics, cache: &ListInvoicesCache, now: i64) {
+    metrics.reset();
+
+    metrics
+        .invoice_settled_value_msat_total
+        .set(cache.settled_value_msat);
+    metrics.invoice_settled_count_total.set(cache.settled_count);
+    metrics
+        .invoice_cancelled_count_total
+        .set(cache.cancelled_count);
+    metrics
+        .invoice_last_settled_timestamp_seconds
+        .set(cache.last_settled_timestamp);
+
+    let created_since = |since: i64| {
+        cache
+            .creation_dates
+            .iter()
+            .filter(|creation_date| **creation_date > since)
+            .count() as f64
+    };
+    metrics
+        .invoice_creation_rate_per_minute
+        .set(created_since(now - 60));
+    metrics
+        .invoice_creation_rate_per_hour
+        .set(created_since(now - 3600));
+}
+
+fn record_invoice_proofs(metrics: &InvoiceProofMetrics, cache: &ListInvoicesCache) {
+    metrics.reset();
+
+    for (payment_hash, proof) in cache.invoice_proofs.iter() {
+        match proof {
+            InvoiceProof::Preimage(has_preimage) => metrics
+                .invoice_has_preimage
+                .with_label_values(&[payment_hash])
+                .set((*has_preimage).into()),
+            InvoiceProof::AmpSettled(settled) => metrics
+                .invoice_amp_settle_indices
+                .with_label_values(&[payment_hash])
+                .set(*settled as i64),
+        }
+    }
+}
+
+/// Settlement proof of the invoice, if it was settled at least once. AMP
+/// invoices are settled by sub-invoices, each with its own preimage, rather
+/// than by the invoice preimage.
+fn invoice_proof(invoice: &lnrpc::Invoice) -> Option<InvoiceProof> {
+    if is_amp(invoice) {
+        let settled_sets = invoice
+            .htlcs
+            .iter()
+            .filter(|htlc| htlc.state() == lnrpc::InvoiceHtlcState::Settled)
+            .filter_map(|htlc| htlc.amp.as_ref())
+            .map(|amp| &amp.set_id)
+            .collect::<std::collections::HashSet<_>>();
+
+        if settled_sets.is_empty() {
+            return None;
+        }
+
+        return Some(InvoiceProof::AmpSettled(settled_sets.len() as u64));
+    }
+
+    if invoice.state() != lnrpc::invoice::InvoiceState::Settled {
+        return None;
+    }
+
+    Some(InvoiceProof::Preimage(!invoice.r_preimage.is_empty()))
+}
+
+fn is_amp(invoice: &lnrpc::Invoice) -> bool {
+    let has_amp_feature = [lnrpc::FeatureBit::AmpReq, lnrpc::FeatureBit::AmpOpt]
+        .iter()
+        .any(|bit| invoice.features.contains_key(&(*bit as u32)));
+
+    invoice.is_amp || (invoice.payment_addr.len() == AMP_PAYMENT_ADDR_LEN && has_amp_feature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settled_htlc(set_id: Option<u8>) -> lnrpc::InvoiceHtlc {
+        lnrpc::InvoiceHtlc {
+            state: lnrpc::InvoiceHtlcState::Settled as i32,
+            amp: set_id.map(|set_id| lnrpc::Amp {
+                set_id: vec![set_id; 32],
+                ..lnrpc::Amp::default()
+            }),
+            ..lnrpc::InvoiceHtlc::default()
+        }
+    }
+
+    #[test]
+    fn invoice_proofs() {
+        let settled = |r_preimage: Vec<u8>| lnrpc::Invoice {
+            state: lnrpc::invoice::InvoiceState::Settled as i32,
+            r_preimage,
+            htlcs: vec![settled_htlc(None)],
+            ..lnrpc::Invoice::default()
+        };
+        assert_eq!(
+            invoice_proof(&settled(vec![1; 32])),
+            Some(InvoiceProof::Preimage(true))
+        );
+        assert_eq!(
+            invoice_proof(&settled(vec![])),
+            Some(InvoiceProof::Preimage(false))
+        );
+        assert_eq!(invoice_proof(&lnrpc::Invoice::default()), None);
+
+        // AMP invoices stay open while their sub-invoices are settled
+        let amp = lnrpc::Invoice {
+            payment_addr: vec![0; 32],
+            features: std::iter::once((
+                lnrpc::FeatureBit::AmpOpt as u32,
+                lnrpc::Feature::default(),
+            ))
+            .collect(),
+            htlcs: vec![
+                settled_htlc(Some(1)),
+                settled_htlc(Some(1)),
+                settled_htlc(Some(2)),
+            ],
+            ..lnrpc::Invoice::default()
+        };
+        assert_eq!(invoice_proof(&amp), Some(InvoiceProof::AmpSettled(2)));
+    }
+
+    #[test]
+    fn creation_rates_count_recent_invoices() {
+        let now = 1_700_000_000;
+        let open = |add_index, age| lnrpc::Invoice {
+            add_index,
+            creation_date: now - age,
+            state: lnrpc::invoice::InvoiceState::Open as i32,
+            ..lnrpc::Invoice::default()
+        };
+        let mut cache = ListInvoicesCache::default();
+        let res = lnrpc::ListInvoiceResponse {
+            invoices: vec![open(1, 7200), open(2, 1800), open(3, 90), open(4, 30)],
+            ..lnrpc::ListInvoiceResponse::default()
+        };
+
+        // Open invoices are listed again until settled, but only created once
+        record_listinvoices_page(&mut cache, &res, false);
+        record_listinvoices_page(&mut cache, &res, false);
+
+        let metrics = InvoicesMetrics::new(&MetricsConfig::new("lnd"));
+        record_listinvoices(&metrics, &cache, now);
+        assert_eq!(metrics.invoice_creation_rate_per_minute.get(), 1.0);
+        assert_eq!(metrics.invoice_creation_rate_per_hour.get(), 3.0);
+    }
+
+    #[test]
+    fn last_settled_timestamp_only_moves_forward() {
+        let settled = |add_index, settle_date| lnrpc::Invoice {
+            add_index,
+            settle_date,
+            state: lnrpc::invoice::InvoiceState::Settled as i32,
+            ..lnrpc::Invoice::default()
+        };
+        let mut cache = ListInvoicesCache::default();
+        let metrics = InvoicesMetrics::new(&MetricsConfig::new("lnd"));
+
+        record_listinvoices(&metrics, &cache, 1_700_000_000);
+        assert_eq!(metrics.invoice_last_settled_timestamp_seconds.get(), 0);
+
+        // Invoices are listed by add index, not settle date
+        record_listinvoices_page(
+            &mut cache,
+            &lnrpc::ListInvoiceResponse {
+                invoices: vec![settled(1, 1_700_000_500), settled(2, 1_700_000_100)],
+                ..lnrpc::ListInvoiceResponse::default()
+            },
+            false,
+        );
+        record_listinvoices(&metrics, &cache, 1_700_001_000);
+        assert_eq!(
+            metrics.invoice_last_settled_timestamp_seconds.get(),
+            1_700_000_500
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    bitcoind::{BitcoindClient, MempoolInfo},
+    metrics::{MempoolMetrics, MetricsConfig},
+};
+
+/// Scrapes the mempool of a Bitcoin Core node rather than lnd.
+pub struct MempoolScraper {
+    bitcoind: BitcoindClient,
+    metrics: MempoolMetrics,
+}
+
+impl MempoolScraper {
+    pub fn new(config: &MetricsConfig, bitcoind: BitcoindClient) -> Self {
+        Self {
+            bitcoind,
+            metrics: MempoolMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for MempoolScraper {
+    fn name(&self) -> &'static str {
+        "mempool"
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, _ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        scrape_mempool(&self.bitcoind, &self.metrics).await?;
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_mempool(
+    bitcoind: &BitcoindClient,
+    metrics: &MempoolMetrics,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping getmempoolinfo");
+
+    match bitcoind.mempool_info().await {
+        Ok(info) => {
+            record_mempool_info(metrics, &info);
+            Ok(())
+        }
+
+        Err(e) => {
+            log::error!("Failed to collect mempool metrics ERROR={:?}", e);
+            Err(ScrapeError::Bitcoind(e))
+        }
+    }
+}
+
+fn record_mempool_info(metrics: &MempoolMetrics, info: &MempoolInfo) {
+    metrics.reset();
+
+    metrics.mempool_tx_count.set(info.tx_count as i64);
+    metrics.mempool_vbytes.set(info.vbytes as i64);
+    // 1 BTC/kvB = 100_000_000 sat / 1_000 vB
+    metrics
+        .mempool_min_fee_rate_sat_per_vb
+        .set(info.min_fee_btc_per_kvb * 100_000.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_fee_rate_is_converted_to_sat_per_vb() {
+        let metrics = MempoolMetrics::new(&MetricsConfig::new("lnd"));
+        record_mempool_info(
+            &metrics,
+            &MempoolInfo {
+                tx_count: 42,
+                vbytes: 10_000,
+                min_fee_btc_per_kvb: 0.00001,
+            },
+        );
+
+        assert_eq!(metrics.mempool_tx_count.get(), 42);
+        assert_eq!(metrics.mempool_vbytes.get(), 10_000);
+        assert!((metrics.mempool_min_fee_rate_sat_per_vb.get() - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,418 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{MetricsConfig, PaymentsMetrics},
+    ListPaymentsCache, NodeInfo, ScraperName,
+};
+
+/// Length pubkeys are truncated to in destination labels.
+const DESTINATION_PUB_KEY_LEN: usize = 16;
+
+pub struct PaymentsScraper {
+    cache: Mutex<ListPaymentsCache>,
+    metrics: PaymentsMetrics,
+    /// Number of most paid destinations exported.
+    top_destinations: usize,
+    node_info: Arc<Mutex<NodeInfo>>,
+}
+
+impl PaymentsScraper {
+    pub fn new(
+        config: &MetricsConfig,
+        top_destinations: usize,
+        amount_window: usize,
+        node_info: Arc<Mutex<NodeInfo>>,
+    ) -> Self {
+        Self {
+            cache: Mutex::new(ListPaymentsCache::new(amount_window)),
+            metrics: PaymentsMetrics::new(config),
+            top_destinations,
+            node_info,
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for PaymentsScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Payments.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        // Prevent concurrent listpayments scrapes from double counting payments
+        log::debug!("Acquiring listpayments cache lock");
+        let mut cache = self.cache.lock().await;
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Payments);
+
+        log::debug!("Scraping listpayments");
+
+        let res = lnd_client
+            .list_payments(lnrpc::ListPaymentsRequest {
+                include_incomplete: true,
+                index_offset: cache.index_offset,
+                ..lnrpc::ListPaymentsRequest::default()
+            })
+            .await;
+
+        match res {
+            Ok(res) => {
+                // Known by now, GetInfo runs before the other scrapers
+                let self_pubkey = self.node_info.lock().await.self_pubkey.clone();
+                record_listpayments(
+                    &self.metrics,
+                    &mut cache,
+                    res.get_ref(),
+                    self.top_destinations,
+                    self_pubkey.as_deref(),
+                );
+                Ok(self.metrics.collect())
+            }
+
+            Err(e) => {
+                log::error!("Failed to collect listpayments metrics ERROR={:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
+
+fn record_listpayments(
+    metrics: &PaymentsMetrics,
+    cache: &mut ListPaymentsCache,
+    res: &lnrpc::ListPaymentsResponse,
+    top_destinations: usize,
+    self_pubkey: Option<&str>,
+) {
+    if res.last_index_offset > 0 {
+        cache.index_offset = res.last_index_offset;
+
+        for payment in res.payments.iter() {
+            *cache.outgoing_payments.entry(payment.status()).or_default() += 1;
+
+            *cache
+                .payment_failure_reasons
+                .entry(payment.failure_reason())
+                .or_default() += 1;
+
+            cache.total_fee_msat += payment.fee_msat;
+
+            let attempts = payment.htlcs.len() as u64;
+            let retries = match payment.status() {
+                lnrpc::payment::PaymentStatus::Succeeded => attempts.saturating_sub(1),
+                lnrpc::payment::PaymentStatus::Failed => attempts,
+                _ => continue,
+            };
+
+            metrics.payment_retry_count.observe(retries as f64);
+            cache.completed_payments += 1;
+            if payment.status() == lnrpc::payment::PaymentStatus::Succeeded && attempts == 1 {
+                cache.single_attempt_payments += 1;
+            }
+
+            if payment.status() == lnrpc::payment::PaymentStatus::Succeeded {
+                if let Some(destination) = destination(payment) {
+                    *cache.destinations.entry(destination).or_default() += 1;
+                }
+
+                if self_pubkey.is_some() && destination_pub_key(payment) == self_pubkey {
+                    cache.circular_rebalances += 1;
+                    cache.circular_rebalance_fee_msat += payment.fee_msat;
+                    cache.circular_rebalance_amount_msat += payment.value_msat;
+                }
+
+                cache.min_fee_msat = cache.min_fee_msat.min(payment.fee_msat);
+                cache.max_fee_msat = cache.max_fee_msat.max(payment.fee_msat);
+                cache.recent_fees_msat.push(payment.fee_msat);
+                cache
+                    .recent_amounts_msat
+                    .push(payment.value_msat.max(0) as u64);
+            }
+        }
+    }
+
+    metrics.reset();
+
+    for (status, count) in cache.outgoing_payments.iter() {
+        let status_str = match status {
+            lnrpc::payment::PaymentStatus::Unknown => "unknown",
+            lnrpc::payment::PaymentStatus::InFlight => "in_flight",
+            lnrpc::payment::PaymentStatus::Succeeded => "succeeded",
+            lnrpc::payment::PaymentStatus::Failed => "failed",
+        };
+
+        metrics
+            .outgoing_payments
+            .with_label_values(&[status_str])
+            .set(*count);
+    }
+
+    for (reason, count) in cache.payment_failure_reasons.iter() {
+        let reason_str = match reason {
+            lnrpc::PaymentFailureReason::FailureReasonNone => "none",
+            lnrpc::PaymentFailureReason::FailureReasonTimeout => "timeout",
+            lnrpc::PaymentFailureReason::FailureReasonNoRoute => "no_route",
+            lnrpc::PaymentFailureReason::FailureReasonError => "error",
+            lnrpc::PaymentFailureReason::FailureReasonIncorrectPaymentDetails => {
+                "incorrect_payment_details"
+            }
+            lnrpc::PaymentFailureReason::FailureReasonInsufficientBalance => "insufficient_balance",
+        };
+
+        metrics
+            .payment_failure_reasons
+            .with_label_values(&[reason_str])
+            .set(*count);
+    }
+
+    metrics.total_fee_msat.set(cache.total_fee_msat);
+    metrics
+        .circular_rebalance_count_total
+        .set(cache.circular_rebalances);
+    metrics
+        .circular_rebalance_fee_msat_total
+        .set(cache.circular_rebalance_fee_msat);
+    metrics
+        .circular_rebalance_amount_msat_total
+        .set(cache.circular_rebalance_amount_msat);
+
+    if cache.completed_payments > 0 {
+        metrics
+            .payment_single_attempt_ratio
+            .set(cache.single_attempt_payments as f64 / cache.completed_payments as f64);
+    }
+
+    if let Some(median_fee_msat) = cache.recent_fees_msat.median() {
+        metrics.payment_min_fee_msat.set(cache.min_fee_msat);
+        metrics.payment_max_fee_msat.set(cache.max_fee_msat);
+        metrics.payment_median_fee_msat.set(median_fee_msat);
+    }
+
+    let amount_quantiles = [
+        (&metrics.payment_amount_p50_msat, 0.5),
+        (&metrics.payment_amount_p90_msat, 0.9),
+        (&metrics.payment_amount_p99_msat, 0.99),
+    ];
+    for (gauge, quantile) in amount_quantiles.iter() {
+        if let Some(amount_msat) = cache.recent_amounts_msat.quantile(*quantile) {
+            gauge.set(amount_msat as i64);
+        }
+    }
+
+    // Only the most paid destinations are exported to bound the label space
+    let mut destinations = cache.destinations.iter().collect::<Vec<_>>();
+    destinations
+        .sort_by(|(a_key, a_count), (b_key, b_count)| b_count.cmp(a_count).then(a_key.cmp(b_key)));
+    for (pub_key, count) in destinations.into_iter().take(top_destinations) {
+        metrics
+            .payment_destination_total
+            .with_label_values(&[pub_key])
+            .set(*count as i64);
+    }
+}
+
+/// Pubkey of the payment's final hop, taken from its first HTLC.
+fn destination_pub_key(payment: &lnrpc::Payment) -> Option<&str> {
+    let hop = payment.htlcs.first()?.route.as_ref()?.hops.last()?;
+
+    Some(&hop.pub_key)
+}
+
+/// Truncated pubkey of the payment's final hop.
+fn destination(payment: &lnrpc::Payment) -> Option<String> {
+    destination_pub_key(payment)
+        .map(|pub_key| pub_key.chars().take(DESTINATION_PUB_KEY_LEN).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_is_unchanged() {
+        let metrics = PaymentsMetrics::new(&MetricsConfig::new("lnd"));
+        record_listpayments(
+            &metrics,
+            &mut ListPaymentsCache::default(),
+            &lnrpc::ListPaymentsResponse {
+                payments: vec![
+                    lnrpc::Payment {
+                        status: lnrpc::payment::PaymentStatus::Succeeded as i32,
+                        fee_msat: 1_000,
+                        htlcs: vec![lnrpc::HtlcAttempt::default(), lnrpc::HtlcAttempt::default()],
+                        ..lnrpc::Payment::default()
+                    },
+                    lnrpc::Payment {
+                        status: lnrpc::payment::PaymentStatus::Failed as i32,
+                        failure_reason: lnrpc::PaymentFailureReason::FailureReasonNoRoute as i32,
+                        htlcs: vec![lnrpc::HtlcAttempt::default()],
+                        ..lnrpc::Payment::default()
+                    },
+                ],
+                last_index_offset: 2,
+                ..lnrpc::ListPaymentsResponse::default()
+            },
+            20,
+            None,
+        );
+
+        assert_eq!(
+            super::super::encode(metrics),
+            include_str!("testdata/payments.prom")
+        );
+    }
+
+    fn succeeded(fee_msat: i64) -> lnrpc::Payment {
+        lnrpc::Payment {
+            status: lnrpc::payment::PaymentStatus::Succeeded as i32,
+            fee_msat,
+            ..lnrpc::Payment::default()
+        }
+    }
+
+    #[test]
+    fn fee_statistics_cover_succeeded_payments() {
+        let metrics = PaymentsMetrics::new(&MetricsConfig::new("lnd"));
+        let mut cache = ListPaymentsCache::default();
+        let failed = lnrpc::Payment {
+            status: lnrpc::payment::PaymentStatus::Failed as i32,
+            fee_msat: 1,
+            ..lnrpc::Payment::default()
+        };
+
+        record_listpayments(
+            &metrics,
+            &mut cache,
+            &lnrpc::ListPaymentsResponse {
+                payments: vec![succeeded(3_000), failed, succeeded(1_000)],
+                last_index_offset: 3,
+                ..lnrpc::ListPaymentsResponse::default()
+            },
+            20,
+            None,
+        );
+        assert_eq!(metrics.payment_min_fee_msat.get(), 1_000);
+        assert_eq!(metrics.payment_max_fee_msat.get(), 3_000);
+        assert_eq!(metrics.payment_median_fee_msat.get(), 2_000);
+
+        record_listpayments(
+            &metrics,
+            &mut cache,
+            &lnrpc::ListPaymentsResponse {
+                payments: vec![succeeded(10_000)],
+                last_index_offset: 4,
+                ..lnrpc::ListPaymentsResponse::default()
+            },
+            20,
+            None,
+        );
+        assert_eq!(metrics.payment_min_fee_msat.get(), 1_000);
+        assert_eq!(metrics.payment_max_fee_msat.get(), 10_000);
+        assert_eq!(metrics.payment_median_fee_msat.get(), 3_000);
+    }
+
+    fn payment_to(pub_key: &str) -> lnrpc::Payment {
+        let hop = |pub_key: &str| lnrpc::Hop {
+            pub_key: pub_key.to_string(),
+            ..lnrpc::Hop::default()
+        };
+
+        lnrpc::Payment {
+            status: lnrpc::payment::PaymentStatus::Succeeded as i32,
+            htlcs: vec![lnrpc::HtlcAttempt {
+                route: Some(lnrpc::Route {
+                    hops: vec![hop("02aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"), hop(pub_key)],
+                    ..lnrpc::Route::default()
+                }),
+                ..lnrpc::HtlcAttempt::default()
+            }],
+            ..lnrpc::Payment::default()
+        }
+    }
+
+    #[test]
+    fn only_top_destinations_are_exported() {
+        let metrics = PaymentsMetrics::new(&MetricsConfig::new("lnd"));
+        record_listpayments(
+            &metrics,
+            &mut ListPaymentsCache::default(),
+            &lnrpc::ListPaymentsResponse {
+                payments: vec![
+                    payment_to("03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+                    payment_to("03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+                    payment_to("03cccccccccccccccccccccccccccccccc"),
+                    payment_to("03dddddddddddddddddddddddddddddddd"),
+                    payment_to("03dddddddddddddddddddddddddddddddd"),
+                    payment_to("03dddddddddddddddddddddddddddddddd"),
+                ],
+                last_index_offset: 6,
+                ..lnrpc::ListPaymentsResponse::default()
+            },
+            2,
+            None,
+        );
+
+        let families = metrics.payment_destination_total.collect();
+        let mut destinations = families[0]
+            .get_metric()
+            .iter()
+            .map(|m| (m.get_label()[0].get_value(), m.get_gauge().get_value()))
+            .collect::<Vec<_>>();
+        destinations.sort_by(|a, b| a.0.cmp(b.0));
+
+        assert_eq!(
+            destinations,
+            vec![("03bbbbbbbbbbbbbb", 2.0), ("03dddddddddddddd", 3.0)]
+        );
+    }
+
+    #[test]
+    fn payments_to_self_are_circular_rebalances() {
+        let metrics = PaymentsMetrics::new(&MetricsConfig::new("lnd"));
+        let self_pubkey = "03eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+        let rebalance = |fee_msat: i64, value_msat: i64| lnrpc::Payment {
+            fee_msat,
+            value_msat,
+            ..payment_to(self_pubkey)
+        };
+        let failed = lnrpc::Payment {
+            status: lnrpc::payment::PaymentStatus::Failed as i32,
+            ..rebalance(1, 1)
+        };
+
+        record_listpayments(
+            &metrics,
+            &mut ListPaymentsCache::default(),
+            &lnrpc::ListPaymentsResponse {
+                payments: vec![
+                    rebalance(2_000, 1_000_000),
+                    payment_to("03bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+                    failed,
+                    rebalance(500, 250_000),
+                ],
+                last_index_offset: 4,
+                ..lnrpc::ListPaymentsResponse::default()
+            },
+            20,
+            Some(self_pubkey),
+        );
+
+        assert_eq!(metrics.circular_rebalance_count_total.get(), 2);
+        assert_eq!(metrics.circular_rebalance_fee_msat_total.get(), 2_500);
+        assert_eq!(
+            metrics.circular_rebalance_amount_msat_total.get(),
+            1_250_000
+        );
+    }
+}
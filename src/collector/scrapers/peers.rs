@@ -0,0 +1,257 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use tokio::sync::Mutex;
+
+use super::{supports_route_blinding, ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{MetricsConfig, PeersMetrics},
+    PeerData, ScraperName,
+};
+
+/// Scrapes the connection of each connected peer, complementing the channel
+/// level health with what lnd sees of the underlying TCP connection.
+pub struct PeersScraper {
+    metrics: PeersMetrics,
+    peer_data: Arc<Mutex<PeerData>>,
+}
+
+impl PeersScraper {
+    pub fn new(config: &MetricsConfig, peer_data: Arc<Mutex<PeerData>>) -> Self {
+        Self {
+            metrics: PeersMetrics::new(config),
+            peer_data,
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for PeersScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Peers.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Peers);
+        scrape_peer_tcp_quality(
+            &mut lnd_client,
+            &self.metrics,
+            &mut *self.peer_data.lock().await,
+        )
+        .await?;
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_peer_tcp_quality(
+    lnd_client: &mut lnrpc::LndClient,
+    metrics: &PeersMetrics,
+    peer_data: &mut PeerData,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping listpeers");
+
+    match lnd_client
+        .list_peers(lnrpc::ListPeersRequest { latest_error: true })
+        .await
+    {
+        Ok(res) => {
+            record_peers(metrics, peer_data, res.get_ref());
+            Ok(())
+        }
+
+        Err(e) => {
+            log::error!("Failed to collect peer metrics ERROR={:?}", e);
+            Err(e.into())
+        }
+    }
+}
+
+fn record_peers(metrics: &PeersMetrics, peer_data: &mut PeerData, res: &lnrpc::ListPeersResponse) {
+    // Drop the series of peers disconnected since the last scrape
+    metrics.reset();
+    peer_data.blinded_paths_supported = res
+        .peers
+        .iter()
+        .filter(|peer| supports_route_blinding(&peer.features))
+        .map(|peer| peer.pub_key.clone())
+        .collect();
+
+    let (mut onion, mut ipv4, mut ipv6) = (0, 0, 0);
+    for peer in res.peers.iter() {
+        let pub_key = peer.pub_key.as_str();
+
+        match address_kind(&peer.address) {
+            Some(AddressKind::Onion) => onion += 1,
+            Some(AddressKind::Ipv4) => ipv4 += 1,
+            Some(AddressKind::Ipv6) => ipv6 += 1,
+            None => {}
+        }
+        metrics
+            .peer_address
+            .with_label_values(&[pub_key, &peer.address])
+            .set(1);
+
+        metrics
+            .peer_flap_count_total
+            .with_label_values(&[pub_key])
+            .set(peer.flap_count.into());
+        metrics
+            .peer_bytes_sent_total
+            .with_label_values(&[pub_key])
+            .set(peer.bytes_sent as i64);
+        metrics
+            .peer_bytes_recv_total
+            .with_label_values(&[pub_key])
+            .set(peer.bytes_recv as i64);
+        // The round trip time is in microseconds, the ping payload holds none
+        metrics
+            .peer_ping_time_ms
+            .with_label_values(&[pub_key])
+            .set(peer.ping_time as f64 / 1_000.0);
+        metrics
+            .peer_blinded_paths_supported
+            .with_label_values(&[pub_key])
+            .set(supports_route_blinding(&peer.features).into());
+    }
+    metrics.peers_onion_total.set(onion);
+    metrics.peers_ipv4_total.set(ipv4);
+    metrics.peers_ipv6_total.set(ipv6);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AddressKind {
+    Onion,
+    Ipv4,
+    Ipv6,
+}
+
+/// Classifies a peer address, given as `HOST:PORT` by lnd. Unknown kinds, e.g.
+/// DNS names, are left out.
+fn address_kind(address: &str) -> Option<AddressKind> {
+    let ip = match address.parse::<SocketAddr>() {
+        Ok(addr) => addr.ip(),
+        Err(_) => {
+            let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+            if host.ends_with(".onion") {
+                return Some(AddressKind::Onion);
+            }
+            address.parse::<IpAddr>().ok()?
+        }
+    };
+
+    match ip {
+        IpAddr::V4(_) => Some(AddressKind::Ipv4),
+        IpAddr::V6(_) => Some(AddressKind::Ipv6),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connected_peers_are_exported() {
+        let metrics = PeersMetrics::new(&MetricsConfig::new("lnd"));
+        let mut peer_data = PeerData::default();
+        let peer = |pub_key: &str| lnrpc::Peer {
+            pub_key: pub_key.to_string(),
+            address: "203.0.113.7:9735".to_string(),
+            bytes_sent: 2_048,
+            bytes_recv: 4_096,
+            ping_time: 12_500,
+            flap_count: 3,
+            ..lnrpc::Peer::default()
+        };
+
+        let mut blinding_peer = peer("02aa");
+        blinding_peer.features.insert(25, lnrpc::Feature::default());
+        record_peers(
+            &metrics,
+            &mut peer_data,
+            &lnrpc::ListPeersResponse {
+                peers: vec![blinding_peer, peer("03bb")],
+            },
+        );
+        assert_eq!(
+            metrics
+                .peer_flap_count_total
+                .with_label_values(&["02aa"])
+                .get(),
+            3
+        );
+        assert_eq!(
+            metrics
+                .peer_bytes_recv_total
+                .with_label_values(&["03bb"])
+                .get(),
+            4_096
+        );
+        assert_eq!(
+            metrics.peer_ping_time_ms.with_label_values(&["02aa"]).get(),
+            12.5
+        );
+        assert_eq!(
+            metrics
+                .peer_blinded_paths_supported
+                .with_label_values(&["02aa"])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .peer_blinded_paths_supported
+                .with_label_values(&["03bb"])
+                .get(),
+            0
+        );
+        assert!(peer_data.blinded_paths_supported.contains("02aa"));
+        assert_eq!(
+            metrics
+                .peer_address
+                .with_label_values(&["03bb", "203.0.113.7:9735"])
+                .get(),
+            1
+        );
+        assert_eq!(metrics.peers_ipv4_total.get(), 2);
+
+        record_peers(
+            &metrics,
+            &mut peer_data,
+            &lnrpc::ListPeersResponse {
+                peers: vec![peer("03bb")],
+            },
+        );
+        assert_eq!(
+            metrics.peer_bytes_sent_total.collect()[0]
+                .get_metric()
+                .len(),
+            1
+        );
+        assert!(peer_data.blinded_paths_supported.is_empty());
+        assert_eq!(metrics.peers_ipv4_total.get(), 1);
+    }
+
+    #[test]
+    fn peer_addresses_are_classified() {
+        assert_eq!(address_kind("203.0.113.7:9735"), Some(AddressKind::Ipv4));
+        assert_eq!(address_kind("[2001:db8::1]:9735"), Some(AddressKind::Ipv6));
+        assert_eq!(address_kind("2001:db8::1"), Some(AddressKind::Ipv6));
+        assert_eq!(
+            address_kind("vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd.onion:9735"),
+            Some(AddressKind::Onion)
+        );
+        assert_eq!(address_kind("node.example.com:9735"), None);
+        assert_eq!(address_kind(""), None);
+    }
+}
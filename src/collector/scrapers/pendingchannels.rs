@@ -0,0 +1,373 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use async_trait::async_trait;
+use lnrpc::{
+    channel_close_summary::ClosureType,
+    pending_channels_response::force_closed_channel::AnchorState,
+};
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{MetricsConfig, PendingChannelsMetrics},
+    PendingChannelOpenCache, ScraperName,
+};
+
+/// Value of the anchor output of each party in anchor channels.
+const ANCHOR_OUTPUT_VALUE_SAT: i64 = 330;
+
+/// Scrapes the funds of force closed channels waiting to be swept, and how
+/// long channel opens stay pending.
+pub struct PendingChannelsScraper {
+    metrics: PendingChannelsMetrics,
+    pending_open_cache: Arc<Mutex<PendingChannelOpenCache>>,
+}
+
+impl PendingChannelsScraper {
+    pub fn new(
+        config: &MetricsConfig,
+        pending_open_cache: Arc<Mutex<PendingChannelOpenCache>>,
+    ) -> Self {
+        Self {
+            metrics: PendingChannelsMetrics::new(config),
+            pending_open_cache,
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for PendingChannelsScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::PendingChannels.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::PendingChannels);
+        let mut pending_open_cache = self.pending_open_cache.lock().await;
+        scrape_pendingchannels(&mut lnd_client, &self.metrics, &mut pending_open_cache).await?;
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_pendingchannels(
+    lnd_client: &mut lnrpc::LndClient,
+    metrics: &PendingChannelsMetrics,
+    pending_open_cache: &mut PendingChannelOpenCache,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping pendingchannels");
+
+    // Pending force closes lack who closed them, which the close summaries
+    // written once the closing transaction confirms have. Opens that never
+    // confirm get a summary too, once lnd gives up on them.
+    let res = async {
+        let pending = lnd_client
+            .pending_channels(lnrpc::PendingChannelsRequest {})
+            .await?;
+        let closed = lnd_client
+            .closed_channels(lnrpc::ClosedChannelsRequest {
+                local_force: true,
+                remote_force: true,
+                funding_canceled: true,
+                abandoned: true,
+                ..lnrpc::ClosedChannelsRequest::default()
+            })
+            .await?;
+
+        Ok::<_, lnrpc::Status>((pending, closed))
+    }
+    .await;
+
+    match res {
+        Ok((pending, closed)) => {
+            record_pending_force_closes(metrics, pending.get_ref(), closed.get_ref());
+            record_pending_opens(
+                metrics,
+                pending_open_cache,
+                pending.get_ref(),
+                closed.get_ref(),
+                Instant::now(),
+            );
+            Ok(())
+        }
+
+        Err(e) => {
+            log::error!("Failed to collect pending channel metrics ERROR={:?}", e);
+            Err(e.into())
+        }
+    }
+}
+
+fn record_pending_force_closes(
+    metrics: &PendingChannelsMetrics,
+    pending: &lnrpc::PendingChannelsResponse,
+    closed: &lnrpc::ClosedChannelsResponse,
+) {
+    metrics.reset();
+
+    let close_types = closed
+        .channels
+        .iter()
+        .map(|summary| (summary.channel_point.as_str(), summary.close_type()))
+        .collect::<HashMap<_, _>>();
+
+    let mut sweepable_anchors = 0;
+    let mut recovery_balance_sat = HashMap::new();
+    for force_closed in pending.pending_force_closing_channels.iter() {
+        let channel = match &force_closed.channel {
+            Some(channel) => channel,
+            None => continue,
+        };
+
+        if channel.commitment_type() == lnrpc::CommitmentType::Anchors
+            && force_closed.anchor() == AnchorState::Limbo
+        {
+            sweepable_anchors += 1;
+        }
+
+        let close_type = match close_types.get(channel.channel_point.as_str()) {
+            Some(ClosureType::LocalForceClose) => "local",
+            Some(ClosureType::RemoteForceClose) => "remote",
+            _ => "unknown",
+        };
+        *recovery_balance_sat.entry(close_type).or_default() += force_closed.limbo_balance;
+    }
+
+    metrics
+        .pending_anchor_sweepable_count
+        .set(sweepable_anchors);
+    metrics
+        .pending_anchor_sweepable_value_sat
+        .set(sweepable_anchors * ANCHOR_OUTPUT_VALUE_SAT);
+    for (close_type, balance_sat) in recovery_balance_sat {
+        metrics
+            .pending_force_close_recovery_balance_sat
+            .with_label_values(&[close_type])
+            .set(balance_sat);
+    }
+}
+
+/// Tracks the pending opens, observing how long the ones no longer pending
+/// took. Opens lnd gave up on failed, every other one confirmed.
+fn record_pending_opens(
+    metrics: &PendingChannelsMetrics,
+    cache: &mut PendingChannelOpenCache,
+    pending: &lnrpc::PendingChannelsResponse,
+    closed: &lnrpc::ClosedChannelsResponse,
+    now: Instant,
+) {
+    let pending_opens = pending
+        .pending_open_channels
+        .iter()
+        .filter_map(|pending_open| pending_open.channel.as_ref())
+        .map(|channel| channel.channel_point.as_str())
+        .collect::<Vec<_>>();
+
+    let first_seen = if cache.scraped { Some(now) } else { None };
+    for channel_point in pending_opens.iter() {
+        cache
+            .first_seen
+            .entry(channel_point.to_string())
+            .or_insert(first_seen);
+    }
+    cache.scraped = true;
+
+    let failed = closed
+        .channels
+        .iter()
+        .filter(|summary| {
+            matches!(
+                summary.close_type(),
+                ClosureType::FundingCanceled | ClosureType::Abandoned
+            )
+        })
+        .map(|summary| summary.channel_point.as_str())
+        .collect::<Vec<_>>();
+
+    let mut ended = vec![];
+    cache.first_seen.retain(|channel_point, first_seen| {
+        if pending_opens.contains(&channel_point.as_str()) {
+            return true;
+        }
+
+        ended.push((failed.contains(&channel_point.as_str()), *first_seen));
+        false
+    });
+    for (failed, first_seen) in ended {
+        if failed {
+            cache.failed += 1;
+        } else {
+            cache.succeeded += 1;
+        }
+        if let Some(first_seen) = first_seen {
+            metrics
+                .channel_open_duration_seconds
+                .observe(now.duration_since(first_seen).as_secs_f64());
+        }
+    }
+
+    metrics
+        .channel_open_success_total
+        .with_label_values(&["succeeded"])
+        .set(cache.succeeded as i64);
+    metrics
+        .channel_open_success_total
+        .with_label_values(&["failed"])
+        .set(cache.failed as i64);
+}
+
+#[cfg(test)]
+mod tests {
+    use lnrpc::pending_channels_response::{ForceClosedChannel, PendingChannel};
+
+    use super::*;
+
+    fn force_closed(
+        channel_point: &str,
+        commitment_type: lnrpc::CommitmentType,
+        anchor: AnchorState,
+        limbo_balance: i64,
+    ) -> ForceClosedChannel {
+        ForceClosedChannel {
+            channel: Some(PendingChannel {
+                channel_point: channel_point.to_string(),
+                commitment_type: commitment_type as i32,
+                ..PendingChannel::default()
+            }),
+            anchor: anchor as i32,
+            limbo_balance,
+            ..ForceClosedChannel::default()
+        }
+    }
+
+    fn close_summary(channel_point: &str, close_type: ClosureType) -> lnrpc::ChannelCloseSummary {
+        lnrpc::ChannelCloseSummary {
+            channel_point: channel_point.to_string(),
+            close_type: close_type as i32,
+            ..lnrpc::ChannelCloseSummary::default()
+        }
+    }
+
+    #[test]
+    fn pending_force_closes_are_broken_down() {
+        let metrics = PendingChannelsMetrics::new(&MetricsConfig::new("lnd"));
+
+        record_pending_force_closes(
+            &metrics,
+            &lnrpc::PendingChannelsResponse {
+                pending_force_closing_channels: vec![
+                    force_closed(
+                        "a:0",
+                        lnrpc::CommitmentType::Anchors,
+                        AnchorState::Limbo,
+                        1_000,
+                    ),
+                    force_closed(
+                        "b:0",
+                        lnrpc::CommitmentType::Anchors,
+                        AnchorState::Recovered,
+                        2_000,
+                    ),
+                    force_closed(
+                        "c:0",
+                        lnrpc::CommitmentType::StaticRemoteKey,
+                        AnchorState::Limbo,
+                        4_000,
+                    ),
+                    force_closed(
+                        "d:0",
+                        lnrpc::CommitmentType::Anchors,
+                        AnchorState::Limbo,
+                        8_000,
+                    ),
+                ],
+                ..lnrpc::PendingChannelsResponse::default()
+            },
+            &lnrpc::ClosedChannelsResponse {
+                channels: vec![
+                    close_summary("a:0", ClosureType::LocalForceClose),
+                    close_summary("b:0", ClosureType::RemoteForceClose),
+                    close_summary("c:0", ClosureType::LocalForceClose),
+                ],
+            },
+        );
+
+        assert_eq!(metrics.pending_anchor_sweepable_count.get(), 2);
+        assert_eq!(metrics.pending_anchor_sweepable_value_sat.get(), 660);
+
+        let recovery_balance = |close_type| {
+            metrics
+                .pending_force_close_recovery_balance_sat
+                .with_label_values(&[close_type])
+                .get()
+        };
+        assert_eq!(recovery_balance("local"), 5_000);
+        assert_eq!(recovery_balance("remote"), 2_000);
+        assert_eq!(recovery_balance("unknown"), 8_000);
+    }
+
+    #[test]
+    fn pending_opens_are_timed_until_they_end() {
+        use lnrpc::pending_channels_response::PendingOpenChannel;
+        use std::time::Duration;
+
+        let metrics = PendingChannelsMetrics::new(&MetricsConfig::new("lnd"));
+        let mut cache = PendingChannelOpenCache::default();
+        let pending = |channel_points: &[&str]| lnrpc::PendingChannelsResponse {
+            pending_open_channels: channel_points
+                .iter()
+                .map(|channel_point| PendingOpenChannel {
+                    channel: Some(PendingChannel {
+                        channel_point: channel_point.to_string(),
+                        ..PendingChannel::default()
+                    }),
+                    ..PendingOpenChannel::default()
+                })
+                .collect(),
+            ..lnrpc::PendingChannelsResponse::default()
+        };
+        let no_closes = lnrpc::ClosedChannelsResponse::default();
+        let start = Instant::now();
+
+        // Already pending when the exporter started
+        record_pending_opens(&metrics, &mut cache, &pending(&["a:0"]), &no_closes, start);
+        record_pending_opens(
+            &metrics,
+            &mut cache,
+            &pending(&["a:0", "b:0", "c:0"]),
+            &no_closes,
+            start + Duration::from_secs(30),
+        );
+        record_pending_opens(
+            &metrics,
+            &mut cache,
+            &pending(&[]),
+            &lnrpc::ClosedChannelsResponse {
+                channels: vec![close_summary("c:0", ClosureType::FundingCanceled)],
+            },
+            start + Duration::from_secs(630),
+        );
+
+        let result = |result| {
+            metrics
+                .channel_open_success_total
+                .with_label_values(&[result])
+                .get()
+        };
+        assert_eq!(result("succeeded"), 2);
+        assert_eq!(result("failed"), 1);
+        assert_eq!(metrics.channel_open_duration_seconds.get_sample_count(), 2);
+        assert_eq!(
+            metrics.channel_open_duration_seconds.get_sample_sum(),
+            1_200.0
+        );
+        assert!(cache.first_seen.is_empty());
+    }
+}
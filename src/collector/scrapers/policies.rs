@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{MetricsConfig, PoliciesMetrics},
+    subscriptions::unix_now,
+    ScraperName,
+};
+
+/// Expected time between blocks, used to turn CLTV deltas into durations.
+const BLOCK_INTERVAL_SECS: i64 = 10 * 60;
+
+/// Scrapes the routing policies of the local channels. lnd's fee report lacks
+/// the CLTV delta and update time, so they are read from the graph,
+/// unannounced channels included.
+pub struct PoliciesScraper {
+    metrics: PoliciesMetrics,
+}
+
+impl PoliciesScraper {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            metrics: PoliciesMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for PoliciesScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Policies.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Policies);
+
+        log::debug!("Scraping channel policies");
+
+        let res = async {
+            let info = lnd_client.get_info(lnrpc::GetInfoRequest {}).await?;
+            let graph = lnd_client
+                .describe_graph(lnrpc::ChannelGraphRequest {
+                    include_unannounced: true,
+                })
+                .await?;
+
+            Ok::<_, lnrpc::Status>((info, graph))
+        }
+        .await;
+
+        match res {
+            Ok((info, graph)) => {
+                record_channel_policies(&self.metrics, info.get_ref(), graph.get_ref(), unix_now());
+                Ok(self.metrics.collect())
+            }
+
+            Err(e) => {
+                log::error!("Failed to collect channel policy metrics ERROR={:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
+
+fn record_channel_policies(
+    metrics: &PoliciesMetrics,
+    info: &lnrpc::GetInfoResponse,
+    graph: &lnrpc::ChannelGraph,
+    now: u64,
+) {
+    // Drop the series of channels closed since the last scrape
+    metrics.reset();
+    let self_pubkey = &info.identity_pubkey;
+
+    for edge in graph.edges.iter() {
+        let local_policy = if &edge.node1_pub == self_pubkey {
+            &edge.node1_policy
+        } else if &edge.node2_pub == self_pubkey {
+            &edge.node2_policy
+        } else {
+            continue;
+        };
+        let policy = match local_policy {
+            Some(policy) => policy,
+            None => continue,
+        };
+        let cltv_expiry_delta = policy.time_lock_delta as i64;
+
+        let chan_id = edge.channel_id.to_string();
+        metrics
+            .channel_cltv_expiry_delta
+            .with_label_values(&[&chan_id, &edge.chan_point])
+            .set(cltv_expiry_delta);
+        metrics
+            .channel_effective_htlc_timeout_seconds
+            .with_label_values(&[&chan_id, &edge.chan_point])
+            .set(cltv_expiry_delta * BLOCK_INTERVAL_SECS);
+
+        // Zero until the policy is announced
+        if policy.last_update > 0 {
+            let last_update = u64::from(policy.last_update);
+            metrics
+                .channel_policy_last_update_timestamp_seconds
+                .with_label_values(&[&chan_id, &edge.chan_point])
+                .set(last_update as i64);
+            metrics
+                .channel_policy_age_seconds
+                .with_label_values(&[&chan_id, &edge.chan_point])
+                .set(now.saturating_sub(last_update) as i64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_policies_are_exported() {
+        let metrics = PoliciesMetrics::new(&MetricsConfig::new("lnd"));
+        let policy = |time_lock_delta| {
+            Some(lnrpc::RoutingPolicy {
+                time_lock_delta,
+                last_update: 1_700_000_000 + time_lock_delta,
+                ..lnrpc::RoutingPolicy::default()
+            })
+        };
+        let edge = |channel_id, node1_pub: &str, node2_pub: &str| lnrpc::ChannelEdge {
+            channel_id,
+            chan_point: format!("txid:{}", channel_id),
+            node1_pub: node1_pub.to_string(),
+            node2_pub: node2_pub.to_string(),
+            node1_policy: policy(40),
+            node2_policy: policy(144),
+            ..lnrpc::ChannelEdge::default()
+        };
+
+        record_channel_policies(
+            &metrics,
+            &lnrpc::GetInfoResponse {
+                identity_pubkey: "self".to_string(),
+                ..lnrpc::GetInfoResponse::default()
+            },
+            &lnrpc::ChannelGraph {
+                edges: vec![
+                    edge(1, "self", "peer"),
+                    edge(2, "peer", "self"),
+                    edge(3, "peer", "other"),
+                ],
+                ..lnrpc::ChannelGraph::default()
+            },
+            1_700_001_000,
+        );
+
+        let delta = |chan_id| {
+            metrics
+                .channel_cltv_expiry_delta
+                .with_label_values(&[chan_id, &format!("txid:{}", chan_id)])
+                .get()
+        };
+        assert_eq!(delta("1"), 40);
+        assert_eq!(delta("2"), 144);
+        assert_eq!(
+            metrics
+                .channel_effective_htlc_timeout_seconds
+                .with_label_values(&["2", "txid:2"])
+                .get(),
+            144 * 600
+        );
+        assert_eq!(
+            metrics
+                .channel_policy_last_update_timestamp_seconds
+                .with_label_values(&["1", "txid:1"])
+                .get(),
+            1_700_000_040
+        );
+        assert_eq!(
+            metrics
+                .channel_policy_age_seconds
+                .with_label_values(&["2", "txid:2"])
+                .get(),
+            1_000 - 144
+        );
+        assert_eq!(
+            metrics.channel_cltv_expiry_delta.collect()[0]
+                .get_metric()
+                .len(),
+            2
+        );
+    }
+}
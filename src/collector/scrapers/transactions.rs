@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use tokio::sync::Mutex;
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{MetricsConfig, TransactionsMetrics},
+    ScraperName, TransactionCache,
+};
+
+/// Scrapes the on-chain transactions of the wallet by label, for bookkeeping.
+pub struct TransactionsScraper {
+    cache: Mutex<TransactionCache>,
+    metrics: TransactionsMetrics,
+}
+
+impl TransactionsScraper {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            cache: Mutex::new(TransactionCache::default()),
+            metrics: TransactionsMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for TransactionsScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Transactions.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut cache = self.cache.lock().await;
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::Transactions);
+        scrape_transactions(&mut lnd_client, &self.metrics, &mut cache).await?;
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_transactions(
+    lnd_client: &mut lnrpc::LndClient,
+    metrics: &TransactionsMetrics,
+    cache: &mut TransactionCache,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping gettransactions");
+
+    let res = lnd_client
+        .get_transactions(lnrpc::GetTransactionsRequest {
+            start_height: cache.start_height,
+            // Up to the chain tip, unconfirmed transactions included
+            end_height: -1,
+            ..lnrpc::GetTransactionsRequest::default()
+        })
+        .await;
+
+    match res {
+        Ok(res) => {
+            record_transactions(metrics, cache, res.get_ref());
+            Ok(())
+        }
+
+        Err(e) => {
+            log::error!("Failed to collect transaction metrics ERROR={:?}", e);
+            Err(e.into())
+        }
+    }
+}
+
+fn record_transactions(
+    metrics: &TransactionsMetrics,
+    cache: &mut TransactionCache,
+    res: &lnrpc::TransactionDetails,
+) {
+    let mut start_height = cache.start_height;
+
+    for tx in res
+        .transactions
+        .iter()
+        .filter(|tx| tx.num_confirmations > 0)
+    {
+        start_height = start_height.max(tx.block_height + 1);
+
+        let tx_type = match tx.amount {
+            amount if amount > 0 => "incoming",
+            amount if amount < 0 => "outgoing",
+            _ => continue,
+        };
+        *cache
+            .amount_sat
+            .entry((tx.label.clone(), tx_type))
+            .or_default() += tx.amount.abs();
+        if tx_type == "outgoing" {
+            *cache.fees_sat.entry(tx.label.clone()).or_default() += tx.total_fees;
+        }
+    }
+    cache.start_height = start_height;
+
+    for ((label, tx_type), amount_sat) in cache.amount_sat.iter() {
+        metrics
+            .transaction_amount_sat_total
+            .with_label_values(&[label, tx_type])
+            .set(*amount_sat);
+    }
+    for (label, fees_sat) in cache.fees_sat.iter() {
+        metrics
+            .transaction_fees_sat_total
+            .with_label_values(&[label])
+            .set(*fees_sat);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(
+        amount: i64,
+        total_fees: i64,
+        block_height: i32,
+        label: &str,
+    ) -> lnrpc::Transaction {
+        lnrpc::Transaction {
+            amount,
+            total_fees,
+            block_height,
+            num_confirmations: if block_height > 0 { 1 } else { 0 },
+            label: label.to_string(),
+            ..lnrpc::Transaction::default()
+        }
+    }
+
+    #[test]
+    fn confirmed_transactions_are_counted_once() {
+        let metrics = TransactionsMetrics::new(&MetricsConfig::new("lnd"));
+        let mut cache = TransactionCache::default();
+
+        record_transactions(
+            &metrics,
+            &mut cache,
+            &lnrpc::TransactionDetails {
+                transactions: vec![
+                    transaction(50_000, 0, 100, "deposit"),
+                    transaction(-20_000, 300, 101, "sweep"),
+                    // Unconfirmed yet
+                    transaction(-5_000, 200, 0, "sweep"),
+                ],
+            },
+        );
+        assert_eq!(cache.start_height, 102);
+
+        // Listing resumes past the counted blocks, where the pending sweep confirmed
+        record_transactions(
+            &metrics,
+            &mut cache,
+            &lnrpc::TransactionDetails {
+                transactions: vec![transaction(-5_000, 200, 103, "sweep")],
+            },
+        );
+
+        let amount = |label, tx_type| {
+            metrics
+                .transaction_amount_sat_total
+                .with_label_values(&[label, tx_type])
+                .get()
+        };
+        assert_eq!(amount("deposit", "incoming"), 50_000);
+        assert_eq!(amount("sweep", "outgoing"), 25_000);
+        assert_eq!(
+            metrics
+                .transaction_fees_sat_total
+                .with_label_values(&["sweep"])
+                .get(),
+            500
+        );
+        assert_eq!(cache.start_height, 104);
+    }
+}
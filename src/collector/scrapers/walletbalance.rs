@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{MetricsConfig, WalletBalanceMetrics},
+    ScraperName,
+};
+
+/// Scrapes the on-chain balance of each wallet account, for nodes isolating
+/// funds in accounts (lnd 0.15+).
+pub struct WalletBalanceScraper {
+    metrics: WalletBalanceMetrics,
+}
+
+impl WalletBalanceScraper {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            metrics: WalletBalanceMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for WalletBalanceScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::WalletBalance.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut lnd_client = ctx.lnd_clients.get(ScraperName::WalletBalance);
+        scrape_walletbalance_accounts(&mut lnd_client, &self.metrics).await?;
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_walletbalance_accounts(
+    lnd_client: &mut lnrpc::LndClient,
+    metrics: &WalletBalanceMetrics,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping walletbalance");
+
+    match lnd_client
+        .wallet_balance(lnrpc::WalletBalanceRequest {})
+        .await
+    {
+        Ok(res) => {
+            record_account_balances(metrics, res.get_ref());
+            Ok(())
+        }
+
+        Err(e) => {
+            log::error!("Failed to collect wallet balance metrics ERROR={:?}", e);
+            Err(e.into())
+        }
+    }
+}
+
+fn record_account_balances(metrics: &WalletBalanceMetrics, res: &lnrpc::WalletBalanceResponse) {
+    // Drop the series of accounts gone since the last scrape. Nodes predating
+    // accounts report none and export nothing.
+    metrics.reset();
+
+    for (account, balance) in res.account_balance.iter() {
+        let account = if account.is_empty() {
+            "default"
+        } else {
+            account.as_str()
+        };
+
+        metrics
+            .wallet_account_balance_sat
+            .with_label_values(&[account, "true"])
+            .set(balance.confirmed_balance);
+        metrics
+            .wallet_account_balance_sat
+            .with_label_values(&[account, "false"])
+            .set(balance.unconfirmed_balance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_balances_are_exported() {
+        let metrics = WalletBalanceMetrics::new(&MetricsConfig::new("lnd"));
+        let account_balance =
+            |confirmed_balance, unconfirmed_balance| lnrpc::WalletAccountBalance {
+                confirmed_balance,
+                unconfirmed_balance,
+            };
+
+        record_account_balances(
+            &metrics,
+            &lnrpc::WalletBalanceResponse {
+                account_balance: vec![
+                    ("".to_string(), account_balance(1_000, 10)),
+                    ("cold".to_string(), account_balance(50_000, 0)),
+                ]
+                .into_iter()
+                .collect(),
+                ..lnrpc::WalletBalanceResponse::default()
+            },
+        );
+
+        let balance = |account, confirmed| {
+            metrics
+                .wallet_account_balance_sat
+                .with_label_values(&[account, confirmed])
+                .get()
+        };
+        assert_eq!(balance("default", "true"), 1_000);
+        assert_eq!(balance("default", "false"), 10);
+        assert_eq!(balance("cold", "true"), 50_000);
+
+        // Single-account nodes of older lnd versions report no accounts
+        record_account_balances(&metrics, &lnrpc::WalletBalanceResponse::default());
+        assert!(metrics.wallet_account_balance_sat.collect()[0]
+            .get_metric()
+            .is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use super::{ScrapeContext, ScrapeError, Scraper};
+use crate::collector::{
+    metrics::{MetricsConfig, WatchtowerMetrics},
+    ScraperName,
+};
+
+/// Scrapes the sessions of the watchtowers the node backs its channels up to,
+/// which keep it safe from breaches while offline.
+pub struct WatchtowerScraper {
+    metrics: WatchtowerMetrics,
+}
+
+impl WatchtowerScraper {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            metrics: WatchtowerMetrics::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl Scraper for WatchtowerScraper {
+    fn name(&self) -> &'static str {
+        ScraperName::Watchtower.as_str()
+    }
+
+    fn desc(&self) -> Vec<&Desc> {
+        self.metrics.desc()
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext<'_>) -> Result<Vec<MetricFamily>, ScrapeError> {
+        let mut watchtower_client = ctx.lnd_clients.watchtower(ScraperName::Watchtower);
+        scrape_watchtower_client(&mut watchtower_client, &self.metrics).await?;
+        Ok(self.metrics.collect())
+    }
+}
+
+async fn scrape_watchtower_client(
+    watchtower_client: &mut lnrpc::WatchtowerClient,
+    metrics: &WatchtowerMetrics,
+) -> Result<(), ScrapeError> {
+    log::debug!("Scraping listtowers");
+
+    match watchtower_client
+        .list_towers(lnrpc::wtclientrpc::ListTowersRequest {
+            include_sessions: true,
+        })
+        .await
+    {
+        Ok(res) => {
+            record_towers(metrics, res.get_ref());
+            Ok(())
+        }
+
+        Err(e) => {
+            log::error!("Failed to collect watchtower metrics ERROR={:?}", e);
+            Err(e.into())
+        }
+    }
+}
+
+fn record_towers(metrics: &WatchtowerMetrics, res: &lnrpc::wtclientrpc::ListTowersResponse) {
+    // Drop the series of towers removed since the last scrape
+    metrics.reset();
+
+    let mut total_backups = 0;
+    for tower in res.towers.iter() {
+        let tower_pubkey = hex::encode(&tower.pubkey);
+        let backups = tower
+            .sessions
+            .iter()
+            .map(|session| i64::from(session.num_backups))
+            .sum::<i64>();
+        total_backups += backups;
+
+        metrics
+            .watchtower_session_count
+            .with_label_values(&[&tower_pubkey])
+            .set(tower.num_sessions.into());
+        metrics
+            .watchtower_backup_count
+            .with_label_values(&[&tower_pubkey])
+            .set(backups);
+        metrics
+            .watchtower_tower_active
+            .with_label_values(&[&tower_pubkey])
+            .set((tower.active_session_candidate && tower.num_sessions > 0).into());
+    }
+
+    metrics.watchtower_total_backups.set(total_backups);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lnrpc::wtclientrpc::{ListTowersResponse, Tower, TowerSession};
+
+    #[test]
+    fn tower_sessions_are_summed() {
+        let metrics = WatchtowerMetrics::new(&MetricsConfig::new("lnd"));
+        let session = |num_backups: u32| TowerSession {
+            num_backups,
+            max_backups: 1_024,
+            ..TowerSession::default()
+        };
+
+        record_towers(
+            &metrics,
+            &ListTowersResponse {
+                towers: vec![
+                    Tower {
+                        pubkey: vec![0x02, 0xaa],
+                        active_session_candidate: true,
+                        num_sessions: 2,
+                        sessions: vec![session(1_024), session(100)],
+                        ..Tower::default()
+                    },
+                    Tower {
+                        pubkey: vec![0x03, 0xbb],
+                        active_session_candidate: false,
+                        num_sessions: 1,
+                        sessions: vec![session(7)],
+                        ..Tower::default()
+                    },
+                ],
+            },
+        );
+
+        assert_eq!(
+            metrics
+                .watchtower_backup_count
+                .with_label_values(&["02aa"])
+                .get(),
+            1_124
+        );
+        assert_eq!(
+            metrics
+                .watchtower_session_count
+                .with_label_values(&["02aa"])
+                .get(),
+            2
+        );
+        assert_eq!(
+            metrics
+                .watchtower_tower_active
+                .with_label_values(&["02aa"])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .watchtower_tower_active
+                .with_label_values(&["03bb"])
+                .get(),
+            0
+        );
+        assert_eq!(metrics.watchtower_total_backups.get(), 1_131);
+    }
+}
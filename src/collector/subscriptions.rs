@@ -0,0 +1,229 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use lnrpc::routerrpc::{htlc_event, HtlcEvent};
+use tokio::sync::Mutex;
+
+use super::{CustomMessageCache, ForwardingCache};
+
+/// Delay before resubscribing after a stream ends or fails to start.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(10);
+
+/// Forwards tracked until they resolve, beyond which new ones go untimed.
+const MAX_IN_FLIGHT_HTLCS: usize = 10_000;
+
+/// Forwards in flight by incoming chan_id and htlc_id, with the timestamp of
+/// their forward event in nanoseconds.
+type InFlightHtlcs = HashMap<(u64, u64), u64>;
+
+/// Counts forwards failed by the outgoing link and times settled forwards into
+/// the forwarding cache, resubscribing for as long as the exporter runs.
+pub async fn subscribe_htlc_events(
+    mut router_client: lnrpc::RouterClient,
+    cache: Arc<Mutex<ForwardingCache>>,
+) {
+    loop {
+        log::debug!("Subscribing to htlc events");
+
+        match router_client
+            .subscribe_htlc_events(lnrpc::routerrpc::SubscribeHtlcEventsRequest {})
+            .await
+        {
+            Ok(res) => {
+                let mut stream = res.into_inner();
+                // Forwards resolved while resubscribing would never be seen
+                let mut in_flight = InFlightHtlcs::new();
+
+                loop {
+                    match stream.message().await {
+                        Ok(Some(event)) => {
+                            record_htlc_event(&mut *cache.lock().await, &mut in_flight, &event)
+                        }
+                        Ok(None) => {
+                            log::warn!("Htlc events stream closed");
+                            break;
+                        }
+                        Err(e) => {
+                            log::error!("Htlc events stream failed ERROR={:?}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            Err(e) if super::is_denied_status(&e) => {
+                log::warn!("lnd refused htlc events subscription: {}", e.message());
+                return;
+            }
+
+            Err(e) => log::error!("Failed to subscribe to htlc events ERROR={:?}", e),
+        }
+
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
+
+/// Counts custom peer messages, e.g. LSPS requests, by type into the cache,
+/// resubscribing for as long as the exporter runs.
+pub async fn subscribe_custom_messages(
+    mut lnd_client: lnrpc::LndClient,
+    cache: Arc<Mutex<CustomMessageCache>>,
+) {
+    loop {
+        log::debug!("Subscribing to custom messages");
+
+        match lnd_client
+            .subscribe_custom_messages(lnrpc::SubscribeCustomMessagesRequest {})
+            .await
+        {
+            Ok(res) => {
+                let mut stream = res.into_inner();
+
+                loop {
+                    match stream.message().await {
+                        Ok(Some(message)) => {
+                            record_custom_message(&mut *cache.lock().await, &message, unix_now())
+                        }
+                        Ok(None) => {
+                            log::warn!("Custom messages stream closed");
+                            break;
+                        }
+                        Err(e) => {
+                            log::error!("Custom messages stream failed ERROR={:?}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            Err(e) if super::is_denied_status(&e) => {
+                log::warn!("lnd refused custom messages subscription: {}", e.message());
+                return;
+            }
+
+            Err(e) => log::error!("Failed to subscribe to custom messages ERROR={:?}", e),
+        }
+
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
+
+pub(super) fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system time after unix epoch")
+        .as_secs()
+}
+
+fn record_htlc_event(
+    cache: &mut ForwardingCache,
+    in_flight: &mut InFlightHtlcs,
+    event: &HtlcEvent,
+) {
+    if event.event_type() != htlc_event::EventType::Forward {
+        return;
+    }
+
+    let htlc = (event.incoming_channel_id, event.incoming_htlc_id);
+    match event.event {
+        Some(htlc_event::Event::ForwardEvent(_)) if in_flight.len() < MAX_IN_FLIGHT_HTLCS => {
+            in_flight.insert(htlc, event.timestamp_ns);
+        }
+        Some(htlc_event::Event::SettleEvent(_)) => {
+            if let Some(forwarded_at) = in_flight.remove(&htlc) {
+                if cache.resolution_times.len() < ForwardingCache::MAX_RESOLUTION_TIMES {
+                    let nanos = event.timestamp_ns.saturating_sub(forwarded_at);
+                    cache.resolution_times.push(nanos as f64 / 1e9);
+                }
+            }
+        }
+        Some(htlc_event::Event::ForwardFailEvent(_)) => {
+            in_flight.remove(&htlc);
+        }
+        Some(htlc_event::Event::LinkFailEvent(_)) => {
+            *cache
+                .link_failed
+                .entry(event.outgoing_channel_id)
+                .or_default() += 1;
+        }
+        _ => {}
+    }
+}
+
+fn record_custom_message(
+    cache: &mut CustomMessageCache,
+    message: &lnrpc::CustomMessage,
+    received_at: u64,
+) {
+    let mut message_type = message.r#type.to_string();
+    if !cache.types.contains_key(&message_type)
+        && cache.types.len() >= CustomMessageCache::MAX_TYPES
+    {
+        message_type = "other".to_string();
+    }
+
+    let (count, last_received) = cache.types.entry(message_type).or_default();
+    *count += 1;
+    *last_received = received_at;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settled_forwards_are_timed() {
+        let mut cache = ForwardingCache::new();
+        let mut in_flight = InFlightHtlcs::new();
+        let event = |htlc_id: u64, timestamp_ns: u64, event: htlc_event::Event| HtlcEvent {
+            incoming_channel_id: 7,
+            incoming_htlc_id: htlc_id,
+            outgoing_channel_id: 9,
+            timestamp_ns,
+            event_type: htlc_event::EventType::Forward.into(),
+            event: Some(event),
+            ..HtlcEvent::default()
+        };
+        let forward = || htlc_event::Event::ForwardEvent(Default::default());
+        let settle = || htlc_event::Event::SettleEvent(Default::default());
+
+        for e in [
+            event(1, 1_000_000_000, forward()),
+            event(2, 1_000_000_000, forward()),
+            event(
+                2,
+                1_200_000_000,
+                htlc_event::Event::ForwardFailEvent(Default::default()),
+            ),
+            event(1, 3_500_000_000, settle()),
+            // Forwarded before subscribing
+            event(3, 3_500_000_000, settle()),
+        ] {
+            record_htlc_event(&mut cache, &mut in_flight, &e);
+        }
+
+        assert_eq!(cache.resolution_times, vec![2.5]);
+        assert!(in_flight.is_empty());
+    }
+
+    #[test]
+    fn custom_messages_are_counted_by_type() {
+        let mut cache = CustomMessageCache::default();
+        let message = |r#type: u32| lnrpc::CustomMessage {
+            r#type,
+            ..lnrpc::CustomMessage::default()
+        };
+
+        record_custom_message(&mut cache, &message(37913), 100);
+        record_custom_message(&mut cache, &message(37913), 160);
+        record_custom_message(&mut cache, &message(32768), 120);
+        assert_eq!(cache.types["37913"], (2, 160));
+        assert_eq!(cache.types["32768"], (1, 120));
+
+        for r#type in 40000..40000 + CustomMessageCache::MAX_TYPES as u32 {
+            record_custom_message(&mut cache, &message(r#type), 200);
+        }
+        assert_eq!(cache.types.len(), CustomMessageCache::MAX_TYPES + 1);
+        assert_eq!(cache.types["other"], (2, 200));
+        assert_eq!(cache.types["37913"], (2, 160));
+    }
+}
@@ -0,0 +1,71 @@
+use std::{sync::Arc, time::Duration};
+
+use lnrpc::WalletState;
+
+/// Interval between wallet unlock progress logs.
+const PROGRESS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Polls the wallet state every `poll_interval` until lnd is ready to serve
+/// macaroon authenticated requests, for however long it takes someone to unlock
+/// the wallet.
+pub async fn wait_for_unlock(
+    mut state_client: lnrpc::StateClient,
+    poll_interval: Duration,
+    wallet_state: Arc<std::sync::Mutex<Option<WalletState>>>,
+) {
+    let start_time = std::time::Instant::now();
+    let mut last_log = start_time;
+
+    loop {
+        match state_client.get_state(lnrpc::GetStateRequest {}).await {
+            Ok(res) => {
+                let state = res.get_ref().state();
+                *wallet_state.lock().expect("wallet state lock") = Some(state);
+
+                if is_active(state) {
+                    log::info!(
+                        "lnd wallet ready after {}s, starting scrapers",
+                        start_time.elapsed().as_secs()
+                    );
+                    return;
+                }
+            }
+
+            // Nodes predating the State service cannot be waited for
+            Err(e) if e.code() == lnrpc::Code::Unimplemented => {
+                log::warn!("lnd has no State service, not waiting for wallet unlock");
+                *wallet_state.lock().expect("wallet state lock") = Some(WalletState::ServerActive);
+                return;
+            }
+
+            Err(e) => log::debug!("Failed to get wallet state ERROR={:?}", e),
+        }
+
+        if last_log.elapsed() >= PROGRESS_LOG_INTERVAL {
+            log::info!(
+                "Waiting for lnd wallet unlock for {}s, wallet state: {:?}",
+                start_time.elapsed().as_secs(),
+                *wallet_state.lock().expect("wallet state lock")
+            );
+            last_log = std::time::Instant::now();
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Whether lnd accepts macaroon authenticated requests in the state.
+pub fn is_active(state: WalletState) -> bool {
+    matches!(state, WalletState::RpcActive | WalletState::ServerActive)
+}
+
+pub fn state_label(state: WalletState) -> &'static str {
+    match state {
+        WalletState::NonExisting => "non_existing",
+        WalletState::Locked => "locked",
+        WalletState::Unlocked => "unlocked",
+        WalletState::RpcActive => "rpc_active",
+        WalletState::ServerActive => "server_active",
+        WalletState::WaitingToStart => "waiting_to_start",
+    }
+}
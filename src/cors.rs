@@ -0,0 +1,144 @@
+use hyper::header::{self, HeaderMap, HeaderValue};
+
+/// Origin allowed to read the endpoints from a browser, like
+/// `https://status.example.com`, or `*` for any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorsOrigin {
+    Any,
+    Origin(String),
+}
+
+impl std::str::FromStr for CorsOrigin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            return Ok(CorsOrigin::Any);
+        }
+
+        // Browsers send the serialized origin, so anything else never matches
+        match reqwest::Url::parse(s) {
+            Ok(url) if url.origin().ascii_serialization() == s => {
+                Ok(CorsOrigin::Origin(s.to_string()))
+            }
+            _ => Err(format!("invalid origin {:?}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for CorsOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorsOrigin::Any => f.write_str("*"),
+            CorsOrigin::Origin(origin) => f.write_str(origin),
+        }
+    }
+}
+
+/// Cross-origin access to the metrics and health endpoints.
+pub struct Cors {
+    origins: Vec<CorsOrigin>,
+}
+
+impl Cors {
+    pub fn new(origins: Vec<CorsOrigin>) -> Self {
+        Self { origins }
+    }
+
+    /// Answer to a preflight request, which carries no credentials.
+    pub fn preflight(&self, req_headers: &HeaderMap) -> hyper::Response<hyper::Body> {
+        let mut res = hyper::Response::new(hyper::Body::empty());
+        *res.status_mut() = hyper::StatusCode::NO_CONTENT;
+        self.apply(req_headers, res.headers_mut());
+
+        res
+    }
+
+    /// Adds the CORS headers to a response, if the request comes from an
+    /// allowed origin.
+    pub fn apply(&self, req_headers: &HeaderMap, res_headers: &mut HeaderMap) {
+        let origin = match req_headers.get(header::ORIGIN) {
+            Some(origin) => origin,
+            None => return,
+        };
+
+        let allow_origin = if self.origins.contains(&CorsOrigin::Any) {
+            HeaderValue::from_static("*")
+        } else {
+            // The response depends on the origin when it's echoed back
+            res_headers.append(header::VARY, HeaderValue::from_static("Origin"));
+
+            let allowed = self
+                .origins
+                .iter()
+                .any(|allowed| matches!(allowed, CorsOrigin::Origin(allowed) if origin == allowed));
+            if !allowed {
+                return;
+            }
+            origin.clone()
+        };
+
+        res_headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        res_headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static("GET"),
+        );
+        res_headers.insert(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static("Accept, Authorization"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origins_are_parsed() {
+        assert_eq!("*".parse(), Ok(CorsOrigin::Any));
+        assert_eq!(
+            "https://status.example.com:8443".parse(),
+            Ok(CorsOrigin::Origin(
+                "https://status.example.com:8443".to_string()
+            ))
+        );
+        assert!("https://status.example.com/".parse::<CorsOrigin>().is_err());
+        assert!("status.example.com".parse::<CorsOrigin>().is_err());
+    }
+
+    #[test]
+    fn only_allowed_origins_get_the_headers() {
+        let cors = Cors::new(vec![CorsOrigin::Origin(
+            "https://status.example.com".to_string(),
+        )]);
+        let request_from = |origin: &'static str| {
+            let mut req_headers = HeaderMap::new();
+            req_headers.insert(header::ORIGIN, HeaderValue::from_static(origin));
+            let mut res_headers = HeaderMap::new();
+            cors.apply(&req_headers, &mut res_headers);
+            res_headers
+        };
+
+        let allowed = request_from("https://status.example.com");
+        assert_eq!(
+            allowed[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://status.example.com"
+        );
+        assert_eq!(allowed[header::VARY], "Origin");
+
+        let denied = request_from("https://evil.example.com");
+        assert!(denied.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+        let any = Cors::new(vec![CorsOrigin::Any]);
+        let mut req_headers = HeaderMap::new();
+        req_headers.insert(
+            header::ORIGIN,
+            HeaderValue::from_static("https://evil.example.com"),
+        );
+        let res = any.preflight(&req_headers);
+        assert_eq!(res.status(), 204);
+        assert_eq!(res.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert_eq!(res.headers()[header::ACCESS_CONTROL_ALLOW_METHODS], "GET");
+    }
+}
@@ -0,0 +1,168 @@
+mod json;
+mod openmetrics;
+
+pub use json::JsonEncoder;
+pub use openmetrics::OpenMetricsEncoder;
+
+use std::io::Write;
+
+use prometheus::{proto::MetricFamily, Encoder};
+
+/// Bodies smaller than this are sent uncompressed, as gzip would save little.
+pub const MIN_GZIP_SIZE: usize = 1024;
+
+/// Formats the metrics can be served in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    OpenMetrics,
+    Json,
+    /// JSON families along with the schema version, which is never negotiated
+    /// but served at its own path.
+    JsonDocument,
+}
+
+impl Format {
+    /// Negotiates the format from the `Accept` header of the request, picking
+    /// the supported media type of highest quality and the Prometheus text
+    /// format if none is.
+    pub fn negotiate(headers: &hyper::HeaderMap) -> Self {
+        let mut best: Option<(Format, f32)> = None;
+
+        let media_ranges = headers
+            .get_all(hyper::header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for media_range in media_ranges {
+            let mut params = media_range.split(';').map(str::trim);
+            let format = match params.next().unwrap_or_default() {
+                "application/openmetrics-text" => Format::OpenMetrics,
+                "application/json" => Format::Json,
+                "text/plain" => Format::Text,
+                _ => continue,
+            };
+            let quality = params
+                .filter_map(|param| param.strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            let better = match best {
+                Some((_, best_quality)) => quality > best_quality,
+                None => quality > 0.0,
+            };
+            if better {
+                best = Some((format, quality));
+            }
+        }
+
+        best.map(|(format, _)| format).unwrap_or(Format::Text)
+    }
+}
+
+/// Encodes the families in the format, returning them along with their content
+/// type.
+pub fn encode(mfs: &[MetricFamily], format: Format) -> prometheus::Result<(Vec<u8>, String)> {
+    let mut buf = vec![];
+
+    let format_type = match format {
+        Format::Json | Format::JsonDocument => {
+            let encoder = if format == Format::Json {
+                JsonEncoder::new()
+            } else {
+                JsonEncoder::document()
+            };
+            encoder.encode(mfs, &mut buf)?;
+            encoder.format_type().to_string()
+        }
+        Format::OpenMetrics => {
+            let encoder = OpenMetricsEncoder::new();
+            encoder.encode(mfs, &mut buf)?;
+            encoder.format_type().to_string()
+        }
+        Format::Text => {
+            let encoder = prometheus::TextEncoder::new();
+            encoder.encode(mfs, &mut buf)?;
+            encoder.format_type().to_string()
+        }
+    };
+
+    Ok((buf, format_type))
+}
+
+/// Whether the `Accept-Encoding` header of the request allows gzip.
+pub fn accepts_gzip(headers: &hyper::HeaderMap) -> bool {
+    headers
+        .get_all(hyper::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or_default();
+            let quality = params
+                .filter_map(|param| param.strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            matches!(name, "gzip" | "*") && quality > 0.0
+        })
+}
+
+pub fn gzip(buf: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(buf)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn negotiate(accept: &str) -> Format {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(hyper::header::ACCEPT, accept.parse().expect("valid header"));
+
+        Format::negotiate(&headers)
+    }
+
+    #[test]
+    fn formats_are_negotiated_by_quality() {
+        // Sent by Prometheus 2.x
+        assert_eq!(
+            negotiate(
+                "application/openmetrics-text;version=1.0.0,application/openmetrics-text;\
+                 version=0.0.1;q=0.75,text/plain;version=0.0.4;q=0.5,*/*;q=0.1"
+            ),
+            Format::OpenMetrics
+        );
+        assert_eq!(
+            negotiate("application/openmetrics-text;q=0.5,text/plain"),
+            Format::Text
+        );
+        assert_eq!(negotiate("application/json"), Format::Json);
+        assert_eq!(negotiate("application/openmetrics-text;q=0"), Format::Text);
+        assert_eq!(negotiate("*/*"), Format::Text);
+        assert_eq!(Format::negotiate(&hyper::HeaderMap::new()), Format::Text);
+    }
+
+    #[test]
+    fn gzip_is_accepted_unless_refused() {
+        let accepts = |accept_encoding: &str| {
+            let mut headers = hyper::HeaderMap::new();
+            headers.insert(
+                hyper::header::ACCEPT_ENCODING,
+                accept_encoding.parse().expect("valid header"),
+            );
+
+            accepts_gzip(&headers)
+        };
+
+        assert!(accepts("gzip"));
+        assert!(accepts("deflate, gzip;q=0.8"));
+        assert!(accepts("*"));
+        assert!(!accepts("gzip;q=0"));
+        assert!(!accepts("identity"));
+        assert!(!accepts_gzip(&hyper::HeaderMap::new()));
+    }
+}
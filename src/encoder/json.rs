@@ -0,0 +1,305 @@
+use std::{collections::BTreeMap, io::Write};
+
+use prometheus::{
+    proto::{Metric, MetricFamily, MetricType},
+    Encoder,
+};
+use serde::Serialize;
+
+/// Version of the schema of JSON documents, bumped on breaking changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Encodes metric families as a JSON array, for consumers parsing JSON more
+/// easily than the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct JsonEncoder {
+    document: bool,
+}
+
+impl JsonEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes the families as a document along with the schema version, i.e.
+    /// `{"schema_version": 1, "families": [...]}`.
+    pub fn document() -> Self {
+        Self { document: true }
+    }
+}
+
+impl Encoder for JsonEncoder {
+    fn encode<W: Write>(&self, mfs: &[MetricFamily], writer: &mut W) -> prometheus::Result<()> {
+        let families = mfs.iter().map(MetricFamilyJson::from).collect::<Vec<_>>();
+
+        let res = if self.document {
+            serde_json::to_writer(
+                writer,
+                &DocumentJson {
+                    schema_version: SCHEMA_VERSION,
+                    families,
+                },
+            )
+        } else {
+            serde_json::to_writer(writer, &families)
+        };
+        res.map_err(|e| prometheus::Error::Msg(e.to_string()))
+    }
+
+    fn format_type(&self) -> &str {
+        "application/json"
+    }
+}
+
+#[derive(Serialize)]
+struct DocumentJson<'a> {
+    schema_version: u32,
+    families: Vec<MetricFamilyJson<'a>>,
+}
+
+/// Serializable view of a [`MetricFamily`], which protobuf generated types
+/// cannot derive.
+#[derive(Serialize)]
+pub struct MetricFamilyJson<'a> {
+    name: &'a str,
+    help: &'a str,
+    #[serde(rename = "type")]
+    metric_type: &'static str,
+    metrics: Vec<MetricJson<'a>>,
+}
+
+impl<'a> From<&'a MetricFamily> for MetricFamilyJson<'a> {
+    fn from(mf: &'a MetricFamily) -> Self {
+        let metric_type = mf.get_field_type();
+
+        Self {
+            name: mf.get_name(),
+            help: mf.get_help(),
+            metric_type: match metric_type {
+                MetricType::COUNTER => "counter",
+                MetricType::GAUGE => "gauge",
+                MetricType::SUMMARY => "summary",
+                MetricType::UNTYPED => "untyped",
+                MetricType::HISTOGRAM => "histogram",
+            },
+            metrics: mf
+                .get_metric()
+                .iter()
+                .map(|m| MetricJson::new(m, metric_type))
+                .collect(),
+        }
+    }
+}
+
+/// Values that are not finite, e.g. the sum of an empty summary, serialize as
+/// `null`.
+#[derive(Serialize)]
+struct MetricJson<'a> {
+    labels: BTreeMap<&'a str, &'a str>,
+    #[serde(flatten)]
+    value: ValueJson,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp_ms: Option<i64>,
+}
+
+impl<'a> MetricJson<'a> {
+    fn new(m: &'a Metric, metric_type: MetricType) -> Self {
+        let value = match metric_type {
+            MetricType::COUNTER => ValueJson::Value {
+                value: m.get_counter().get_value(),
+            },
+            MetricType::GAUGE => ValueJson::Value {
+                value: m.get_gauge().get_value(),
+            },
+            MetricType::UNTYPED => ValueJson::Value {
+                value: m.get_untyped().get_value(),
+            },
+            MetricType::SUMMARY => {
+                let summary = m.get_summary();
+                ValueJson::Summary {
+                    count: summary.get_sample_count(),
+                    sum: summary.get_sample_sum(),
+                    quantiles: summary
+                        .get_quantile()
+                        .iter()
+                        .map(|q| QuantileJson {
+                            quantile: q.get_quantile(),
+                            value: q.get_value(),
+                        })
+                        .collect(),
+                }
+            }
+            MetricType::HISTOGRAM => {
+                let histogram = m.get_histogram();
+                let mut buckets = histogram
+                    .get_bucket()
+                    .iter()
+                    .map(|b| BucketJson {
+                        le: upper_bound(b.get_upper_bound()),
+                        count: b.get_cumulative_count(),
+                    })
+                    .collect::<Vec<_>>();
+                // Like the text format, the +Inf bucket is implied
+                if !matches!(buckets.last(), Some(b) if b.le == POSITIVE_INF) {
+                    buckets.push(BucketJson {
+                        le: POSITIVE_INF.to_string(),
+                        count: histogram.get_sample_count(),
+                    });
+                }
+
+                ValueJson::Histogram {
+                    count: histogram.get_sample_count(),
+                    sum: histogram.get_sample_sum(),
+                    buckets,
+                }
+            }
+        };
+
+        let timestamp_ms = m.get_timestamp_ms();
+
+        Self {
+            labels: m
+                .get_label()
+                .iter()
+                .map(|l| (l.get_name(), l.get_value()))
+                .collect(),
+            value,
+            timestamp_ms: if timestamp_ms != 0 {
+                Some(timestamp_ms)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ValueJson {
+    Value {
+        value: f64,
+    },
+    Summary {
+        count: u64,
+        sum: f64,
+        quantiles: Vec<QuantileJson>,
+    },
+    Histogram {
+        count: u64,
+        sum: f64,
+        buckets: Vec<BucketJson>,
+    },
+}
+
+#[derive(Serialize)]
+struct QuantileJson {
+    quantile: f64,
+    value: f64,
+}
+
+/// Cumulative count of a histogram bucket, by its upper bound as in the text
+/// format.
+#[derive(Serialize)]
+struct BucketJson {
+    le: String,
+    count: u64,
+}
+
+const POSITIVE_INF: &str = "+Inf";
+
+fn upper_bound(le: f64) -> String {
+    if le.is_infinite() && le.is_sign_positive() {
+        POSITIVE_INF.to_string()
+    } else {
+        le.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn families_are_encoded_as_json() {
+        let payments = prometheus::IntCounterVec::new(
+            prometheus::Opts::new("lnd_payments_total", "Payments"),
+            &["status"],
+        )
+        .expect("valid metric");
+        payments.with_label_values(&["succeeded"]).inc_by(3);
+        let duration = prometheus::Histogram::with_opts(
+            prometheus::HistogramOpts::new("lnd_scrape_seconds", "Scrape duration")
+                .buckets(vec![0.5, 1.0]),
+        )
+        .expect("valid metric");
+        duration.observe(0.7);
+
+        let registry = prometheus::Registry::new();
+        registry
+            .register(Box::new(payments))
+            .expect("registered metric");
+        registry
+            .register(Box::new(duration))
+            .expect("registered metric");
+
+        let mut buf = vec![];
+        JsonEncoder::new()
+            .encode(&registry.gather(), &mut buf)
+            .expect("encoded metrics");
+
+        let json: serde_json::Value = serde_json::from_slice(&buf).expect("json metrics");
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "name": "lnd_payments_total",
+                    "help": "Payments",
+                    "type": "counter",
+                    "metrics": [{"labels": {"status": "succeeded"}, "value": 3.0}],
+                },
+                {
+                    "name": "lnd_scrape_seconds",
+                    "help": "Scrape duration",
+                    "type": "histogram",
+                    "metrics": [{
+                        "labels": {},
+                        "count": 1,
+                        "sum": 0.7,
+                        "buckets": [
+                            {"le": "0.5", "count": 0},
+                            {"le": "1", "count": 1},
+                            {"le": "+Inf", "count": 1},
+                        ],
+                    }],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn documents_carry_the_schema_version() {
+        let up = prometheus::IntGauge::new("lnd_up", "Whether lnd is up").expect("valid metric");
+        up.set(1);
+        let registry = prometheus::Registry::new();
+        registry.register(Box::new(up)).expect("registered metric");
+
+        let mut buf = vec![];
+        JsonEncoder::document()
+            .encode(&registry.gather(), &mut buf)
+            .expect("encoded metrics");
+
+        let json: serde_json::Value = serde_json::from_slice(&buf).expect("json metrics");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "schema_version": 1,
+                "families": [{
+                    "name": "lnd_up",
+                    "help": "Whether lnd is up",
+                    "type": "gauge",
+                    "metrics": [{"labels": {}, "value": 1.0}],
+                }],
+            })
+        );
+    }
+}
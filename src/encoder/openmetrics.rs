@@ -0,0 +1,229 @@
+use std::io::Write;
+
+use prometheus::{
+    proto::{LabelPair, Metric, MetricFamily, MetricType},
+    Encoder,
+};
+
+/// Content type of OpenMetrics 1.0.0 text.
+pub const OPENMETRICS_FORMAT: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Encodes metric families in the OpenMetrics text format, for scrapers
+/// preferring it over the Prometheus text format.
+///
+/// Counter families are named without their `_total` suffix, which their
+/// samples carry. Creation times are not tracked, so no `_created` samples are
+/// written.
+#[derive(Debug, Default)]
+pub struct OpenMetricsEncoder;
+
+impl OpenMetricsEncoder {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Encoder for OpenMetricsEncoder {
+    fn encode<W: Write>(&self, mfs: &[MetricFamily], writer: &mut W) -> prometheus::Result<()> {
+        for mf in mfs {
+            let metric_type = mf.get_field_type();
+            let name = match metric_type {
+                MetricType::COUNTER => mf
+                    .get_name()
+                    .strip_suffix("_total")
+                    .unwrap_or_else(|| mf.get_name()),
+                _ => mf.get_name(),
+            };
+
+            if !mf.get_help().is_empty() {
+                writeln!(writer, "# HELP {} {}", name, escape(mf.get_help()))?;
+            }
+            writeln!(
+                writer,
+                "# TYPE {} {}",
+                name,
+                match metric_type {
+                    MetricType::COUNTER => "counter",
+                    MetricType::GAUGE => "gauge",
+                    MetricType::SUMMARY => "summary",
+                    MetricType::UNTYPED => "unknown",
+                    MetricType::HISTOGRAM => "histogram",
+                }
+            )?;
+
+            for m in mf.get_metric() {
+                encode_metric(writer, name, metric_type, m)?;
+            }
+        }
+
+        writer.write_all(b"# EOF\n")?;
+        Ok(())
+    }
+
+    fn format_type(&self) -> &str {
+        OPENMETRICS_FORMAT
+    }
+}
+
+fn encode_metric<W: Write>(
+    writer: &mut W,
+    name: &str,
+    metric_type: MetricType,
+    m: &Metric,
+) -> std::io::Result<()> {
+    let labels = m.get_label();
+    // OpenMetrics timestamps are in seconds
+    let timestamp = if m.has_timestamp_ms() {
+        Some(m.get_timestamp_ms() as f64 / 1_000.0)
+    } else {
+        None
+    };
+    let mut sample = |suffix: &str, extra: Option<(&str, String)>, value: String| {
+        write!(writer, "{}{}", name, suffix)?;
+        write_labels(writer, labels, extra)?;
+        write!(writer, " {}", value)?;
+        if let Some(timestamp) = timestamp {
+            write!(writer, " {}", float(timestamp))?;
+        }
+        writeln!(writer)
+    };
+
+    match metric_type {
+        MetricType::COUNTER => sample("_total", None, float(m.get_counter().get_value())),
+        MetricType::GAUGE => sample("", None, float(m.get_gauge().get_value())),
+        MetricType::UNTYPED => sample("", None, float(m.get_untyped().get_value())),
+        MetricType::SUMMARY => {
+            let summary = m.get_summary();
+            for quantile in summary.get_quantile() {
+                sample(
+                    "",
+                    Some(("quantile", float(quantile.get_quantile()))),
+                    float(quantile.get_value()),
+                )?;
+            }
+            sample("_sum", None, float(summary.get_sample_sum()))?;
+            sample("_count", None, summary.get_sample_count().to_string())
+        }
+        MetricType::HISTOGRAM => {
+            let histogram = m.get_histogram();
+            let mut has_inf = false;
+            for bucket in histogram.get_bucket() {
+                has_inf |= bucket.get_upper_bound() == f64::INFINITY;
+                sample(
+                    "_bucket",
+                    Some(("le", float(bucket.get_upper_bound()))),
+                    bucket.get_cumulative_count().to_string(),
+                )?;
+            }
+            // The +Inf bucket is required, but left out of the protobuf
+            if !has_inf {
+                sample(
+                    "_bucket",
+                    Some(("le", float(f64::INFINITY))),
+                    histogram.get_sample_count().to_string(),
+                )?;
+            }
+            sample("_sum", None, float(histogram.get_sample_sum()))?;
+            sample("_count", None, histogram.get_sample_count().to_string())
+        }
+    }
+}
+
+fn write_labels<W: Write>(
+    writer: &mut W,
+    labels: &[LabelPair],
+    extra: Option<(&str, String)>,
+) -> std::io::Result<()> {
+    if labels.is_empty() && extra.is_none() {
+        return Ok(());
+    }
+
+    let pairs = labels
+        .iter()
+        .map(|label| (label.get_name(), label.get_value()))
+        .chain(extra.as_ref().map(|(name, value)| (*name, value.as_str())))
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect::<Vec<_>>();
+
+    write!(writer, "{{{}}}", pairs.join(","))
+}
+
+/// Escapes backslashes, double quotes and line feeds, as both label values and
+/// help texts require.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Renders the number in the canonical form OpenMetrics expects, e.g. `1.0`
+/// rather than `1` for bucket bounds.
+fn float(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_string()
+    } else if f.is_infinite() {
+        if f.is_sign_positive() { "+Inf" } else { "-Inf" }.to_string()
+    } else if f.fract() == 0.0 && f.abs() < 1e15 {
+        format!("{:.1}", f)
+    } else {
+        f.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn families_are_encoded_as_openmetrics() {
+        let payments = prometheus::IntCounterVec::new(
+            prometheus::Opts::new("lnd_payments_total", "Payments\nby \"status\""),
+            &["status"],
+        )
+        .expect("valid metric");
+        payments.with_label_values(&["succeeded"]).inc_by(3);
+        let balance =
+            prometheus::IntGauge::new("lnd_balance_sat", "Balance").expect("valid metric");
+        balance.set(1_000);
+        let duration = prometheus::Histogram::with_opts(
+            prometheus::HistogramOpts::new("lnd_scrape_seconds", "Scrape duration")
+                .buckets(vec![0.5, 1.0]),
+        )
+        .expect("valid metric");
+        duration.observe(0.7);
+
+        let registry = prometheus::Registry::new();
+        registry
+            .register(Box::new(payments))
+            .expect("registered metric");
+        registry
+            .register(Box::new(balance))
+            .expect("registered metric");
+        registry
+            .register(Box::new(duration))
+            .expect("registered metric");
+
+        let mut buf = vec![];
+        OpenMetricsEncoder::new()
+            .encode(&registry.gather(), &mut buf)
+            .expect("encoded metrics");
+
+        assert_eq!(
+            String::from_utf8(buf).expect("utf-8 metrics"),
+            "# HELP lnd_balance_sat Balance\n\
+             # TYPE lnd_balance_sat gauge\n\
+             lnd_balance_sat 1000.0\n\
+             # HELP lnd_payments Payments\\nby \\\"status\\\"\n\
+             # TYPE lnd_payments counter\n\
+             lnd_payments_total{status=\"succeeded\"} 3.0\n\
+             # HELP lnd_scrape_seconds Scrape duration\n\
+             # TYPE lnd_scrape_seconds histogram\n\
+             lnd_scrape_seconds_bucket{le=\"0.5\"} 0\n\
+             lnd_scrape_seconds_bucket{le=\"1.0\"} 1\n\
+             lnd_scrape_seconds_bucket{le=\"+Inf\"} 1\n\
+             lnd_scrape_seconds_sum 0.7\n\
+             lnd_scrape_seconds_count 1\n\
+             # EOF\n"
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use std::{sync::Arc, time::Instant};
+
+use serde::Serialize;
+
+use crate::collector::{HealthStatus, LndCollector};
+
+/// Health of the exporter served at `/healthz`.
+#[derive(Serialize)]
+struct Health {
+    version: &'static str,
+    uptime_seconds: f64,
+    nodes: Vec<HealthStatus>,
+}
+
+/// Checks the health of the nodes, returning whether they are all reachable
+/// with their wallet unlocked along with the encoded report.
+pub async fn report(
+    collectors: &[Arc<LndCollector>],
+    started_at: Instant,
+) -> (bool, serde_json::Result<Vec<u8>>) {
+    let nodes = futures_util::future::join_all(
+        collectors.iter().map(|collector| collector.health_status()),
+    )
+    .await;
+    let healthy = nodes.iter().all(HealthStatus::healthy);
+
+    let health = Health {
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_seconds: started_at.elapsed().as_secs_f64(),
+        nodes,
+    };
+
+    (healthy, serde_json::to_vec(&health))
+}
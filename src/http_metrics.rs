@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use crate::collector::MetricsConfig;
+
+/// Label of the paths and methods the exporter doesn't serve, so that scanners
+/// can't blow up the cardinality.
+const OTHER: &str = "other";
+
+const KNOWN_METHODS: &[&str] = &["GET", "HEAD", "POST", "PUT", "DELETE", "OPTIONS", "PATCH"];
+
+/// Metrics of the requests to the exporter itself, which don't depend on lnd
+/// being reachable.
+#[derive(Clone)]
+pub struct HttpMetrics {
+    known_paths: Vec<String>,
+    requests_total: prometheus::IntCounterVec,
+    request_duration_seconds: prometheus::HistogramVec,
+    requests_in_flight: prometheus::IntGauge,
+    request_timeouts_total: prometheus::IntCounter,
+    connections_rejected_total: prometheus::IntCounter,
+}
+
+impl HttpMetrics {
+    pub fn new(known_paths: Vec<String>, config: &MetricsConfig) -> Self {
+        Self {
+            known_paths,
+            requests_total: prometheus::IntCounterVec::new(
+                config.opts(
+                    "exporter_http_requests_total",
+                    "Number of HTTP requests served by the exporter",
+                ),
+                &["path", "method", "code"],
+            )
+            .expect("valid metric"),
+            request_duration_seconds: prometheus::HistogramVec::new(
+                prometheus::HistogramOpts::from(config.opts(
+                    "exporter_http_request_duration_seconds",
+                    "Duration of the HTTP requests served by the exporter",
+                )),
+                &["path"],
+            )
+            .expect("valid metric"),
+            requests_in_flight: prometheus::IntGauge::with_opts(config.opts(
+                "exporter_http_requests_in_flight",
+                "Number of HTTP requests being served by the exporter",
+            ))
+            .expect("valid metric"),
+            request_timeouts_total: prometheus::IntCounter::with_opts(config.opts(
+                "exporter_http_request_timeouts_total",
+                "Number of HTTP requests aborted for taking too long",
+            ))
+            .expect("valid metric"),
+            connections_rejected_total: prometheus::IntCounter::with_opts(config.opts(
+                "exporter_http_connections_rejected_total",
+                "Number of HTTP connections rejected for exceeding the connection limit",
+            ))
+            .expect("valid metric"),
+        }
+    }
+
+    /// Counts the request as in flight until the returned guard is dropped.
+    pub fn in_flight(&self) -> InFlightGuard<'_> {
+        self.requests_in_flight.inc();
+        InFlightGuard(&self.requests_in_flight)
+    }
+
+    pub fn request_timed_out(&self) {
+        self.request_timeouts_total.inc();
+    }
+
+    pub fn connection_rejected(&self) {
+        self.connections_rejected_total.inc();
+    }
+
+    pub fn observe(&self, path: &str, method: &str, code: u16, duration: Duration) {
+        let path = if self.known_paths.iter().any(|known| known == path) {
+            path
+        } else {
+            OTHER
+        };
+        let method = if KNOWN_METHODS.contains(&method) {
+            method
+        } else {
+            OTHER
+        };
+
+        self.requests_total
+            .with_label_values(&[path, method, &code.to_string()])
+            .inc();
+        self.request_duration_seconds
+            .with_label_values(&[path])
+            .observe(duration.as_secs_f64());
+    }
+}
+
+pub struct InFlightGuard<'a>(&'a prometheus::IntGauge);
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+impl Collector for HttpMetrics {
+    fn desc(&self) -> Vec<&Desc> {
+        self.requests_total
+            .desc()
+            .into_iter()
+            .chain(self.request_duration_seconds.desc())
+            .chain(self.requests_in_flight.desc())
+            .chain(self.request_timeouts_total.desc())
+            .chain(self.connections_rejected_total.desc())
+            .collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.requests_total
+            .collect()
+            .into_iter()
+            .chain(self.request_duration_seconds.collect())
+            .chain(self.requests_in_flight.collect())
+            .chain(self.request_timeouts_total.collect())
+            .chain(self.connections_rejected_total.collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_paths_and_methods_are_grouped() {
+        let metrics = HttpMetrics::new(
+            vec!["/metrics".to_string(), "/health".to_string()],
+            &MetricsConfig::new("lnd"),
+        );
+
+        {
+            let _guard = metrics.in_flight();
+            assert_eq!(metrics.requests_in_flight.get(), 1);
+        }
+        assert_eq!(metrics.requests_in_flight.get(), 0);
+
+        metrics.observe("/metrics", "GET", 200, Duration::from_millis(20));
+        metrics.observe("/wp-login.php", "GET", 404, Duration::from_millis(1));
+        metrics.observe("/.env", "PROPFIND", 404, Duration::from_millis(1));
+
+        assert_eq!(
+            metrics
+                .requests_total
+                .with_label_values(&["/metrics", "GET", "200"])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .requests_total
+                .with_label_values(&["other", "GET", "404"])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .requests_total
+                .with_label_values(&["other", "other", "404"])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .request_duration_seconds
+                .with_label_values(&["other"])
+                .get_sample_count(),
+            2
+        );
+    }
+}
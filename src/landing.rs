@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use crate::collector::LndCollector;
+
+const LANDING_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><title>{name}</title></head>
+<body>
+<h1>{name}</h1>
+<p>Version {version}</p>
+<ul>
+<li><a href="{telemetry_path}">Metrics</a></li>
+<li><a href="/health">Health</a></li>
+<li><a href="/ready">Readiness</a></li>
+</ul>
+<h2>Nodes</h2>
+<ul>
+{nodes}</ul>
+</body>
+</html>
+"#;
+
+/// Page served at `/`, pointing at the other endpoints. Nodes are shown with
+/// the alias of their last GetInfo response, so rendering never waits on lnd.
+pub fn render(
+    telemetry_path: &str,
+    endpoints: &[String],
+    collectors: &[Arc<LndCollector>],
+) -> String {
+    let nodes = collectors
+        .iter()
+        .enumerate()
+        .map(|(i, collector)| {
+            let alias = collector.alias().unwrap_or_else(|| "unknown".to_string());
+            let endpoint = endpoints.get(i).map(String::as_str).unwrap_or_default();
+            format!("<li>{} ({})</li>\n", escape(&alias), escape(endpoint))
+        })
+        .collect::<String>();
+
+    LANDING_PAGE
+        .replace("{name}", env!("CARGO_PKG_NAME"))
+        .replace("{version}", crate::VERSION)
+        .replace("{telemetry_path}", &escape(telemetry_path))
+        .replace("{nodes}", &nodes)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
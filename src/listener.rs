@@ -0,0 +1,201 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+/// Address the exporter listens at, either `HOST:PORT` or `unix:PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl std::str::FromStr for ListenAddr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("unix:") {
+            Some("") => Err("unix socket path is empty".to_string()),
+            Some(_) if cfg!(not(unix)) => {
+                Err("unix sockets are not supported on this platform".to_string())
+            }
+            Some(path) => Ok(ListenAddr::Unix(PathBuf::from(path))),
+            None => s
+                .parse()
+                .map(ListenAddr::Tcp)
+                .map_err(|_| format!("invalid listen address {:?}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenAddr::Tcp(addr) => write!(f, "{}", addr),
+            ListenAddr::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl ListenAddr {
+    /// Binds the address, setting the permission bits of unix sockets to
+    /// `unix_socket_mode`.
+    pub fn bind(&self, unix_socket_mode: u32) -> std::io::Result<Listener> {
+        match self {
+            ListenAddr::Tcp(addr) => std::net::TcpListener::bind(addr).map(Listener::Tcp),
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => {
+                UnixIncoming::bind(path, unix_socket_mode).map(Listener::Unix)
+            }
+            #[cfg(not(unix))]
+            ListenAddr::Unix(_) => {
+                let _ = unix_socket_mode;
+                unreachable!("unix listen address")
+            }
+        }
+    }
+}
+
+/// Socket the exporter serves at, bound from a [`ListenAddr`] or passed by
+/// systemd.
+pub enum Listener {
+    Tcp(std::net::TcpListener),
+    #[cfg(unix)]
+    Unix(UnixIncoming),
+}
+
+#[cfg(unix)]
+pub use unix::UnixIncoming;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        os::unix::fs::{FileTypeExt, PermissionsExt},
+        path::{Path, PathBuf},
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use tokio::net::{UnixListener, UnixStream};
+
+    /// Connections of a unix socket listener. The socket file is removed when
+    /// the listener is dropped, i.e. on graceful shutdown, unless it was passed
+    /// by systemd, which removes it itself.
+    pub struct UnixIncoming {
+        listener: UnixListener,
+        path: Option<PathBuf>,
+    }
+
+    impl UnixIncoming {
+        /// Binds the socket, replacing the file of a previous run, and sets
+        /// its permission bits to `mode`.
+        pub fn bind(path: &Path, mode: u32) -> std::io::Result<Self> {
+            match std::fs::symlink_metadata(path) {
+                Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+                Ok(_) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        "file exists and is not a socket",
+                    ))
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+
+            let listener = UnixListener::bind(path)?;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+
+            Ok(Self {
+                listener,
+                path: Some(path.to_path_buf()),
+            })
+        }
+
+        /// Takes over a bound socket, leaving its file in place.
+        pub fn from_std(listener: std::os::unix::net::UnixListener) -> std::io::Result<Self> {
+            listener.set_nonblocking(true)?;
+
+            Ok(Self {
+                listener: UnixListener::from_std(listener)?,
+                path: None,
+            })
+        }
+
+        /// Address clients of the socket are logged with.
+        pub fn remote_addr(&self) -> String {
+            let path = self
+                .listener
+                .local_addr()
+                .ok()
+                .and_then(|addr| addr.as_pathname().map(Path::to_path_buf));
+
+            match path {
+                Some(path) => format!("unix:{}", path.display()),
+                None => "unix:".to_string(),
+            }
+        }
+    }
+
+    impl Drop for UnixIncoming {
+        fn drop(&mut self) {
+            if let Some(path) = &self.path {
+                if let Err(e) = std::fs::remove_file(path) {
+                    log::warn!("Failed to remove {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    impl hyper::server::accept::Accept for UnixIncoming {
+        type Conn = UnixStream;
+        type Error = std::io::Error;
+
+        fn poll_accept(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+            self.listener
+                .poll_accept(cx)
+                .map(|res| Some(res.map(|(stream, _)| stream)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listen_addrs_are_parsed() {
+        assert_eq!(
+            "127.0.0.1:29090".parse(),
+            Ok(ListenAddr::Tcp(
+                "127.0.0.1:29090".parse().expect("valid address")
+            ))
+        );
+        assert!("localhost".parse::<ListenAddr>().is_err());
+        assert!("unix:".parse::<ListenAddr>().is_err());
+        #[cfg(unix)]
+        assert_eq!(
+            "unix:/run/lnd-exporter.sock".parse(),
+            Ok(ListenAddr::Unix(PathBuf::from("/run/lnd-exporter.sock")))
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stale_sockets_are_replaced_and_removed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("lnd-exporter-{}.sock", std::process::id()));
+        drop(std::os::unix::net::UnixListener::bind(&path).expect("stale socket"));
+        assert!(path.exists());
+
+        let incoming = UnixIncoming::bind(&path, 0o600).expect("bound socket");
+        let mode = std::fs::metadata(&path)
+            .expect("socket")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        drop(incoming);
+        assert!(!path.exists());
+    }
+}
@@ -1,52 +1,862 @@
+mod allowlist;
+mod auth;
+mod cache;
 mod collector;
+mod config;
+mod cors;
+mod debug;
+mod encoder;
+mod health;
+mod http_metrics;
+mod landing;
+mod listener;
+mod once;
+mod otlp;
+mod probe;
+#[cfg(feature = "process-metrics")]
+mod process_metrics;
+mod push;
+mod rate_limit;
+mod runtime_metrics;
+mod systemd;
+#[cfg(test)]
+mod tests;
+mod tls;
 
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 
-use clap::Parser;
-use prometheus::Encoder;
+use clap::{Args, CommandFactory, FromArgMatches, Parser};
+use serde::{Deserialize, Serialize};
 use tokio::io::AsyncReadExt;
 
-use crate::collector::LndCollector;
+use crate::allowlist::{Cidr, SourceAllowlist};
+use crate::auth::TokenAuth;
+use crate::cache::GatherCache;
+use crate::collector::{
+    check_permissions, BitcoindClient, BlockExplorer, CollectorOptions, FamilyFilter, LndCollector,
+    MetricsConfig, MultiNodeCollector, ScopedLndClientSet, ScraperName, ScraperSet,
+    SharedCollector, NODE_LABELS, WALLET_STATE_POLL_INTERVAL,
+};
+use crate::cors::{Cors, CorsOrigin};
+use crate::http_metrics::HttpMetrics;
+use crate::listener::{ListenAddr, Listener};
+use crate::otlp::OtlpExporter;
+use crate::probe::{ProbeModule, Prober};
+use crate::push::Pusher;
+use crate::rate_limit::RateLimiter;
+use crate::runtime_metrics::RuntimeCollector;
 
+/// Version of the exporter, as reported by `--version`.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Short git hash of the commit the exporter was built from.
+const REVISION: &str = env!("LND_EXPORTER_REVISION");
+const RUSTC_VERSION: &str = env!("LND_EXPORTER_RUSTC_VERSION");
+
+/// Interval lnd is checked at until systemd can be told the exporter is
+/// ready, with `--systemd-notify-wait-lnd`.
+const SYSTEMD_READY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time in-flight requests have to complete once shutdown starts.
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prometheus exporter of lnd metrics.
 #[derive(Parser)]
-#[clap(version = "0.1.0", author = "Felipe Rosa <felipe.sgrosa@gmail.com>")]
+#[clap(version = VERSION, author = "Felipe Rosa <felipe.sgrosa@gmail.com>")]
 struct Opts {
+    #[clap(flatten)]
+    lnd: LndOpts,
+    #[clap(flatten)]
+    web: WebOpts,
+    #[clap(long, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    #[clap(long, value_name = "DIR")]
+    replay: Option<PathBuf>,
+    /// TOML file with `[lnd]`, `[web]`, `[collectors]` and `[labels]` tables.
+    /// Flags on the command line take precedence.
+    #[clap(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Fails on unknown config file keys rather than ignoring them.
+    #[clap(long, requires = "config")]
+    strict_config: bool,
+    /// Prints the effective config file, with credentials redacted, and exits.
     #[clap(long)]
-    macaroon_path: Option<String>,
+    print_config: bool,
+    /// Scrapes lnd once, prints the metrics and exits, non-zero if any
+    /// scraper failed. No HTTP server is started.
+    #[clap(long)]
+    once: bool,
+    /// Writes the metrics of `--once` to this file, replacing it atomically,
+    /// rather than to stdout.
+    #[clap(long, value_name = "FILE", requires = "once")]
+    output: Option<PathBuf>,
+    /// Exits with success after `--once` even if some scrapers failed.
+    #[clap(long, requires = "once")]
+    allow_partial: bool,
+    /// Holds off telling systemd the exporter is ready until every lnd node
+    /// answered GetInfo, when run as a `Type=notify` service.
+    #[clap(long)]
+    systemd_notify_wait_lnd: bool,
+    #[clap(long, default_value = "10", value_name = "SECONDS")]
+    scraper_timeout: u64,
+    #[clap(
+        long = "enable-collector",
+        alias = "enable-scraper",
+        value_name = "NAME"
+    )]
+    enabled_collectors: Vec<ScraperName>,
+    #[clap(
+        long = "disable-collector",
+        alias = "disable-scraper",
+        value_name = "NAME"
+    )]
+    disabled_collectors: Vec<ScraperName>,
+    #[clap(long)]
+    enable_backup_metrics: bool,
+    /// Same as `--enable-collector watchtower`.
+    #[clap(long)]
+    enable_watchtower_metrics: bool,
+    /// Same as `--enable-collector autopilot`.
+    #[clap(long)]
+    enable_autopilot_metrics: bool,
+    /// Same as `--enable-collector closefees`.
+    #[clap(long)]
+    enable_close_fee_estimates: bool,
+    /// Confirmation target of the channel close fee estimates.
+    #[clap(long, default_value = "6", value_name = "BLOCKS")]
+    close_fee_estimate_confs: i32,
+    #[clap(long, default_value = "300", value_name = "SECONDS")]
+    close_fee_estimate_refresh_secs: u64,
+    /// Same as `--enable-collector hubdistances`, which needs `--hub-pubkeys`.
+    #[clap(long)]
+    enable_graph_distance_metrics: bool,
+    /// Routing hubs to export the hop distance of, comma separated.
+    #[clap(long, value_name = "PUBKEYS", use_value_delimiter = true)]
+    hub_pubkeys: Vec<String>,
+    #[clap(long, default_value = "0", value_name = "SECONDS")]
+    min_scrape_interval: u64,
+    #[clap(long, default_value = "3", value_name = "COUNT")]
+    flap_threshold: u64,
+    /// Time after which pending HTLCs are counted as stuck.
+    #[clap(long, default_value = "3600", value_name = "SECONDS")]
+    stuck_htlc_threshold_secs: u64,
+    /// Fee rate below which Bitcoin Core treats outputs as dust, its
+    /// `-dustrelayfee`. HTLCs too small to pay for their output are flagged.
+    #[clap(long, default_value = "3", value_name = "SAT_PER_VB")]
+    dust_relay_fee_rate: u64,
+    #[clap(long, default_value = "2", value_name = "N")]
+    scraper_retries: u32,
+    /// Fails startup when the macaroons lack permissions of an enabled
+    /// scraper, and keeps scraping the ones lnd refuses later on rather than
+    /// disabling them.
+    #[clap(long)]
+    strict_permissions: bool,
+    #[clap(long)]
+    require_all_permissions: bool,
+    #[clap(long)]
+    enable_sync_progress_metric: bool,
+    /// Serves /debug/status, with the last scrape errors and GetInfo response.
+    #[clap(long)]
+    enable_debug_endpoints: bool,
+    #[clap(
+        long,
+        default_value = "https://mempool.space/api/blocks/tip/height",
+        value_name = "URL"
+    )]
+    block_explorer_api_url: hyper::Uri,
+    #[clap(long, default_value = "lnd", value_name = "NAMESPACE")]
+    metric_namespace: String,
+    #[clap(long = "const-label", value_name = "KEY=VALUE")]
+    const_labels: Vec<String>,
+    /// Exports the tokio runtime metrics of the exporter, named
+    /// `exporter_tokio_*` under the metric namespace.
+    #[clap(long)]
+    runtime_metrics: bool,
+    #[clap(long)]
+    no_process_metrics: bool,
+    #[clap(long)]
+    no_wait_unlock: bool,
+    #[clap(long, default_value = "1", value_name = "N")]
+    metrics_max_concurrent_scrapes: usize,
+    #[clap(long, default_value = "0", value_name = "SECONDS")]
+    metrics_min_interval_secs: u64,
+    #[clap(long, default_value = "20", value_name = "N")]
+    top_destinations_n: usize,
+    #[clap(long, default_value = "10000", value_name = "N")]
+    payment_distribution_window: usize,
+    #[clap(long)]
+    enable_invoice_proof_metrics: bool,
+    /// Same as `--enable-collector mempool`.
+    #[clap(long)]
+    enable_mempool_metrics: bool,
+    #[clap(long, default_value = "http://127.0.0.1:8332", value_name = "URL")]
+    bitcoin_rpc_url: String,
     #[clap(long)]
+    bitcoin_rpc_user: Option<String>,
+    #[clap(long)]
+    bitcoin_rpc_pass: Option<String>,
+    #[clap(
+        long = "probe-module",
+        value_name = "NAME=MACAROON_PATH[,TLS_CERT_PATH]"
+    )]
+    probe_modules: Vec<String>,
+    #[clap(long, default_value = "16", value_name = "N")]
+    probe_max_targets: usize,
+    #[clap(long, value_name = "REGEX")]
+    metric_drop: Option<String>,
+    #[clap(long, value_name = "REGEX")]
+    metric_keep: Option<String>,
+    /// Pushes the metrics to this Pushgateway, along with serving them.
+    /// Basic auth credentials are taken from the URL.
+    #[clap(long, value_name = "URL")]
+    pushgateway_url: Option<String>,
+    #[clap(long, default_value = "15", value_name = "SECONDS")]
+    push_interval: u64,
+    #[clap(long, default_value = "lnd-exporter", value_name = "NAME")]
+    push_job: String,
+    /// Instance the pushes are grouped by, the hostname if not given.
+    #[clap(long, value_name = "NAME")]
+    push_instance: Option<String>,
+    /// Exports the metrics to this OTLP/gRPC endpoint, along with serving
+    /// them.
+    #[clap(long, value_name = "URL")]
+    otlp_endpoint: Option<String>,
+    #[clap(long, default_value = "60", value_name = "SECONDS")]
+    otlp_interval: u64,
+}
+
+/// Flags of the connection to lnd, also read from the `[lnd]` table of the
+/// config file.
+#[derive(Clone, Args, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct LndOpts {
+    #[clap(
+        long = "lnd-endpoint",
+        default_value = "https://localhost:10009",
+        value_name = "[NAME=]URL"
+    )]
+    endpoints: Vec<String>,
+    #[clap(long = "macaroon-path", value_name = "PATH")]
+    macaroon_paths: Vec<String>,
+    #[clap(long = "tls-cert-path", value_name = "PATH")]
+    tls_cert_paths: Vec<String>,
+    #[clap(long = "macaroon-scope", value_name = "NAME=PATH")]
+    macaroon_scopes: Vec<String>,
+    #[clap(long = "scraper-macaroon-scope", value_name = "SCRAPER=NAME")]
+    scraper_macaroon_scopes: Vec<String>,
+    #[clap(long = "lnd-connections", default_value = "2", value_name = "N")]
+    connections: usize,
+}
+
+impl Default for LndOpts {
+    fn default() -> Self {
+        config::flag_defaults()
+    }
+}
+
+/// Flags of the HTTP server, also read from the `[web]` table of the config
+/// file.
+#[derive(Clone, Args, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct WebOpts {
+    /// `HOST:PORT` or `unix:PATH` to serve at. Repeatable, TLS only applies to
+    /// TCP addresses. Ignored when systemd passes the sockets to serve at.
+    #[clap(
+        long = "exporter-listen-addr",
+        default_value = "127.0.0.1:29090",
+        value_name = "ADDR"
+    )]
+    #[serde(with = "config::strings")]
+    listen_addrs: Vec<ListenAddr>,
+    /// Permission bits of unix sockets, in octal.
+    #[clap(
+        long = "web-unix-socket-mode",
+        default_value = "660",
+        value_name = "MODE"
+    )]
+    unix_socket_mode: String,
+    /// Serves HTTPS with this PEM certificate chain, along with `--web-tls-key`.
+    #[clap(long = "web-tls-cert", value_name = "PATH")]
+    tls_cert: Option<String>,
+    #[clap(long = "web-tls-key", value_name = "PATH")]
+    tls_key: Option<String>,
+    /// Requires client certificates signed by this PEM CA bundle.
+    #[clap(long = "web-tls-client-ca", value_name = "PATH")]
+    tls_client_ca: Option<String>,
+    /// Requires the bearer token in this file on the telemetry path and /probe.
+    #[clap(long = "web-auth-token-file", value_name = "PATH")]
+    auth_token_file: Option<String>,
+    /// Path the metrics of the lnd nodes are served at.
+    #[clap(
+        long = "web-telemetry-path",
+        default_value = "/metrics",
+        value_name = "PATH"
+    )]
+    telemetry_path: String,
+    /// Only serves clients in this network, along with loopback unless
+    /// `--web-deny-loopback` is given. Repeatable.
+    #[clap(long = "web-allow-cidr", value_name = "CIDR")]
+    #[serde(with = "config::strings")]
+    allow_cidrs: Vec<Cidr>,
+    #[clap(long = "web-deny-loopback")]
+    deny_loopback: bool,
+    /// Lets browser pages from this origin read the metrics and health
+    /// endpoints, `*` for any. Repeatable.
+    #[clap(long = "web-cors-origin", value_name = "ORIGIN")]
+    #[serde(with = "config::strings")]
+    cors_origins: Vec<CorsOrigin>,
+    /// Time after which requests are aborted with 503.
+    #[clap(
+        long = "web-request-timeout-secs",
+        default_value = "30",
+        value_name = "SECONDS"
+    )]
+    request_timeout_secs: u64,
+    /// Connections past this many open ones are answered 503 and closed.
+    #[clap(long = "web-max-connections", default_value = "64", value_name = "N")]
+    max_connections: usize,
+}
+
+impl Default for WebOpts {
+    fn default() -> Self {
+        config::flag_defaults()
+    }
+}
+
+fn parse_key_value(s: &str) -> (&str, &str) {
+    s.split_once('=')
+        .unwrap_or_else(|| usage_error(format!("expected KEY=VALUE, got {:?}", s)))
+}
+
+/// Resolves the node labels with a GetInfo call, leaving them to the first
+/// scrape if lnd is not reachable yet.
+async fn resolve_node_labels(
+    lnd_clients: &ScopedLndClientSet,
+    timeout: Duration,
+) -> HashMap<String, String> {
+    let mut lnd_client = lnd_clients.get(ScraperName::GetInfo);
+
+    match tokio::time::timeout(timeout, lnd_client.get_info(lnrpc::GetInfoRequest {})).await {
+        Ok(Ok(res)) => collector::node_labels(res.get_ref()),
+        Ok(Err(e)) => {
+            log::warn!("Failed to resolve node labels: {}", e.message());
+            HashMap::new()
+        }
+        Err(_) => {
+            log::warn!("Timed out resolving node labels");
+            HashMap::new()
+        }
+    }
+}
+
+/// Label telling nodes apart when the exporter scrapes several of them.
+const NODE_NAME_LABEL: &str = "node";
+
+/// lnd node to scrape, from the i-th `--lnd-endpoint` along with the i-th
+/// `--macaroon-path` and `--tls-cert-path`, or the only one given.
+struct LndNode {
+    /// Value of the node label, the alias of the node if unnamed.
+    name: Option<String>,
+    endpoint: String,
+    macaroon_path: Option<String>,
     tls_cert_path: Option<String>,
-    #[clap(long, default_value = "https://localhost:10009")]
-    lnd_endpoint: String,
-    #[clap(long, default_value = "127.0.0.1:29090")]
-    exporter_listen_addr: SocketAddr,
+}
+
+fn lnd_nodes(opts: &Opts) -> Vec<LndNode> {
+    let endpoints = &opts.lnd.endpoints;
+    let node_option = |paths: &[String], flag: &str, i: usize| match paths.len() {
+        0 => None,
+        1 => Some(paths[0].clone()),
+        n if n == endpoints.len() => Some(paths[i].clone()),
+        _ => usage_error(format!(
+            "expected one --{} or one per --lnd-endpoint, got {}",
+            flag,
+            paths.len()
+        )),
+    };
+
+    endpoints
+        .iter()
+        .enumerate()
+        .map(|(i, endpoint)| {
+            // URLs have no '=' before their scheme, so NAME=URL is unambiguous
+            let (name, endpoint) = match endpoint.split_once('=') {
+                Some((name, url)) if !name.contains(':') => (Some(name.to_string()), url),
+                _ => (None, endpoint.as_str()),
+            };
+
+            LndNode {
+                name,
+                endpoint: endpoint.to_string(),
+                macaroon_path: node_option(&opts.lnd.macaroon_paths, "macaroon-path", i),
+                tls_cert_path: node_option(&opts.lnd.tls_cert_paths, "tls-cert-path", i),
+            }
+        })
+        .collect()
+}
+
+fn collector_options(
+    opts: &Opts,
+    enabled_scrapers: ScraperSet,
+    family_filter: Arc<FamilyFilter>,
+) -> CollectorOptions {
+    let bitcoind = enabled_scrapers.contains(ScraperName::Mempool).then(|| {
+        BitcoindClient::new(
+            opts.bitcoin_rpc_url.clone(),
+            opts.bitcoin_rpc_user.clone(),
+            opts.bitcoin_rpc_pass.clone(),
+        )
+    });
+
+    CollectorOptions {
+        enabled_scrapers,
+        scraper_timeout: Duration::from_secs(opts.scraper_timeout),
+        scraper_retries: opts.scraper_retries,
+        strict_permissions: opts.strict_permissions,
+        flap_threshold: opts.flap_threshold,
+        top_destinations: opts.top_destinations_n,
+        payment_distribution_window: opts.payment_distribution_window,
+        invoice_proofs: opts.enable_invoice_proof_metrics,
+        block_explorer: opts
+            .enable_sync_progress_metric
+            .then(|| BlockExplorer::new(opts.block_explorer_api_url.clone())),
+        bitcoind,
+        wait_unlock: if opts.no_wait_unlock {
+            None
+        } else {
+            Some(WALLET_STATE_POLL_INTERVAL)
+        },
+        scraper_permissions: HashMap::new(),
+        family_filter,
+        close_fee_estimate_confs: opts.close_fee_estimate_confs,
+        close_fee_estimate_refresh: Duration::from_secs(opts.close_fee_estimate_refresh_secs),
+        hub_pubkeys: opts.hub_pubkeys.clone(),
+        stuck_htlc_threshold: Duration::from_secs(opts.stuck_htlc_threshold_secs),
+        dust_relay_fee_rate: opts.dust_relay_fee_rate,
+    }
+}
+
+/// Loads the credentials of `--probe-module NAME=MACAROON_PATH[,TLS_CERT_PATH]`.
+async fn probe_modules(opts: &Opts) -> HashMap<String, ProbeModule> {
+    let mut modules = HashMap::new();
+
+    for module in opts.probe_modules.iter() {
+        let (name, paths) = parse_key_value(module);
+        let (macaroon_path, tls_cert_path) = match paths.split_once(',') {
+            Some((macaroon_path, tls_cert_path)) => (macaroon_path, Some(tls_cert_path)),
+            None => (paths, None),
+        };
+
+        let tls_cert = match tls_cert_path {
+            Some(tls_cert_path) => Some(read_file(tls_cert_path).await),
+            None => None,
+        };
+        modules.insert(
+            name.to_string(),
+            ProbeModule {
+                macaroon: read_file(macaroon_path).await,
+                tls_cert,
+            },
+        );
+        log::info!("Probe module {} loaded", name);
+    }
+
+    modules
+}
+
+/// Connects to the node, unless replaying, with the macaroons of its scrapers.
+async fn node_clients(opts: &Opts, node: &LndNode) -> ScopedLndClientSet {
+    let macaroon = if let Some(macaroon_path) = &node.macaroon_path {
+        let macaroon_bytes = read_file(macaroon_path).await;
+        log::info!("Macaroon loaded");
+
+        Some(macaroon_bytes)
+    } else {
+        None
+    };
+
+    let tls_cert = if let Some(tls_cert_path) = &node.tls_cert_path {
+        let tls_cert_bytes = read_file(tls_cert_path).await;
+        log::info!("TLS cert loaded");
+
+        Some(tls_cert_bytes)
+    } else {
+        None
+    };
+
+    let transports = if let Some(replay_dir) = &opts.replay {
+        let replayer = Arc::new(lnrpc::Replayer::load(replay_dir).expect("recorded lnd responses"));
+        log::info!("Replaying lnd responses from {}", replay_dir.display());
+
+        (0..opts.lnd.connections)
+            .map(|_| lnrpc::Transport::replay(replayer.clone()))
+            .collect()
+    } else {
+        let recorder = opts.record.as_ref().map(|record_dir| {
+            log::info!("Recording lnd responses to {}", record_dir.display());
+            Arc::new(lnrpc::Recorder::new(record_dir).expect("recording directory"))
+        });
+
+        let endpoint =
+            lnrpc::Endpoint::from_shared(node.endpoint.clone()).expect("valid endpoint address");
+        (0..opts.lnd.connections)
+            .map(|_| {
+                let transport = lnrpc::connect_lazy(tls_cert.as_ref(), endpoint.clone())
+                    .expect("lightning client");
+                match &recorder {
+                    Some(recorder) => transport.with_recorder(recorder.clone()),
+                    None => transport,
+                }
+            })
+            .collect()
+    };
+
+    let mut lnd_clients = ScopedLndClientSet::new(transports, macaroon.as_deref());
+
+    let mut macaroon_scopes = HashMap::new();
+    for scope in opts.lnd.macaroon_scopes.iter() {
+        let (name, path) = parse_key_value(scope);
+        let macaroon_bytes = read_file(path).await;
+        log::info!("Macaroon for scope {} loaded", name);
+
+        macaroon_scopes.insert(name, macaroon_bytes);
+    }
+
+    for scraper_scope in opts.lnd.scraper_macaroon_scopes.iter() {
+        let (scraper, name) = parse_key_value(scraper_scope);
+        let scraper = scraper
+            .parse::<ScraperName>()
+            .unwrap_or_else(|e| usage_error(e));
+
+        let macaroon = macaroon_scopes
+            .get(name)
+            .unwrap_or_else(|| usage_error(format!("unknown macaroon scope {:?}", name)));
+
+        lnd_clients.insert(scraper, macaroon);
+    }
+
+    lnd_clients
+}
+
+/// Builds the collector of the node, labeled with its name when the exporter
+/// scrapes several nodes. Returns it along with its metrics config, or an
+/// error when its macaroons lack permissions that are required.
+async fn node_collector(
+    opts: &Opts,
+    node: &LndNode,
+    options: &CollectorOptions,
+    metrics_config: &MetricsConfig,
+) -> Result<(LndCollector, MetricsConfig), String> {
+    let lnd_clients = node_clients(opts, node).await;
+
+    let scraper_timeout = Duration::from_secs(opts.scraper_timeout);
+    let node_labels = resolve_node_labels(&lnd_clients, scraper_timeout).await;
+    let mut metrics_config = metrics_config.clone();
+    if opts.lnd.endpoints.len() > 1 {
+        let name = node
+            .name
+            .clone()
+            .or_else(|| node_labels.get("node_alias").cloned())
+            .unwrap_or_else(|| node.endpoint.clone());
+        metrics_config
+            .const_labels
+            .insert(NODE_NAME_LABEL.to_string(), name);
+    }
+    let metrics_config = metrics_config.with_const_labels(node_labels);
+
+    let permissions =
+        check_permissions(&lnd_clients, &options.enabled_scrapers, scraper_timeout).await;
+    log::info!("Scraper permissions of {}: {}", node.endpoint, permissions);
+    if (opts.require_all_permissions || opts.strict_permissions) && permissions.has_missing() {
+        return Err(format!(
+            "Scraper macaroons of {} lack permissions",
+            node.endpoint
+        ));
+    }
+
+    let collector = LndCollector::new(
+        lnd_clients,
+        CollectorOptions {
+            scraper_permissions: permissions.permitted(),
+            ..options.clone()
+        },
+        metrics_config.clone(),
+    );
+    log::info!("Scraping LND node at {}", node.endpoint);
+
+    Ok((collector, metrics_config))
+}
+
+async fn read_file(path: &str) -> Vec<u8> {
+    let mut bytes = vec![];
+
+    tokio::fs::File::open(path)
+        .await
+        .unwrap_or_else(|e| panic!("failed to open {}: {}", path, e))
+        .read_to_end(&mut bytes)
+        .await
+        .expect("read all");
+
+    bytes
+}
+
+/// Scrapers named by the `collect[]` parameters of the query, like
+/// node_exporter takes them, if any.
+fn scraper_filter(query: Option<&str>) -> Result<Option<ScraperSet>, String> {
+    let names = form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .filter(|(key, _)| key == "collect[]")
+        .map(|(_, name)| name.parse::<ScraperName>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if names.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(names.into_iter().collect()))
+    }
+}
+
+/// Endpoints served besides the metrics, which the telemetry path must not
+/// shadow.
+const RESERVED_PATHS: &[&str] = &[
+    "/",
+    "/health",
+    "/healthz",
+    "/ready",
+    "/probe",
+    "/debug/status",
+];
+
+/// Settings of the HTTP endpoints.
+struct WebConfig {
+    telemetry_path: String,
+    auth: Option<TokenAuth>,
+    /// Redacted endpoints of the nodes, in the order of the collectors.
+    endpoints: Vec<String>,
+    enable_debug: bool,
+    /// Start of the process, which `/healthz` reports the uptime from.
+    started_at: std::time::Instant,
+    cors: Option<Cors>,
+}
+
+impl WebConfig {
+    /// Path the metrics are served at as a JSON document.
+    fn json_path(&self) -> String {
+        format!("{}.json", self.telemetry_path)
+    }
+
+    /// Whether browser pages of other origins may read the path.
+    fn allows_cors(&self, path: &str) -> bool {
+        path == self.telemetry_path
+            || path == self.json_path()
+            || matches!(path, "/health" | "/healthz" | "/ready")
+    }
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            telemetry_path: "/metrics".to_string(),
+            auth: None,
+            endpoints: vec![],
+            enable_debug: false,
+            started_at: std::time::Instant::now(),
+            cors: None,
+        }
+    }
 }
 
 async fn handler(
     req: hyper::Request<hyper::Body>,
+    gather_cache: Arc<GatherCache>,
+    rate_limiter: Arc<RateLimiter>,
+    prober: Arc<Prober>,
+    family_filter: Arc<FamilyFilter>,
+    collectors: Arc<Vec<Arc<LndCollector>>>,
+    web: Arc<WebConfig>,
+) -> Result<hyper::Response<hyper::Body>, hyper::http::Error> {
+    let cors = match web.cors.as_ref() {
+        Some(cors) if web.allows_cors(req.uri().path()) => cors,
+        _ => {
+            return route(
+                req,
+                gather_cache,
+                rate_limiter,
+                prober,
+                family_filter,
+                collectors,
+                web.clone(),
+            )
+            .await
+        }
+    };
+
+    // Preflights carry no bearer token, so they are answered before auth
+    if req.method() == hyper::http::Method::OPTIONS {
+        return Ok(cors.preflight(req.headers()));
+    }
+
+    let req_headers = req.headers().clone();
+    let mut res = route(
+        req,
+        gather_cache,
+        rate_limiter,
+        prober,
+        family_filter,
+        collectors,
+        web.clone(),
+    )
+    .await?;
+    cors.apply(&req_headers, res.headers_mut());
+
+    Ok(res)
+}
+
+async fn route(
+    req: hyper::Request<hyper::Body>,
+    gather_cache: Arc<GatherCache>,
+    rate_limiter: Arc<RateLimiter>,
+    prober: Arc<Prober>,
+    family_filter: Arc<FamilyFilter>,
+    collectors: Arc<Vec<Arc<LndCollector>>>,
+    web: Arc<WebConfig>,
 ) -> Result<hyper::Response<hyper::Body>, hyper::http::Error> {
+    // Health and readiness stay open to orchestrator probes
+    let path = req.uri().path();
+    let json_path = web.json_path();
+    let protected = path == web.telemetry_path
+        || path == json_path
+        || path == "/probe"
+        || path == "/debug/status";
+    if let Some(auth) = web.auth.as_ref().filter(|_| protected) {
+        if !auth.authorize(req.headers()) {
+            return hyper::http::response::Builder::default()
+                .status(401)
+                .header(hyper::header::WWW_AUTHENTICATE, auth::WWW_AUTHENTICATE)
+                .body(hyper::Body::empty());
+        }
+    }
+
     match (req.method(), req.uri().path()) {
+        (&hyper::http::Method::GET, "/") => hyper::http::response::Builder::default()
+            .status(200)
+            .header(hyper::header::CONTENT_TYPE, "text/html; charset=utf-8")
+            .body(landing::render(&web.telemetry_path, &web.endpoints, &collectors).into()),
+
+        // Liveness only, lnd being down is for /ready to tell
         (&hyper::http::Method::GET, "/health") => Ok(hyper::http::response::Builder::new()
             .status(200)
             .body(hyper::Body::empty())?),
 
-        (&hyper::http::Method::GET, "/metrics") => {
-            let ms = tokio::task::spawn_blocking(|| prometheus::gather())
-                .await
-                .expect("gather");
-            let mut buf = vec![];
+        (&hyper::http::Method::GET, "/healthz") => {
+            match health::report(&collectors, web.started_at).await {
+                (healthy, Ok(body)) => hyper::http::response::Builder::default()
+                    .status(if healthy { 200 } else { 503 })
+                    .header(hyper::header::CONTENT_TYPE, "application/json")
+                    .body(body.into()),
+                (_, Err(e)) => {
+                    log::error!("Failed to encode health: {}", e);
+                    hyper::http::response::Builder::default()
+                        .status(500)
+                        .body(hyper::Body::empty())
+                }
+            }
+        }
+
+        (&hyper::http::Method::GET, "/ready") => {
+            let reasons = futures_util::future::join_all(
+                collectors.iter().map(|collector| collector.ready()),
+            )
+            .await
+            .into_iter()
+            .filter_map(Result::err)
+            .collect::<Vec<_>>();
 
-            match prometheus::TextEncoder::new().encode(&ms, &mut buf) {
-                Ok(_) => Ok(hyper::http::response::Builder::default()
+            if reasons.is_empty() {
+                Ok(hyper::http::response::Builder::new()
                     .status(200)
-                    .body(hyper::Body::from(buf))?),
-                Err(e) => {
-                    eprintln!("Failed to encode metrics: {}", e);
+                    .body(hyper::Body::empty())?)
+            } else {
+                hyper::http::response::Builder::new()
+                    .status(503)
+                    .body(reasons.join("\n").into())
+            }
+        }
 
-                    Ok(hyper::http::response::Builder::default()
+        (&hyper::http::Method::GET, "/debug/status") if web.enable_debug => {
+            match debug::status(&collectors, &web.endpoints) {
+                Ok(body) => hyper::http::response::Builder::default()
+                    .status(200)
+                    .header(hyper::header::CONTENT_TYPE, "application/json")
+                    .body(body.into()),
+                Err(e) => {
+                    log::error!("Failed to encode debug status: {}", e);
+                    hyper::http::response::Builder::default()
                         .status(500)
-                        .body("Failed to encode metrics".into())?)
+                        .body(hyper::Body::empty())
+                }
+            }
+        }
+
+        // Probes scrape a target each, so they are not rate limited
+        (&hyper::http::Method::GET, "/probe") => {
+            let params = form_urlencoded::parse(req.uri().query().unwrap_or_default().as_bytes())
+                .into_owned()
+                .collect::<HashMap<_, _>>();
+            let target = match params.get("target") {
+                Some(target) => target,
+                None => {
+                    return hyper::http::response::Builder::default()
+                        .status(400)
+                        .body("target parameter is missing".into());
+                }
+            };
+            let module = params.get("module").map(String::as_str).unwrap_or_default();
+
+            let mut ms = prober.probe(target, module).await;
+            family_filter.retain(&mut ms);
+            encode_response(
+                &ms,
+                encoder::Format::negotiate(req.headers()),
+                encoder::accepts_gzip(req.headers()),
+            )
+        }
+
+        (&hyper::http::Method::GET, path) if path == web.telemetry_path || path == json_path => {
+            let format = if path == json_path {
+                encoder::Format::JsonDocument
+            } else {
+                encoder::Format::negotiate(req.headers())
+            };
+
+            let filter = match scraper_filter(req.uri().query()) {
+                Ok(filter) => filter,
+                Err(e) => {
+                    return hyper::http::response::Builder::default()
+                        .status(400)
+                        .body(e.into());
+                }
+            };
+
+            let _permit = match rate_limiter.try_acquire() {
+                Ok(permit) => permit,
+                Err(retry_after) => {
+                    return hyper::http::response::Builder::default()
+                        .status(429)
+                        .header(
+                            hyper::header::RETRY_AFTER,
+                            rate_limit::retry_after_secs(retry_after),
+                        )
+                        .body(hyper::Body::empty());
                 }
+            };
+
+            let mut ms = gather_cache.gather(filter).await;
+            ms.extend(rate_limiter.collect());
+            if let Some(auth) = &web.auth {
+                ms.extend(auth.collect());
             }
+            family_filter.retain(&mut ms);
+
+            encode_response(&ms, format, encoder::accepts_gzip(req.headers()))
         }
 
         _ => Ok(hyper::http::response::Builder::default()
@@ -55,98 +865,691 @@ async fn handler(
     }
 }
 
+fn encode_response(
+    ms: &[prometheus::proto::MetricFamily],
+    format: encoder::Format,
+    gzip: bool,
+) -> Result<hyper::Response<hyper::Body>, hyper::http::Error> {
+    match encoder::encode(ms, format) {
+        Ok((buf, content_type)) => {
+            let res = hyper::http::response::Builder::default()
+                .status(200)
+                .header(hyper::header::CONTENT_TYPE, content_type)
+                .header(hyper::header::VARY, "Accept-Encoding");
+
+            if !gzip || buf.len() < encoder::MIN_GZIP_SIZE {
+                return res.body(hyper::Body::from(buf));
+            }
+            match encoder::gzip(&buf) {
+                Ok(compressed) => res
+                    .header(hyper::header::CONTENT_ENCODING, "gzip")
+                    .body(hyper::Body::from(compressed)),
+                Err(e) => {
+                    log::warn!(
+                        "Failed to compress metrics, sending them uncompressed: {}",
+                        e
+                    );
+                    res.body(hyper::Body::from(buf))
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to encode metrics: {}", e);
+
+            hyper::http::response::Builder::default()
+                .status(500)
+                .body("Failed to encode metrics".into())
+        }
+    }
+}
+
+/// TLS config of the exporter listener, if `--web-tls-cert` is set. Exits the
+/// process when the files can't be loaded.
+fn web_tls_config(opts: &Opts) -> Option<Arc<tokio_rustls::rustls::ServerConfig>> {
+    let (cert_path, key_path) = match (&opts.web.tls_cert, &opts.web.tls_key) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (None, None) if opts.web.tls_client_ca.is_none() => return None,
+        (None, None) => {
+            usage_error("--web-tls-client-ca requires --web-tls-cert and --web-tls-key")
+        }
+        _ => usage_error("--web-tls-cert and --web-tls-key must be set together"),
+    };
+
+    match tls::server_config(cert_path, key_path, opts.web.tls_client_ca.as_deref()) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            log::error!("Failed to load the exporter TLS config: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Resolves on SIGINT or, on unix, SIGTERM, which is how systemd and
+/// Kubernetes stop the exporter.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        res = tokio::signal::ctrl_c() => res.expect("SIGINT handler"),
+        _ = terminate => {}
+    }
+}
+
+/// Drops the CPU, memory and file descriptor metrics of the exporter process,
+/// which the default registry comes with on Linux.
+#[cfg(target_os = "linux")]
+fn unregister_process_metrics() {
+    let collector = prometheus::process_collector::ProcessCollector::for_self();
+    prometheus::unregister(Box::new(collector)).expect("unregistered process collector");
+}
+
+/// Process metrics are only collected on Linux.
+#[cfg(not(target_os = "linux"))]
+fn unregister_process_metrics() {}
+
+/// Exports the memory, CPU time and open files of the exporter on every
+/// platform, under the metric namespace.
+#[cfg(feature = "process-metrics")]
+fn register_exporter_process_metrics(metrics_config: &MetricsConfig) {
+    prometheus::register(Box::new(process_metrics::ProcessMetricsCollector::new(
+        metrics_config,
+    )))
+    .expect("registered process metrics collector");
+}
+
+#[cfg(not(feature = "process-metrics"))]
+fn register_exporter_process_metrics(_: &MetricsConfig) {}
+
+/// Exits with the message, like clap on flags it can't parse.
+fn usage_error(message: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(2);
+}
+
+/// Parses the flags, taking the ones not on the command line from the config
+/// file if given.
+fn parse_opts() -> Opts {
+    let matches = Opts::command().get_matches();
+    let mut opts = Opts::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    if let Some(path) = opts.config.clone() {
+        let file = config::load(&path, opts.strict_config).unwrap_or_else(|e| {
+            usage_error(format!("invalid config file {}: {}", path.display(), e))
+        });
+        config::apply(&mut opts, file, &matches).unwrap_or_else(|e| {
+            usage_error(format!("invalid config file {}: {}", path.display(), e))
+        });
+    }
+
+    if opts.print_config {
+        print!("{}", config::effective(&opts));
+        std::process::exit(0);
+    }
+
+    opts
+}
+
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
+    let started_at = std::time::Instant::now();
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
-    let opts = Opts::parse();
+    let opts = parse_opts();
 
-    let macaroon = if let Some(macaroon_path) = opts.macaroon_path {
-        let mut macaroon_bytes = vec![];
+    if opts.lnd.connections == 0 {
+        usage_error("--lnd-connections must be at least 1");
+    }
+    if opts.probe_max_targets == 0 {
+        usage_error("--probe-max-targets must be at least 1");
+    }
+    if opts.web.request_timeout_secs == 0 {
+        usage_error("--web-request-timeout-secs must be at least 1");
+    }
+    if opts.web.max_connections == 0 {
+        usage_error("--web-max-connections must be at least 1");
+    }
+    if opts.payment_distribution_window == 0 {
+        usage_error("--payment-distribution-window must be at least 1");
+    }
+    if opts.push_interval == 0 {
+        usage_error("--push-interval must be at least 1");
+    }
+    if opts.otlp_interval == 0 {
+        usage_error("--otlp-interval must be at least 1");
+    }
+    if opts.metrics_max_concurrent_scrapes == 0 {
+        usage_error("--metrics-max-concurrent-scrapes must be at least 1");
+    }
+    if !opts.web.telemetry_path.starts_with('/') {
+        usage_error("--web-telemetry-path must start with /");
+    }
+    if RESERVED_PATHS.contains(&opts.web.telemetry_path.as_str()) {
+        usage_error(format!(
+            "--web-telemetry-path {} is reserved",
+            opts.web.telemetry_path
+        ));
+    }
 
-        tokio::fs::File::open(macaroon_path)
-            .await
-            .expect("macaroon file opened")
-            .read_to_end(&mut macaroon_bytes)
-            .await
-            .expect("read all");
+    // Loaded before connecting to lnd so that bad files fail startup early
+    let web_tls_config = web_tls_config(&opts);
 
-        log::info!("Macaroon loaded");
+    let nodes = lnd_nodes(&opts);
+    let multi_node = nodes.len() > 1;
+    if multi_node
+        && (opts.record.is_some()
+            || opts.replay.is_some()
+            || !opts.lnd.scraper_macaroon_scopes.is_empty())
+    {
+        usage_error("--record, --replay and --scraper-macaroon-scope take a single lnd node");
+    }
 
-        Some(macaroon_bytes)
+    let mut enabled_scrapers = ScraperSet::default();
+    for scraper in opts.enabled_collectors.iter() {
+        enabled_scrapers.insert(*scraper);
+    }
+    if opts.enable_backup_metrics {
+        enabled_scrapers.insert(ScraperName::Backup);
+    }
+    if opts.enable_watchtower_metrics {
+        enabled_scrapers.insert(ScraperName::Watchtower);
+    }
+    if opts.enable_autopilot_metrics {
+        enabled_scrapers.insert(ScraperName::Autopilot);
+    }
+    if opts.enable_close_fee_estimates {
+        enabled_scrapers.insert(ScraperName::CloseFees);
+    }
+    if opts.enable_graph_distance_metrics {
+        enabled_scrapers.insert(ScraperName::HubDistances);
+    }
+    if opts.enable_mempool_metrics {
+        enabled_scrapers.insert(ScraperName::Mempool);
+    }
+    for scraper in opts.disabled_collectors.iter() {
+        enabled_scrapers.remove(*scraper);
+    }
+    if enabled_scrapers.contains(ScraperName::HubDistances) && opts.hub_pubkeys.is_empty() {
+        usage_error("the hubdistances collector needs --hub-pubkeys");
+    }
+    for pubkey in opts.hub_pubkeys.iter() {
+        if pubkey.len() != 66 || hex::decode(pubkey).is_err() {
+            usage_error(format!("invalid hub pubkey {:?}", pubkey));
+        }
+    }
+    log::info!("Enabled scrapers: {}", enabled_scrapers);
+
+    let family_filter = Arc::new(
+        FamilyFilter::new(opts.metric_drop.as_deref(), opts.metric_keep.as_deref())
+            .unwrap_or_else(|e| usage_error(format!("invalid metric filter: {}", e))),
+    );
+    let options = collector_options(&opts, enabled_scrapers, family_filter.clone());
+
+    let mut metrics_config = MetricsConfig::new(&opts.metric_namespace);
+    for const_label in opts.const_labels.iter() {
+        let (name, value) = parse_key_value(const_label);
+        if NODE_LABELS.contains(&name) || (multi_node && name == NODE_NAME_LABEL) {
+            usage_error(format!("constant label {:?} is set from lnd", name));
+        }
+
+        metrics_config
+            .const_labels
+            .insert(name.to_string(), value.to_string());
+    }
+
+    let collectors = futures_util::future::join_all(
+        nodes
+            .iter()
+            .map(|node| node_collector(&opts, node, &options, &metrics_config)),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap_or_else(|e| {
+        log::error!("{}, exiting", e);
+        std::process::exit(1);
+    });
+
+    // Probed nodes are labeled from their own GetInfo
+    let prober = Arc::new(Prober::new(
+        probe_modules(&opts).await,
+        options,
+        metrics_config.clone(),
+        opts.lnd.connections,
+        opts.probe_max_targets,
+    ));
+
+    let (collectors, node_configs): (Vec<_>, Vec<_>) = collectors
+        .into_iter()
+        .map(|(collector, node_config)| (Arc::new(collector), node_config))
+        .unzip();
+    // Metrics of the exporter as a whole carry the node labels of a single
+    // node, as they always did, but none of the nodes when there are several
+    let metrics_config = if multi_node {
+        prometheus::register(Box::new(MultiNodeCollector::new(collectors.clone())))
+            .expect("registered collector");
+
+        metrics_config
     } else {
-        None
+        prometheus::register(Box::new(SharedCollector(collectors[0].clone())))
+            .expect("registered collector");
+
+        node_configs.into_iter().next().expect("one lnd node")
     };
+    let collectors = Arc::new(collectors);
+    if opts.runtime_metrics {
+        prometheus::register(Box::new(RuntimeCollector::new(
+            tokio::runtime::Handle::current(),
+            &metrics_config,
+        )))
+        .expect("registered runtime collector");
+    }
+    if opts.no_process_metrics {
+        unregister_process_metrics();
+    } else {
+        register_exporter_process_metrics(&metrics_config);
+    }
 
-    let tls_cert = if let Some(tls_cert_path) = opts.tls_cert_path {
-        let mut tls_cert_bytes = vec![];
+    let gather_cache = Arc::new(GatherCache::new(
+        prometheus::default_registry().clone(),
+        Duration::from_secs(opts.min_scrape_interval),
+        &metrics_config,
+    ));
 
-        tokio::fs::File::open(tls_cert_path)
-            .await
-            .expect("cert file opened")
-            .read_to_end(&mut tls_cert_bytes)
-            .await
-            .expect("read all");
-        log::info!("TLS cert loaded");
+    if opts.once {
+        match once::run(
+            &gather_cache,
+            &family_filter,
+            &collectors,
+            opts.output.as_deref(),
+        )
+        .await
+        {
+            Ok(true) => std::process::exit(0),
+            Ok(false) if opts.allow_partial => {
+                log::warn!(
+                    "Some scrapers failed, exiting with success as partial results are allowed"
+                );
+                std::process::exit(0);
+            }
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                log::error!("Failed to write the metrics: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-        Some(tls_cert_bytes)
-    } else {
-        None
+    let rate_limiter = Arc::new(RateLimiter::new(
+        opts.metrics_max_concurrent_scrapes,
+        Duration::from_secs(opts.metrics_min_interval_secs),
+        &metrics_config,
+    ));
+
+    if let Some(pushgateway_url) = &opts.pushgateway_url {
+        let instance = opts
+            .push_instance
+            .clone()
+            .or_else(push::hostname)
+            .unwrap_or_else(|| {
+                usage_error("--pushgateway-url needs --push-instance, no hostname found")
+            });
+        let pusher = Pusher::new(pushgateway_url, &opts.push_job, &instance, &metrics_config)
+            .unwrap_or_else(|e| usage_error(format!("invalid --pushgateway-url: {}", e)));
+        prometheus::register(Box::new(pusher.failures_total())).expect("registered push metrics");
+        log::info!(
+            "Pushing metrics to the Pushgateway as instance {}",
+            instance
+        );
+
+        // Runs along with the listeners, whose requests share the snapshots
+        tokio::spawn(pusher.run(
+            gather_cache.clone(),
+            family_filter.clone(),
+            Duration::from_secs(opts.push_interval),
+        ));
+    }
+
+    if let Some(otlp_endpoint) = &opts.otlp_endpoint {
+        let exporter = OtlpExporter::new(otlp_endpoint, &metrics_config)
+            .unwrap_or_else(|e| usage_error(format!("invalid --otlp-endpoint: {}", e)));
+        prometheus::register(Box::new(exporter.failures_total())).expect("registered OTLP metrics");
+        log::info!("Exporting metrics over OTLP to {}", otlp_endpoint);
+
+        tokio::spawn(exporter.run(
+            gather_cache.clone(),
+            family_filter.clone(),
+            Duration::from_secs(opts.otlp_interval),
+        ));
+    }
+
+    let telemetry_path = &opts.web.telemetry_path;
+
+    let auth = match &opts.web.auth_token_file {
+        Some(path) => {
+            let token = String::from_utf8(read_file(path).await)
+                .unwrap_or_else(|_| panic!("{} is not valid UTF-8", path));
+            if token.trim().is_empty() {
+                panic!("{} holds no token", path);
+            }
+
+            Some(TokenAuth::new(token.trim(), &metrics_config))
+        }
+        None => None,
     };
+    let web = Arc::new(WebConfig {
+        telemetry_path: telemetry_path.clone(),
+        auth,
+        // Redacted, as the landing page is served without authentication
+        endpoints: nodes
+            .iter()
+            .map(|node| debug::redact_endpoint(&node.endpoint))
+            .collect(),
+        enable_debug: opts.enable_debug_endpoints,
+        started_at,
+        cors: if opts.web.cors_origins.is_empty() {
+            None
+        } else {
+            Some(Cors::new(opts.web.cors_origins.clone()))
+        },
+    });
 
-    let lnd_client = lnrpc::new(
-        tls_cert,
-        macaroon,
-        lnrpc::Endpoint::from_shared(opts.lnd_endpoint.clone()).expect("valid endpoint address"),
-    )
-    .await
-    .expect("lightning client");
+    // Registered by default, so requests are counted even when lnd is down
+    let http_metrics = HttpMetrics::new(
+        vec![telemetry_path.clone(), web.json_path()]
+            .into_iter()
+            .chain(RESERVED_PATHS.iter().map(|path| path.to_string()))
+            .collect(),
+        &metrics_config,
+    );
+    prometheus::register(Box::new(http_metrics.clone())).expect("registered http metrics");
+    let http_metrics = Arc::new(http_metrics);
 
-    let collector = LndCollector::new(lnd_client);
+    let allowlist = if opts.web.allow_cidrs.is_empty() {
+        None
+    } else {
+        Some(Arc::new(SourceAllowlist::new(
+            opts.web.allow_cidrs.clone(),
+            !opts.web.deny_loopback,
+        )))
+    };
 
-    prometheus::register(Box::new(collector)).expect("registered collector");
+    let unix_socket_mode = u32::from_str_radix(&opts.web.unix_socket_mode, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+        .unwrap_or_else(|| {
+            usage_error(format!(
+                "--web-unix-socket-mode {} is not an octal mode",
+                opts.web.unix_socket_mode
+            ))
+        });
+    let request_timeout = Duration::from_secs(opts.web.request_timeout_secs);
+    let connection_limit = Arc::new(tokio::sync::Semaphore::new(opts.web.max_connections));
 
-    log::info!("Connected to LND node at {}", opts.lnd_endpoint);
+    // Checked until every node is ready before telling systemd about it
+    let wait_lnd = opts.systemd_notify_wait_lnd.then(|| collectors.clone());
 
-    let server = hyper::Server::bind(&opts.exporter_listen_addr).serve(
-        hyper::service::make_service_fn(move |sock: &hyper::server::conn::AddrStream| {
-            let remote_addr = sock.remote_addr();
+    // Unix socket clients are only restricted by the permissions of the socket
+    let service = move |remote_addr: String, unix_socket: bool| {
+        // Connections whose address is unknown are only served without allowlist
+        let allowed = match &allowlist {
+            Some(_) if unix_socket => true,
+            Some(allowlist) => match remote_addr.parse::<SocketAddr>() {
+                Ok(addr) => allowlist.allows(addr.ip()),
+                Err(_) => false,
+            },
+            None => true,
+        };
+        // Held as long as the connection is open
+        let connection_permit = connection_limit.clone().try_acquire_owned().ok();
+        if allowed && connection_permit.is_none() {
+            http_metrics.connection_rejected();
+            log::warn!("Rejected connection from {}, too many open", remote_addr);
+        }
+        let gather_cache = gather_cache.clone();
+        let rate_limiter = rate_limiter.clone();
+        let prober = prober.clone();
+        let family_filter = family_filter.clone();
+        let collectors = collectors.clone();
+        let web = web.clone();
+        let http_metrics = http_metrics.clone();
+
+        hyper::service::service_fn(move |req| {
+            let start_time = std::time::Instant::now();
+            let over_limit = connection_permit.is_none();
+            let remote_addr = remote_addr.clone();
+            let gather_cache = gather_cache.clone();
+            let rate_limiter = rate_limiter.clone();
+            let prober = prober.clone();
+            let family_filter = family_filter.clone();
+            let collectors = collectors.clone();
+            let web = web.clone();
+            let http_metrics = http_metrics.clone();
 
             async move {
-                Ok::<_, hyper::http::Error>(hyper::service::service_fn(move |req| {
-                    let start_time = std::time::Instant::now();
-
-                    async move {
-                        let req_path = req.uri().path().to_string();
-                        let req_method = req.method().to_string();
-
-                        let res = handler(req).await;
-
-                        match &res {
-                            Ok(res) => {
-                                log::info!(
-                                    "{} {} {} {} {}",
-                                    req_method,
-                                    req_path,
-                                    remote_addr,
-                                    res.status(),
-                                    start_time.elapsed().as_secs_f64(),
-                                );
-                            }
-                            Err(e) => {
-                                log::error!("Failed handling request: {:?}", e);
-                            }
-                        }
-
-                        res
+                if !allowed {
+                    // Scanners would flood the log at higher levels
+                    log::debug!("Rejected request from {}", remote_addr);
+                    return hyper::http::response::Builder::default()
+                        .status(403)
+                        .body(hyper::Body::empty());
+                }
+                if over_limit {
+                    return hyper::http::response::Builder::default()
+                        .status(503)
+                        .header(hyper::header::CONNECTION, "close")
+                        .body("Too many connections".into());
+                }
+
+                let req_path = req.uri().path().to_string();
+                let req_method = req.method().to_string();
+
+                let in_flight = http_metrics.in_flight();
+                let res = tokio::time::timeout(
+                    request_timeout,
+                    handler(
+                        req,
+                        gather_cache,
+                        rate_limiter,
+                        prober,
+                        family_filter,
+                        collectors,
+                        web,
+                    ),
+                )
+                .await
+                .unwrap_or_else(|_| {
+                    http_metrics.request_timed_out();
+                    log::warn!(
+                        "Aborted {} {} from {} after {:?}",
+                        req_method,
+                        req_path,
+                        remote_addr,
+                        request_timeout
+                    );
+                    hyper::http::response::Builder::default()
+                        .status(503)
+                        .body("Request timed out".into())
+                });
+                drop(in_flight);
+
+                match &res {
+                    Ok(res) => {
+                        http_metrics.observe(
+                            &req_path,
+                            &req_method,
+                            res.status().as_u16(),
+                            start_time.elapsed(),
+                        );
+
+                        // Size of the body as sent, i.e. after compression
+                        let body_size = hyper::body::HttpBody::size_hint(res.body())
+                            .exact()
+                            .map_or("-".to_string(), |size| size.to_string());
+                        log::info!(
+                            "{} {} {} {} {} {}",
+                            req_method,
+                            req_path,
+                            remote_addr,
+                            res.status(),
+                            body_size,
+                            start_time.elapsed().as_secs_f64(),
+                        );
                     }
-                }))
+                    Err(e) => {
+                        log::error!("Failed handling request: {:?}", e);
+                    }
+                }
+
+                res
             }
-        }),
-    );
-    log::info!("Exporter listening at {:?}", opts.exporter_listen_addr);
+        })
+    };
+
+    let (draining_tx, draining_rx) = tokio::sync::oneshot::channel();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        log::info!("Shutting down, waiting for in-flight requests");
+        systemd::notify("STOPPING=1");
+        let _ = shutdown_tx.send(true);
+        let _ = draining_tx.send(());
+    });
+    // Resolves once shutdown starts, for each of the servers
+    let shutdown = move || {
+        let mut shutdown_rx = shutdown_rx.clone();
+        async move {
+            while !*shutdown_rx.borrow() {
+                if shutdown_rx.changed().await.is_err() {
+                    break;
+                }
+            }
+        }
+    };
+
+    let listeners = match systemd::listen_fds() {
+        Ok(listeners) if !listeners.is_empty() => {
+            log::info!("Serving at {} sockets passed by systemd", listeners.len());
+            listeners
+        }
+        Ok(_) => opts
+            .web
+            .listen_addrs
+            .iter()
+            .map(|addr| {
+                addr.bind(unix_socket_mode)
+                    .unwrap_or_else(|e| panic!("failed to bind {}: {}", addr, e))
+            })
+            .collect(),
+        Err(e) => panic!("invalid sockets passed by systemd: {}", e),
+    };
+
+    let mut servers: Vec<std::pin::Pin<Box<dyn std::future::Future<Output = hyper::Result<()>>>>> =
+        vec![];
+    for listener in listeners {
+        let service = service.clone();
+
+        match (listener, &web_tls_config) {
+            (Listener::Tcp(listener), Some(tls_config)) => {
+                let addr = listener.local_addr().expect("bound address");
+                let listener = listener
+                    .set_nonblocking(true)
+                    .and_then(|()| tokio::net::TcpListener::from_std(listener))
+                    .unwrap_or_else(|e| panic!("failed to listen at {}: {}", addr, e));
+                log::info!("Exporter listening at {:?} over TLS", addr);
 
-    if let Err(e) = server.await {
-        eprintln!("Server error: {}", e);
+                servers.push(Box::pin(
+                    hyper::Server::builder(tls::TlsIncoming::new(listener, tls_config.clone()))
+                        .serve(hyper::service::make_service_fn(
+                            move |conn: &tls::TlsStream| {
+                                let service = service(tls::remote_addr(conn), false);
+                                async move { Ok::<_, hyper::http::Error>(service) }
+                            },
+                        ))
+                        .with_graceful_shutdown(shutdown()),
+                ));
+            }
+            (Listener::Tcp(listener), None) => {
+                let addr = listener.local_addr().expect("bound address");
+                let server = hyper::Server::from_tcp(listener)
+                    .unwrap_or_else(|e| panic!("failed to listen at {}: {}", addr, e))
+                    .serve(hyper::service::make_service_fn(
+                        move |sock: &hyper::server::conn::AddrStream| {
+                            let service = service(sock.remote_addr().to_string(), false);
+                            async move { Ok::<_, hyper::http::Error>(service) }
+                        },
+                    ));
+                log::info!("Exporter listening at {:?}", addr);
+
+                servers.push(Box::pin(server.with_graceful_shutdown(shutdown())));
+            }
+            #[cfg(unix)]
+            (Listener::Unix(incoming), _) => {
+                let remote_addr = incoming.remote_addr();
+                log::info!("Exporter listening at {}", remote_addr);
+
+                servers.push(Box::pin(
+                    hyper::Server::builder(incoming)
+                        .serve(hyper::service::make_service_fn(
+                            move |_: &tokio::net::UnixStream| {
+                                let service = service(remote_addr.clone(), true);
+                                async move { Ok::<_, hyper::http::Error>(service) }
+                            },
+                        ))
+                        .with_graceful_shutdown(shutdown()),
+                ));
+            }
+        }
     }
+
+    // Every socket is listening by now
+    tokio::spawn(async move {
+        if let Some(collectors) = wait_lnd {
+            while !futures_util::future::join_all(collectors.iter().map(|c| c.ready()))
+                .await
+                .iter()
+                .all(Result::is_ok)
+            {
+                tokio::time::sleep(SYSTEMD_READY_POLL_INTERVAL).await;
+            }
+        }
+        systemd::notify("READY=1");
+    });
+    if let Some(interval) = systemd::watchdog_interval() {
+        tokio::spawn(systemd::watchdog(interval));
+    }
+
+    let server = futures_util::future::try_join_all(servers);
+
+    tokio::select! {
+        res = server => {
+            if let Err(e) = res {
+                eprintln!("Server error: {}", e);
+                return;
+            }
+        }
+        _ = async {
+            match draining_rx.await {
+                Ok(()) => tokio::time::sleep(SHUTDOWN_DRAIN_TIMEOUT).await,
+                Err(_) => std::future::pending().await,
+            }
+        } => {
+            log::warn!(
+                "In-flight requests still running after {:?}, exiting",
+                SHUTDOWN_DRAIN_TIMEOUT
+            );
+            // Blocking gathers would hold up the runtime shutdown otherwise
+            std::process::exit(0);
+        }
+    }
+
+    // Dropping the runtime on return cancels the background tasks
+    log::info!("Shutdown complete");
 }
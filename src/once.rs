@@ -0,0 +1,83 @@
+use std::{io::Write, path::Path, sync::Arc};
+
+use crate::cache::GatherCache;
+use crate::collector::{FamilyFilter, LndCollector};
+use crate::encoder;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Scrapes the nodes once through the same pipeline as the telemetry path and
+/// writes the metrics in the text format to `output`, or stdout if not given.
+/// Returns whether lnd answered and every scraper succeeded.
+pub async fn run(
+    gather_cache: &GatherCache,
+    family_filter: &FamilyFilter,
+    collectors: &[Arc<LndCollector>],
+    output: Option<&Path>,
+) -> Result<bool, Error> {
+    let mut ms = gather_cache.gather(None).await;
+    family_filter.retain(&mut ms);
+    let (buf, _) = encoder::encode(&ms, encoder::Format::Text)?;
+
+    match output {
+        Some(path) => write_atomically(path, &buf)?,
+        None => {
+            let mut stdout = std::io::stdout();
+            stdout.write_all(&buf)?;
+            stdout.flush()?;
+        }
+    }
+
+    let mut succeeded = true;
+    for collector in collectors.iter() {
+        let status = collector.debug_status();
+        if !status.lnd_up {
+            log::error!("lnd was not up");
+            succeeded = false;
+        }
+        for (scraper, failure) in status.scrape_failures.iter() {
+            log::error!("{} scraper failed: {}", scraper, failure.error);
+            succeeded = false;
+        }
+    }
+
+    Ok(succeeded)
+}
+
+/// Writes the file through a temporary one next to it, so readers like the
+/// node_exporter textfile collector never see it half written.
+fn write_atomically(path: &Path, buf: &[u8]) -> std::io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}.tmp", std::process::id()));
+
+    let res = std::fs::File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(buf)?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&tmp_path, path));
+    if res.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_replaced_whole() {
+        let path = std::env::temp_dir().join(format!("lnd-exporter-{}.prom", std::process::id()));
+        std::fs::write(&path, "lnd_up 0\n").expect("previous file");
+
+        write_atomically(&path, b"lnd_up 1\n").expect("written");
+
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("written file"),
+            "lnd_up 1\n"
+        );
+        std::fs::remove_file(&path).expect("removed file");
+    }
+}
@@ -0,0 +1,402 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use prometheus::proto::{MetricFamily, MetricType};
+
+use crate::cache::GatherCache;
+use crate::collector::{FamilyFilter, MetricsConfig};
+
+#[allow(dead_code, clippy::all)]
+mod proto {
+    tonic::include_proto!("opentelemetry.proto.collector.metrics.v1");
+}
+
+use proto::metrics_service_client::MetricsServiceClient;
+
+/// Upper bound on an export, so a hung collector doesn't pile up exports.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Wait before retrying a failed export, doubled on each retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Attempts at exporting a gather before giving up on it for the next one.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Exports the metrics to an OpenTelemetry collector over OTLP/gRPC, keeping
+/// the names and labels they are served with.
+pub struct OtlpExporter {
+    client: MetricsServiceClient<tonic::transport::Channel>,
+    /// When the cumulative sums and histograms started, i.e. the exporter.
+    start_time_unix_nano: u64,
+    failures_total: prometheus::IntCounter,
+}
+
+impl OtlpExporter {
+    pub fn new(endpoint: &str, metrics_config: &MetricsConfig) -> Result<Self, String> {
+        let mut endpoint = tonic::transport::Endpoint::from_shared(endpoint.to_string())
+            .map_err(|e| e.to_string())?
+            .timeout(EXPORT_TIMEOUT);
+        // Trusting the system roots
+        if endpoint.uri().scheme_str() == Some("https") {
+            endpoint = endpoint
+                .tls_config(tonic::transport::ClientTlsConfig::new())
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(Self {
+            client: MetricsServiceClient::new(endpoint.connect_lazy()),
+            start_time_unix_nano: unix_nanos(SystemTime::now()),
+            failures_total: prometheus::IntCounter::with_opts(metrics_config.opts(
+                "exporter_otlp_export_failures_total",
+                "Number of failed attempts at exporting metrics over OTLP",
+            ))
+            .expect("valid metric"),
+        })
+    }
+
+    pub fn failures_total(&self) -> prometheus::IntCounter {
+        self.failures_total.clone()
+    }
+
+    async fn export(&self, ms: &[MetricFamily]) -> Result<(), tonic::Status> {
+        let req = export_request(ms, self.start_time_unix_nano, unix_nanos(SystemTime::now()));
+        let res = self.client.clone().export(req).await;
+        if res.is_err() {
+            self.failures_total.inc();
+        }
+
+        let res = res?;
+        if let Some(partial_success) = &res.get_ref().partial_success {
+            if partial_success.rejected_data_points > 0 {
+                log::warn!(
+                    "OTLP endpoint rejected {} data points: {}",
+                    partial_success.rejected_data_points,
+                    partial_success.error_message
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gathers and exports the metrics every `interval`, retrying failed
+    /// exports with backoff within the interval.
+    pub async fn run(
+        self,
+        gather_cache: Arc<GatherCache>,
+        family_filter: Arc<FamilyFilter>,
+        interval: Duration,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            let mut ms = gather_cache.gather(None).await;
+            family_filter.retain(&mut ms);
+
+            let mut backoff = INITIAL_BACKOFF;
+            for attempt in 1..=MAX_ATTEMPTS {
+                let e = match self.export(&ms).await {
+                    Ok(()) => {
+                        log::debug!("Exported metrics over OTLP");
+                        break;
+                    }
+                    Err(e) => e,
+                };
+
+                // Newer metrics are exported in place of retrying past the
+                // interval
+                if attempt == MAX_ATTEMPTS || backoff >= interval {
+                    log::warn!(
+                        "Failed to export metrics over OTLP, giving up after {} attempts: {}",
+                        attempt,
+                        e.message()
+                    );
+                    break;
+                }
+                log::debug!(
+                    "Failed to export metrics over OTLP, retrying in {:?}: {}",
+                    backoff,
+                    e.message()
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_nanos() as u64)
+}
+
+/// Converts the families to OTLP metrics: counters to monotonic cumulative
+/// sums, gauges and untyped metrics to gauges, and histograms and summaries to
+/// their cumulative counterparts. Labels become string attributes.
+fn export_request(
+    ms: &[MetricFamily],
+    start_time_unix_nano: u64,
+    time_unix_nano: u64,
+) -> proto::ExportMetricsServiceRequest {
+    let string_attribute = |key: &str, value: &str| proto::KeyValue {
+        key: key.to_string(),
+        value: Some(proto::AnyValue {
+            value: Some(proto::any_value::Value::StringValue(value.to_string())),
+        }),
+    };
+
+    let metrics = ms
+        .iter()
+        .map(|family| {
+            let attributes = |metric: &prometheus::proto::Metric| {
+                metric
+                    .get_label()
+                    .iter()
+                    .map(|label| string_attribute(label.get_name(), label.get_value()))
+                    .collect::<Vec<_>>()
+            };
+            let number_points = |value: fn(&prometheus::proto::Metric) -> f64| {
+                family
+                    .get_metric()
+                    .iter()
+                    .map(|metric| proto::NumberDataPoint {
+                        attributes: attributes(metric),
+                        start_time_unix_nano,
+                        time_unix_nano,
+                        value: Some(proto::number_data_point::Value::AsDouble(value(metric))),
+                    })
+                    .collect()
+            };
+
+            let data = match family.get_field_type() {
+                MetricType::COUNTER => proto::metric::Data::Sum(proto::Sum {
+                    data_points: number_points(|metric| metric.get_counter().get_value()),
+                    aggregation_temporality: proto::AggregationTemporality::Cumulative as i32,
+                    is_monotonic: true,
+                }),
+                MetricType::GAUGE => proto::metric::Data::Gauge(proto::Gauge {
+                    data_points: number_points(|metric| metric.get_gauge().get_value()),
+                }),
+                MetricType::UNTYPED => proto::metric::Data::Gauge(proto::Gauge {
+                    data_points: number_points(|metric| metric.get_untyped().get_value()),
+                }),
+                MetricType::HISTOGRAM => proto::metric::Data::Histogram(proto::Histogram {
+                    data_points: family
+                        .get_metric()
+                        .iter()
+                        .map(|metric| {
+                            let histogram = metric.get_histogram();
+                            let buckets = histogram
+                                .get_bucket()
+                                .iter()
+                                .filter(|bucket| bucket.get_upper_bound().is_finite())
+                                .collect::<Vec<_>>();
+
+                            // Prometheus buckets count the observations up to
+                            // their bound, OTLP ones those since the previous
+                            // bound, along with a last bucket up to +Inf
+                            let mut bucket_counts = vec![];
+                            let mut below = 0;
+                            for bucket in buckets.iter() {
+                                bucket_counts.push(bucket.get_cumulative_count() - below);
+                                below = bucket.get_cumulative_count();
+                            }
+                            bucket_counts.push(histogram.get_sample_count().saturating_sub(below));
+
+                            proto::HistogramDataPoint {
+                                attributes: attributes(metric),
+                                start_time_unix_nano,
+                                time_unix_nano,
+                                count: histogram.get_sample_count(),
+                                sum: histogram.get_sample_sum(),
+                                bucket_counts,
+                                explicit_bounds: buckets
+                                    .iter()
+                                    .map(|bucket| bucket.get_upper_bound())
+                                    .collect(),
+                            }
+                        })
+                        .collect(),
+                    aggregation_temporality: proto::AggregationTemporality::Cumulative as i32,
+                }),
+                MetricType::SUMMARY => proto::metric::Data::Summary(proto::Summary {
+                    data_points: family
+                        .get_metric()
+                        .iter()
+                        .map(|metric| {
+                            let summary = metric.get_summary();
+
+                            proto::SummaryDataPoint {
+                                attributes: attributes(metric),
+                                start_time_unix_nano,
+                                time_unix_nano,
+                                count: summary.get_sample_count(),
+                                sum: summary.get_sample_sum(),
+                                quantile_values: summary
+                                    .get_quantile()
+                                    .iter()
+                                    .map(|quantile| proto::summary_data_point::ValueAtQuantile {
+                                        quantile: quantile.get_quantile(),
+                                        value: quantile.get_value(),
+                                    })
+                                    .collect(),
+                            }
+                        })
+                        .collect(),
+                }),
+            };
+
+            proto::Metric {
+                name: family.get_name().to_string(),
+                description: family.get_help().to_string(),
+                unit: String::new(),
+                data: Some(data),
+            }
+        })
+        .collect();
+
+    proto::ExportMetricsServiceRequest {
+        resource_metrics: vec![proto::ResourceMetrics {
+            resource: Some(proto::Resource {
+                attributes: vec![
+                    string_attribute("service.name", "lnd-exporter"),
+                    string_attribute("service.version", crate::VERSION),
+                ],
+            }),
+            scope_metrics: vec![proto::ScopeMetrics {
+                scope: Some(proto::InstrumentationScope {
+                    name: "lnd-exporter".to_string(),
+                    version: crate::VERSION.to_string(),
+                }),
+                metrics,
+            }],
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::metrics_service_server::{MetricsService, MetricsServiceServer};
+
+    /// Collector receiving the exports, failing the first `failures` of them.
+    struct Receiver {
+        failures: std::sync::Mutex<u32>,
+        exports: tokio::sync::mpsc::UnboundedSender<proto::ExportMetricsServiceRequest>,
+    }
+
+    #[tonic::async_trait]
+    impl MetricsService for Receiver {
+        async fn export(
+            &self,
+            req: tonic::Request<proto::ExportMetricsServiceRequest>,
+        ) -> Result<tonic::Response<proto::ExportMetricsServiceResponse>, tonic::Status> {
+            let mut failures = self.failures.lock().expect("failures lock");
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(tonic::Status::unavailable("not yet"));
+            }
+
+            let _ = self.exports.send(req.into_inner());
+            Ok(tonic::Response::new(
+                proto::ExportMetricsServiceResponse::default(),
+            ))
+        }
+    }
+
+    #[test]
+    fn families_keep_their_names_and_labels() {
+        let config = MetricsConfig::new("lnd");
+        let counter = prometheus::IntCounterVec::new(
+            config.opts("payments_total", "Number of payments"),
+            &["status"],
+        )
+        .expect("valid metric");
+        counter.with_label_values(&["succeeded"]).inc_by(3);
+        let histogram = prometheus::Histogram::with_opts(
+            prometheus::HistogramOpts::from(config.opts("scrape_seconds", "Scrape duration"))
+                .buckets(vec![0.1, 1.0]),
+        )
+        .expect("valid metric");
+        for value in [0.05, 0.5, 0.7, 5.0].iter() {
+            histogram.observe(*value);
+        }
+
+        let ms = prometheus::core::Collector::collect(&counter)
+            .into_iter()
+            .chain(prometheus::core::Collector::collect(&histogram))
+            .collect::<Vec<_>>();
+        let req = export_request(&ms, 1, 2);
+        let metrics = &req.resource_metrics[0].scope_metrics[0].metrics;
+
+        assert_eq!(metrics[0].name, "lnd_payments_total");
+        match &metrics[0].data {
+            Some(proto::metric::Data::Sum(sum)) => {
+                assert!(sum.is_monotonic);
+                assert_eq!(sum.data_points[0].attributes[0].key, "status");
+                assert_eq!(
+                    sum.data_points[0].value,
+                    Some(proto::number_data_point::Value::AsDouble(3.0))
+                );
+            }
+            data => panic!("counter exported as {:?}", data),
+        }
+
+        assert_eq!(metrics[1].name, "lnd_scrape_seconds");
+        match &metrics[1].data {
+            Some(proto::metric::Data::Histogram(histogram)) => {
+                let point = &histogram.data_points[0];
+                assert_eq!(point.explicit_bounds, vec![0.1, 1.0]);
+                assert_eq!(point.bucket_counts, vec![1, 2, 1]);
+                assert_eq!(point.count, 4);
+            }
+            data => panic!("histogram exported as {:?}", data),
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_exports_are_retried_and_counted() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener");
+        let addr = listener.local_addr().expect("local address");
+        let (exports_tx, mut exports_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(MetricsServiceServer::new(Receiver {
+                    failures: std::sync::Mutex::new(1),
+                    exports: exports_tx,
+                }))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+
+        let config = MetricsConfig::new("lnd");
+        let exporter =
+            OtlpExporter::new(&format!("http://{}", addr), &config).expect("OTLP exporter");
+        let failures_total = exporter.failures_total();
+        let gauge =
+            prometheus::IntGauge::with_opts(config.opts("block_height", "Chain block height"))
+                .expect("valid metric");
+        gauge.set(800_000);
+        let registry = prometheus::Registry::new();
+        registry
+            .register(Box::new(gauge))
+            .expect("registered gauge");
+
+        tokio::spawn(exporter.run(
+            Arc::new(GatherCache::new(registry, Duration::ZERO, &config)),
+            Arc::new(FamilyFilter::default()),
+            Duration::from_secs(60),
+        ));
+
+        let req = exports_rx.recv().await.expect("export");
+        assert_eq!(failures_total.get(), 1);
+        assert!(req.resource_metrics[0].scope_metrics[0]
+            .metrics
+            .iter()
+            .any(|metric| metric.name == "lnd_block_height"));
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use prometheus::{core::Collector, proto::MetricFamily};
+
+use crate::collector::{
+    CollectorOptions, LndCollector, MetricsConfig, ScopedLndClientSet, SharedCollector,
+};
+
+/// Module and target of a probe.
+type TargetKey = (String, String);
+
+/// Credentials to probe targets with, selected by the `module` parameter.
+pub struct ProbeModule {
+    pub macaroon: Vec<u8>,
+    pub tls_cert: Option<Vec<u8>>,
+}
+
+/// Scrapes the lnd node given by each `/probe` request, like blackbox_exporter
+/// probes its targets.
+///
+/// Collectors of the targets probed last are kept, along with their caches,
+/// so incremental scrapers don't start over on every probe.
+pub struct Prober {
+    modules: HashMap<String, ProbeModule>,
+    options: CollectorOptions,
+    metrics_config: MetricsConfig,
+    lnd_connections: usize,
+    max_targets: usize,
+    /// Collectors of the targets, the most recently probed last.
+    targets: std::sync::Mutex<Vec<(TargetKey, Arc<LndCollector>)>>,
+}
+
+impl Prober {
+    /// Probes run the scrapers of `options`, but never exit the process or
+    /// wait for the wallet to unlock.
+    pub fn new(
+        modules: HashMap<String, ProbeModule>,
+        options: CollectorOptions,
+        metrics_config: MetricsConfig,
+        lnd_connections: usize,
+        max_targets: usize,
+    ) -> Self {
+        Self {
+            modules,
+            options: CollectorOptions {
+                strict_permissions: false,
+                wait_unlock: None,
+                scraper_permissions: HashMap::new(),
+                ..options
+            },
+            metrics_config,
+            lnd_connections,
+            max_targets,
+            targets: std::sync::Mutex::new(vec![]),
+        }
+    }
+
+    /// Scrapes the target, returning its metrics along with `probe_success`,
+    /// which is 0 when the module is unknown or lnd could not be scraped.
+    pub async fn probe(&self, target: &str, module: &str) -> Vec<MetricFamily> {
+        let start_time = Instant::now();
+
+        let (success, mut ms) = match self.collector(target, module) {
+            Ok(collector) => {
+                let registry = prometheus::Registry::new();
+                registry
+                    .register(Box::new(SharedCollector(collector.clone())))
+                    .expect("registered collector");
+                let ms = tokio::task::spawn_blocking(move || registry.gather())
+                    .await
+                    .expect("gather");
+
+                (collector.lnd_up(), ms)
+            }
+            Err(e) => {
+                log::warn!("Failed to probe {}: {}", target, e);
+                (false, vec![])
+            }
+        };
+
+        ms.extend(self.probe_metrics(success, start_time.elapsed()));
+        ms
+    }
+
+    /// Returns the collector of the target, connecting to it on its first probe
+    /// and evicting the least recently probed target if there are too many.
+    fn collector(&self, target: &str, module: &str) -> Result<Arc<LndCollector>, String> {
+        let key: TargetKey = (module.to_string(), target.to_string());
+        let mut targets = self.targets.lock().expect("probe targets lock");
+
+        if let Some(i) = targets.iter().position(|(k, _)| *k == key) {
+            let entry = targets.remove(i);
+            let collector = entry.1.clone();
+            targets.push(entry);
+
+            return Ok(collector);
+        }
+
+        let probe_module = self
+            .modules
+            .get(module)
+            .ok_or_else(|| format!("unknown module {:?}", module))?;
+        let endpoint = lnrpc::Endpoint::from_shared(format!("https://{}", target))
+            .map_err(|e| format!("invalid target: {}", e))?;
+        let transports = (0..self.lnd_connections)
+            .map(|_| lnrpc::connect_lazy(probe_module.tls_cert.as_ref(), endpoint.clone()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("failed to connect: {}", e))?;
+
+        let collector = Arc::new(LndCollector::new(
+            ScopedLndClientSet::new(transports, Some(&probe_module.macaroon)),
+            self.options.clone(),
+            self.metrics_config.clone(),
+        ));
+        if targets.len() >= self.max_targets {
+            targets.remove(0);
+        }
+        targets.push((key, collector.clone()));
+
+        Ok(collector)
+    }
+
+    fn probe_metrics(&self, success: bool, duration: Duration) -> Vec<MetricFamily> {
+        let opts = |name: &str, help: &str| {
+            prometheus::Opts::new(name, help).const_labels(self.metrics_config.const_labels.clone())
+        };
+
+        let probe_success = prometheus::IntGauge::with_opts(opts(
+            "probe_success",
+            "Whether the probe of the target succeeded",
+        ))
+        .expect("valid metric");
+        probe_success.set(success.into());
+
+        let probe_duration = prometheus::Gauge::with_opts(opts(
+            "probe_duration_seconds",
+            "Duration of the probe of the target",
+        ))
+        .expect("valid metric");
+        probe_duration.set(duration.as_secs_f64());
+
+        probe_success
+            .collect()
+            .into_iter()
+            .chain(probe_duration.collect())
+            .collect()
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Mutex;
+
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::collector::MetricsConfig;
+
+/// Exports the memory, CPU time and open files of the exporter process on any
+/// platform sysinfo supports, e.g. to spot leaks on nodes short of memory.
+pub struct ProcessMetricsCollector {
+    pid: Pid,
+    system: Mutex<System>,
+    memory_rss_bytes: prometheus::IntGauge,
+    memory_vms_bytes: prometheus::IntGauge,
+    open_fds: prometheus::IntGauge,
+    cpu_seconds_total: prometheus::Counter,
+}
+
+impl ProcessMetricsCollector {
+    pub fn new(config: &MetricsConfig) -> Self {
+        let gauge = |name: &str, help: &str| {
+            prometheus::IntGauge::with_opts(config.opts(name, help)).expect("valid metric")
+        };
+
+        Self {
+            pid: sysinfo::get_current_pid().expect("process id"),
+            system: Mutex::new(System::new()),
+            memory_rss_bytes: gauge(
+                "exporter_memory_rss_bytes",
+                "Resident memory of the exporter process",
+            ),
+            memory_vms_bytes: gauge(
+                "exporter_memory_vms_bytes",
+                "Virtual memory of the exporter process",
+            ),
+            open_fds: gauge(
+                "exporter_open_fds",
+                "Number of files opened by the exporter process",
+            ),
+            cpu_seconds_total: prometheus::Counter::with_opts(config.opts(
+                "exporter_cpu_seconds_total",
+                "CPU time spent by the exporter process",
+            ))
+            .expect("valid metric"),
+        }
+    }
+
+    fn collectors(&self) -> [&dyn Collector; 4] {
+        [
+            &self.memory_rss_bytes,
+            &self.memory_vms_bytes,
+            &self.open_fds,
+            &self.cpu_seconds_total,
+        ]
+    }
+}
+
+/// Refreshes the metrics from the state of the process, leaving them as they
+/// were if it can't be read.
+fn scrape_process_metrics(collector: &ProcessMetricsCollector) {
+    let mut system = collector.system.lock().expect("process metrics lock");
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[collector.pid]),
+        false,
+        ProcessRefreshKind::nothing().with_memory().with_cpu(),
+    );
+
+    let process = match system.process(collector.pid) {
+        Some(process) => process,
+        None => {
+            log::warn!("Failed to read the state of the exporter process");
+            return;
+        }
+    };
+
+    collector.memory_rss_bytes.set(process.memory() as i64);
+    collector
+        .memory_vms_bytes
+        .set(process.virtual_memory() as i64);
+    if let Some(open_fds) = process.open_files() {
+        collector.open_fds.set(open_fds as i64);
+    }
+
+    // Accumulated in milliseconds
+    let cpu_seconds = process.accumulated_cpu_time() as f64 / 1_000.0;
+    collector
+        .cpu_seconds_total
+        .inc_by((cpu_seconds - collector.cpu_seconds_total.get()).max(0.0));
+}
+
+impl Collector for ProcessMetricsCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.collectors()
+            .iter()
+            .flat_map(|collector| collector.desc())
+            .collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        scrape_process_metrics(self);
+
+        self.collectors()
+            .iter()
+            .flat_map(|collector| collector.collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_state_is_read() {
+        let collector = ProcessMetricsCollector::new(&MetricsConfig::new("lnd"));
+        let _file = std::fs::File::open("Cargo.toml").expect("opened file");
+
+        collector.collect();
+        assert!(collector.memory_rss_bytes.get() > 0);
+        assert!(collector.memory_vms_bytes.get() >= collector.memory_rss_bytes.get());
+        if cfg!(target_os = "linux") {
+            assert!(collector.open_fds.get() > 0);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::{sync::Arc, time::Duration};
+
+use prometheus::proto::MetricFamily;
+
+use crate::cache::GatherCache;
+use crate::collector::{FamilyFilter, MetricsConfig};
+use crate::encoder;
+
+/// Upper bound on a push, so a hung Pushgateway doesn't pile up pushes.
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Pushes the metrics to a Prometheus Pushgateway, for nodes Prometheus can't
+/// reach to scrape.
+pub struct Pusher {
+    client: reqwest::Client,
+    /// Grouping key URL, with the basic auth credentials of the base URL if
+    /// any.
+    url: reqwest::Url,
+    failures_total: prometheus::IntCounter,
+}
+
+impl Pusher {
+    pub fn new(
+        base_url: &str,
+        job: &str,
+        instance: &str,
+        metrics_config: &MetricsConfig,
+    ) -> Result<Self, String> {
+        // Values with slashes have to be base64 encoded, which isn't worth it
+        // for job and instance names
+        if job.is_empty() || job.contains('/') {
+            return Err(format!("invalid push job {:?}", job));
+        }
+        if instance.is_empty() || instance.contains('/') {
+            return Err(format!("invalid push instance {:?}", instance));
+        }
+
+        let mut url = reqwest::Url::parse(base_url).map_err(|e| e.to_string())?;
+        url.path_segments_mut()
+            .map_err(|_| format!("{} can't be a base URL", base_url))?
+            .pop_if_empty()
+            .extend(&["metrics", "job", job, "instance", instance]);
+
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(PUSH_TIMEOUT)
+                .build()
+                .expect("pushgateway http client"),
+            url,
+            failures_total: prometheus::IntCounter::with_opts(metrics_config.opts(
+                "exporter_push_failures_total",
+                "Number of failed pushes to the Pushgateway",
+            ))
+            .expect("valid metric"),
+        })
+    }
+
+    pub fn failures_total(&self) -> prometheus::IntCounter {
+        self.failures_total.clone()
+    }
+
+    /// Replaces the metrics of the grouping key with `ms`.
+    pub async fn push(&self, ms: &[MetricFamily]) -> Result<(), Error> {
+        let res = self.try_push(ms).await;
+        if res.is_err() {
+            self.failures_total.inc();
+        }
+
+        res
+    }
+
+    async fn try_push(&self, ms: &[MetricFamily]) -> Result<(), Error> {
+        let (body, content_type) = encoder::encode(ms, encoder::Format::Text)?;
+
+        let res = self
+            .client
+            .put(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body)
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            return Err(format!("pushgateway responded with {}", res.status()).into());
+        }
+
+        Ok(())
+    }
+
+    /// Gathers and pushes the metrics every `interval`, logging the failures.
+    pub async fn run(
+        self,
+        gather_cache: Arc<GatherCache>,
+        family_filter: Arc<FamilyFilter>,
+        interval: Duration,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            let mut ms = gather_cache.gather(None).await;
+            family_filter.retain(&mut ms);
+            match self.push(&ms).await {
+                Ok(()) => log::debug!("Pushed metrics to {}", self.redacted_url()),
+                Err(e) => log::warn!("Failed to push metrics to {}: {}", self.redacted_url(), e),
+            }
+        }
+    }
+
+    fn redacted_url(&self) -> String {
+        let mut url = self.url.clone();
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+
+        url.to_string()
+    }
+}
+
+/// Name of the host, which pushes are grouped by unless `--push-instance` is
+/// given.
+pub fn hostname() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn metrics_are_put_to_the_grouping_key() {
+        let (req_tx, mut req_rx) = tokio::sync::mpsc::unbounded_channel();
+        let make_service = hyper::service::make_service_fn(move |_| {
+            let req_tx = req_tx.clone();
+            async move {
+                Ok::<_, hyper::http::Error>(hyper::service::service_fn(
+                    move |req: hyper::Request<hyper::Body>| {
+                        let req_tx = req_tx.clone();
+                        async move {
+                            let (parts, body) = req.into_parts();
+                            let body = hyper::body::to_bytes(body).await.expect("request body");
+                            let _ = req_tx.send((parts, body));
+
+                            hyper::Response::builder()
+                                .status(200)
+                                .body(hyper::Body::empty())
+                        }
+                    },
+                ))
+            }
+        });
+        let server =
+            hyper::Server::bind(&"127.0.0.1:0".parse().expect("valid address")).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        let config = MetricsConfig::new("lnd");
+        let pusher = Pusher::new(
+            &format!("http://user:secret@{}/", addr),
+            "lnd-exporter",
+            "node-1",
+            &config,
+        )
+        .expect("pusher");
+        let gauge =
+            prometheus::IntGauge::with_opts(config.opts("block_height", "Chain block height"))
+                .expect("valid metric");
+        gauge.set(800_000);
+
+        pusher
+            .push(&prometheus::core::Collector::collect(&gauge))
+            .await
+            .expect("pushed");
+
+        let (parts, body) = req_rx.recv().await.expect("push request");
+        assert_eq!(parts.method, hyper::Method::PUT);
+        assert_eq!(
+            parts.uri.path(),
+            "/metrics/job/lnd-exporter/instance/node-1"
+        );
+        assert_eq!(
+            parts.headers[hyper::header::AUTHORIZATION],
+            "Basic dXNlcjpzZWNyZXQ="
+        );
+        assert!(String::from_utf8_lossy(&body).contains("lnd_block_height 800000"));
+        assert_eq!(pusher.failures_total.get(), 0);
+
+        let unreachable =
+            Pusher::new("http://127.0.0.1:1", "lnd-exporter", "node-1", &config).expect("pusher");
+        assert!(unreachable.push(&[]).await.is_err());
+        assert_eq!(unreachable.failures_total.get(), 1);
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use prometheus::{core::Collector, proto::MetricFamily};
+use tokio::{
+    sync::{Semaphore, SemaphorePermit},
+    time::Instant,
+};
+
+use crate::collector::MetricsConfig;
+
+/// Retry-After of requests rejected for exceeding the concurrent scrapes.
+const BUSY_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Limits how many `/metrics` requests are served at once and how often, so
+/// that an aggressive Prometheus cannot overload the exporter and lnd.
+pub struct RateLimiter {
+    scrapes: Semaphore,
+    min_interval: Duration,
+    last_served: std::sync::Mutex<Option<Instant>>,
+    rate_limited_requests_total: prometheus::IntCounter,
+}
+
+impl RateLimiter {
+    pub fn new(
+        max_concurrent_scrapes: usize,
+        min_interval: Duration,
+        metrics_config: &MetricsConfig,
+    ) -> Self {
+        Self {
+            scrapes: Semaphore::new(max_concurrent_scrapes),
+            min_interval,
+            last_served: std::sync::Mutex::new(None),
+            rate_limited_requests_total: prometheus::IntCounter::with_opts(metrics_config.opts(
+                "exporter_rate_limited_requests_total",
+                "Number of /metrics requests rejected by the rate limiter",
+            ))
+            .expect("valid metric"),
+        }
+    }
+
+    /// Admits a request, returning a permit to hold while serving it, or how
+    /// long to wait before retrying.
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_>, Duration> {
+        let res = self.admit();
+        if res.is_err() {
+            self.rate_limited_requests_total.inc();
+        }
+
+        res
+    }
+
+    fn admit(&self) -> Result<SemaphorePermit<'_>, Duration> {
+        let mut last_served = self.last_served.lock().expect("last served lock");
+
+        if let Some(last_served) = *last_served {
+            let elapsed = last_served.elapsed();
+            if elapsed < self.min_interval {
+                return Err(self.min_interval - elapsed);
+            }
+        }
+
+        let permit = self.scrapes.try_acquire().map_err(|_| BUSY_RETRY_AFTER)?;
+        *last_served = Some(Instant::now());
+
+        Ok(permit)
+    }
+
+    pub fn collect(&self) -> Vec<MetricFamily> {
+        self.rate_limited_requests_total.collect()
+    }
+}
+
+/// Value of the Retry-After header, in whole seconds rounded up.
+pub fn retry_after_secs(retry_after: Duration) -> u64 {
+    let secs = retry_after.as_secs();
+    if retry_after.subsec_nanos() > 0 {
+        secs + 1
+    } else {
+        secs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_scrapes_are_limited() {
+        let limiter = RateLimiter::new(1, Duration::ZERO, &MetricsConfig::new("lnd"));
+
+        let permit = limiter.try_acquire().expect("first scrape");
+        assert_eq!(limiter.try_acquire().err(), Some(BUSY_RETRY_AFTER));
+
+        drop(permit);
+        assert!(limiter.try_acquire().is_ok());
+        assert_eq!(limiter.rate_limited_requests_total.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn scrapes_are_spaced_by_min_interval() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(10), &MetricsConfig::new("lnd"));
+
+        drop(limiter.try_acquire().expect("first scrape"));
+
+        let retry_after = limiter.try_acquire().expect_err("rate limited");
+        assert!(retry_after <= Duration::from_secs(10));
+        assert_eq!(retry_after_secs(retry_after), 10);
+    }
+
+    #[test]
+    fn retry_after_is_rounded_up() {
+        assert_eq!(retry_after_secs(Duration::from_secs(3)), 3);
+        assert_eq!(retry_after_secs(Duration::from_millis(2_001)), 3);
+        assert_eq!(retry_after_secs(Duration::from_millis(1)), 1);
+    }
+}
@@ -0,0 +1,156 @@
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+};
+
+use crate::collector::MetricsConfig;
+
+/// Exports the state of the tokio runtime the exporter runs on, e.g. to spot
+/// tasks leaked by scrapers or subscriptions.
+pub struct RuntimeCollector {
+    handle: tokio::runtime::Handle,
+    workers: prometheus::IntGauge,
+    tasks_alive: prometheus::IntGauge,
+    tasks_spawned_total: prometheus::IntCounter,
+    remote_schedules_total: prometheus::IntCounter,
+    polls_total: prometheus::IntCounter,
+    global_queue_depth: prometheus::IntGauge,
+    blocking_threads: prometheus::IntGauge,
+    idle_blocking_threads: prometheus::IntGauge,
+    blocking_queue_depth: prometheus::IntGauge,
+}
+
+impl RuntimeCollector {
+    pub fn new(handle: tokio::runtime::Handle, config: &MetricsConfig) -> Self {
+        let gauge = |name: &str, help: &str| {
+            prometheus::IntGauge::with_opts(config.opts(name, help)).expect("valid metric")
+        };
+        let counter = |name: &str, help: &str| {
+            prometheus::IntCounter::with_opts(config.opts(name, help)).expect("valid metric")
+        };
+
+        Self {
+            handle,
+            workers: gauge(
+                "exporter_tokio_workers",
+                "Number of worker threads of the tokio runtime",
+            ),
+            tasks_alive: gauge(
+                "exporter_tokio_tasks_alive",
+                "Number of tasks alive in the tokio runtime",
+            ),
+            tasks_spawned_total: counter(
+                "exporter_tokio_tasks_spawned_total",
+                "Number of tasks spawned in the tokio runtime",
+            ),
+            remote_schedules_total: counter(
+                "exporter_tokio_remote_schedules_total",
+                "Number of tasks scheduled from outside the tokio runtime",
+            ),
+            polls_total: counter(
+                "exporter_tokio_polls_total",
+                "Number of task polls by the tokio workers",
+            ),
+            global_queue_depth: gauge(
+                "exporter_tokio_global_queue_depth",
+                "Number of tasks waiting in the global queue of the tokio runtime",
+            ),
+            blocking_threads: gauge(
+                "exporter_tokio_blocking_threads",
+                "Number of threads of the tokio blocking pool",
+            ),
+            idle_blocking_threads: gauge(
+                "exporter_tokio_idle_blocking_threads",
+                "Number of idle threads of the tokio blocking pool",
+            ),
+            blocking_queue_depth: gauge(
+                "exporter_tokio_blocking_queue_depth",
+                "Number of tasks waiting for a thread of the tokio blocking pool",
+            ),
+        }
+    }
+
+    fn collectors(&self) -> [&dyn Collector; 9] {
+        [
+            &self.workers,
+            &self.tasks_alive,
+            &self.tasks_spawned_total,
+            &self.remote_schedules_total,
+            &self.polls_total,
+            &self.global_queue_depth,
+            &self.blocking_threads,
+            &self.idle_blocking_threads,
+            &self.blocking_queue_depth,
+        ]
+    }
+}
+
+/// Sets a counter to a total counted by tokio.
+fn set_total(counter: &prometheus::IntCounter, total: u64) {
+    counter.inc_by(total.saturating_sub(counter.get()));
+}
+
+impl Collector for RuntimeCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.collectors()
+            .iter()
+            .flat_map(|collector| collector.desc())
+            .collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let metrics = self.handle.metrics();
+
+        self.workers.set(metrics.num_workers() as i64);
+        self.tasks_alive.set(metrics.num_alive_tasks() as i64);
+        set_total(&self.tasks_spawned_total, metrics.spawned_tasks_count());
+        set_total(
+            &self.remote_schedules_total,
+            metrics.remote_schedule_count(),
+        );
+        set_total(
+            &self.polls_total,
+            (0..metrics.num_workers())
+                .map(|worker| metrics.worker_poll_count(worker))
+                .sum(),
+        );
+        self.global_queue_depth
+            .set(metrics.global_queue_depth() as i64);
+        self.blocking_threads
+            .set(metrics.num_blocking_threads() as i64);
+        self.idle_blocking_threads
+            .set(metrics.num_idle_blocking_threads() as i64);
+        self.blocking_queue_depth
+            .set(metrics.blocking_queue_depth() as i64);
+
+        self.collectors()
+            .iter()
+            .flat_map(|collector| collector.collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn alive_tasks_are_counted() {
+        let collector = RuntimeCollector::new(
+            tokio::runtime::Handle::current(),
+            &MetricsConfig::new("lnd"),
+        );
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(rx);
+
+        collector.collect();
+        assert_eq!(collector.tasks_alive.get(), 1);
+        assert_eq!(collector.tasks_spawned_total.get(), 1);
+
+        tx.send(()).expect("task alive");
+        task.await.expect("task").expect("sender");
+        collector.collect();
+        assert_eq!(collector.tasks_alive.get(), 0);
+        assert_eq!(collector.tasks_spawned_total.get(), 1);
+    }
+}
@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use crate::listener::Listener;
+
+/// Descriptor of the first socket passed by socket activation.
+#[cfg(unix)]
+const LISTEN_FDS_START: i32 = 3;
+
+/// Takes the sockets systemd passed to the exporter through socket
+/// activation, if any.
+pub fn listen_fds() -> std::io::Result<Vec<Listener>> {
+    let count = listen_fds_count(
+        std::env::var("LISTEN_PID").ok(),
+        std::env::var("LISTEN_FDS").ok(),
+        std::process::id(),
+    );
+    // Processes spawned later must not take the sockets for theirs
+    for var in &["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] {
+        std::env::remove_var(var);
+    }
+
+    (0..count).map(listener).collect()
+}
+
+/// Number of sockets passed, which are meant for the process only if it has
+/// the given pid.
+fn listen_fds_count(listen_pid: Option<String>, listen_fds: Option<String>, pid: u32) -> usize {
+    match (
+        listen_pid.and_then(|listen_pid| listen_pid.parse::<u32>().ok()),
+        listen_fds.and_then(|listen_fds| listen_fds.parse().ok()),
+    ) {
+        (Some(listen_pid), Some(count)) if listen_pid == pid => count,
+        _ => 0,
+    }
+}
+
+#[cfg(unix)]
+fn listener(i: usize) -> std::io::Result<Listener> {
+    use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+    let fd = LISTEN_FDS_START + i as i32;
+    // SAFETY: systemd passes the sockets as open descriptors starting at
+    // LISTEN_FDS_START, which nothing else in the process owns
+    let tcp = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+    if tcp.local_addr().is_ok() {
+        return Ok(Listener::Tcp(tcp));
+    }
+
+    // Unix sockets have no internet address
+    // SAFETY: the descriptor was just released by the TCP listener
+    let unix = unsafe { std::os::unix::net::UnixListener::from_raw_fd(tcp.into_raw_fd()) };
+    unix.local_addr()?;
+    crate::listener::UnixIncoming::from_std(unix).map(Listener::Unix)
+}
+
+#[cfg(not(unix))]
+fn listener(_: usize) -> std::io::Result<Listener> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "socket activation is not supported on this platform",
+    ))
+}
+
+/// Tells systemd about the state of the exporter, like `READY=1`, when it runs
+/// it as a `Type=notify` service.
+pub fn notify(state: &str) {
+    let socket = match std::env::var_os("NOTIFY_SOCKET") {
+        Some(socket) => socket,
+        None => return,
+    };
+
+    if let Err(e) = notify_socket(&socket, state) {
+        log::warn!("Failed to notify systemd of {}: {}", state, e);
+    }
+}
+
+#[cfg(unix)]
+fn notify_socket(socket: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::{ffi::OsStrExt, net::UnixDatagram};
+
+    let datagram = UnixDatagram::unbound()?;
+    match socket.as_bytes().strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            datagram.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        #[cfg(not(target_os = "linux"))]
+        Some(_) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "abstract sockets are only supported on Linux",
+            ))
+        }
+        None => {
+            datagram.send_to(state.as_bytes(), socket)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn notify_socket(_: &std::ffi::OsStr, _: &str) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Interval to ping the systemd watchdog at, half its timeout, if it watches
+/// the exporter.
+pub fn watchdog_interval() -> Option<Duration> {
+    watchdog_interval_from(
+        std::env::var("WATCHDOG_USEC").ok(),
+        std::env::var("WATCHDOG_PID").ok(),
+        std::process::id(),
+    )
+}
+
+fn watchdog_interval_from(
+    watchdog_usec: Option<String>,
+    watchdog_pid: Option<String>,
+    pid: u32,
+) -> Option<Duration> {
+    if let Some(watchdog_pid) = watchdog_pid {
+        if watchdog_pid.parse::<u32>().ok() != Some(pid) {
+            return None;
+        }
+    }
+
+    watchdog_usec
+        .and_then(|usec| usec.parse::<u64>().ok())
+        .filter(|usec| *usec > 0)
+        .map(|usec| Duration::from_micros(usec) / 2)
+}
+
+/// Pings the watchdog every `interval`. Runs on the runtime serving requests,
+/// so the pings stop if it hangs.
+pub async fn watchdog(interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+        notify("WATCHDOG=1");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sockets_are_only_taken_by_their_process() {
+        let some = |s: &str| Some(s.to_string());
+
+        assert_eq!(listen_fds_count(some("42"), some("2"), 42), 2);
+        assert_eq!(listen_fds_count(some("41"), some("2"), 42), 0);
+        assert_eq!(listen_fds_count(None, some("2"), 42), 0);
+        assert_eq!(listen_fds_count(some("42"), None, 42), 0);
+
+        assert_eq!(
+            watchdog_interval_from(some("30000000"), None, 42),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            watchdog_interval_from(some("30000000"), some("42"), 42),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            watchdog_interval_from(some("30000000"), some("41"), 42),
+            None
+        );
+        assert_eq!(watchdog_interval_from(some("0"), None, 42), None);
+        assert_eq!(watchdog_interval_from(None, None, 42), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn states_are_sent_to_the_notify_socket() {
+        let path = std::env::temp_dir().join(format!("lnd-exporter-notify-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let socket = std::os::unix::net::UnixDatagram::bind(&path).expect("notify socket");
+
+        notify_socket(path.as_os_str(), "READY=1").expect("notified");
+
+        let mut buf = [0; 64];
+        let n = socket.recv(&mut buf).expect("state");
+        assert_eq!(&buf[..n], b"READY=1");
+        std::fs::remove_file(&path).expect("removed notify socket");
+    }
+}
@@ -0,0 +1,1277 @@
+//! End to end tests running the exporter against a mock lnd node.
+
+mod mock_lnd;
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use mock_lnd::MockLnd;
+
+use crate::{
+    auth::TokenAuth,
+    cache::GatherCache,
+    collector::{
+        check_permissions, BitcoindClient, CollectorOptions, FamilyFilter, LndCollector,
+        MetricsConfig, ScopedLndClientSet, ScraperName, ScraperSet, SharedCollector,
+    },
+    probe::{ProbeModule, Prober},
+    rate_limit::RateLimiter,
+    WebConfig,
+};
+
+/// Exporter scraping a mock lnd node, like `main` sets it up.
+struct Exporter {
+    lnd: MockLnd,
+    gather_cache: Arc<GatherCache>,
+    rate_limiter: Arc<RateLimiter>,
+    prober: Arc<Prober>,
+    family_filter: Arc<FamilyFilter>,
+    collectors: Arc<Vec<Arc<LndCollector>>>,
+    web: Arc<WebConfig>,
+}
+
+impl Exporter {
+    async fn start(lnd: MockLnd) -> Self {
+        Self::start_with_config(lnd, MetricsConfig::new("lnd")).await
+    }
+
+    async fn start_with_config(lnd: MockLnd, metrics_config: MetricsConfig) -> Self {
+        Self::start_with_options(
+            lnd,
+            CollectorOptions {
+                wait_unlock: None,
+                ..CollectorOptions::default()
+            },
+            metrics_config,
+        )
+        .await
+    }
+
+    async fn start_with_options(
+        lnd: MockLnd,
+        options: CollectorOptions,
+        metrics_config: MetricsConfig,
+    ) -> Self {
+        let transport = connect(&lnd).await;
+        Self::start_with_transport(lnd, transport, options, metrics_config).await
+    }
+
+    async fn start_with_transport(
+        lnd: MockLnd,
+        transport: lnrpc::Transport,
+        options: CollectorOptions,
+        metrics_config: MetricsConfig,
+    ) -> Self {
+        let family_filter = options.family_filter.clone();
+        let lnd_clients = ScopedLndClientSet::new(vec![transport], Some(b"macaroon"));
+        let scraper_timeout = Duration::from_secs(5);
+        let metrics_config = metrics_config
+            .with_const_labels(crate::resolve_node_labels(&lnd_clients, scraper_timeout).await);
+        let permissions =
+            check_permissions(&lnd_clients, &options.enabled_scrapers, scraper_timeout).await;
+
+        let collector = Arc::new(LndCollector::new(
+            lnd_clients,
+            CollectorOptions {
+                scraper_timeout,
+                scraper_retries: 0,
+                scraper_permissions: permissions.permitted(),
+                ..options
+            },
+            metrics_config.clone(),
+        ));
+
+        let registry = prometheus::Registry::new();
+        registry
+            .register(Box::new(SharedCollector(collector.clone())))
+            .expect("registered collector");
+
+        Self {
+            lnd,
+            gather_cache: Arc::new(GatherCache::new(registry, Duration::ZERO, &metrics_config)),
+            rate_limiter: Arc::new(RateLimiter::new(1, Duration::ZERO, &metrics_config)),
+            prober: Arc::new(Prober::new(
+                HashMap::new(),
+                CollectorOptions::default(),
+                metrics_config,
+                1,
+                1,
+            )),
+            family_filter,
+            collectors: Arc::new(vec![collector]),
+            web: Arc::new(WebConfig::default()),
+        }
+    }
+
+    async fn get(&self, path: &str) -> hyper::Response<hyper::Body> {
+        let req = hyper::Request::get(path)
+            .body(hyper::Body::empty())
+            .expect("valid request");
+
+        self.request(req).await
+    }
+
+    async fn request(&self, req: hyper::Request<hyper::Body>) -> hyper::Response<hyper::Body> {
+        crate::handler(
+            req,
+            self.gather_cache.clone(),
+            self.rate_limiter.clone(),
+            self.prober.clone(),
+            self.family_filter.clone(),
+            self.collectors.clone(),
+            self.web.clone(),
+        )
+        .await
+        .expect("response")
+    }
+
+    /// Scrapes the telemetry path through the HTTP handler.
+    async fn scrape(&self) -> String {
+        let res = self.get(&self.web.telemetry_path).await;
+        assert_eq!(res.status(), 200);
+
+        let body = hyper::body::to_bytes(res.into_body())
+            .await
+            .expect("metrics body");
+        String::from_utf8(body.to_vec()).expect("utf-8 metrics")
+    }
+}
+
+/// Serves the mock and connects to it.
+async fn connect(lnd: &MockLnd) -> lnrpc::Transport {
+    let (addr, tls_cert) = lnd.clone().serve().await;
+    let endpoint = lnrpc::Endpoint::from_shared(format!("https://localhost:{}", addr.port()))
+        .expect("valid endpoint address");
+
+    // Check the node is reachable the way lnd clients reach it
+    lnrpc::new(Some(&tls_cert), Some(b"macaroon"), endpoint.clone())
+        .await
+        .expect("lightning client");
+
+    lnrpc::connect(Some(&tls_cert), endpoint)
+        .await
+        .expect("lnd connection")
+}
+
+fn assert_has_line(metrics: &str, line: &str) {
+    assert!(
+        metrics.lines().any(|l| l == line),
+        "missing {:?} in:\n{}",
+        line,
+        metrics
+    );
+}
+
+fn channel(chan_id: u64, local_balance: i64, remote_balance: i64) -> lnrpc::Channel {
+    lnrpc::Channel {
+        active: true,
+        chan_id,
+        channel_point: format!("txid:{}", chan_id),
+        capacity: local_balance + remote_balance,
+        local_balance,
+        remote_balance,
+        ..lnrpc::Channel::default()
+    }
+}
+
+fn payment(status: lnrpc::payment::PaymentStatus, fee_msat: i64) -> lnrpc::Payment {
+    lnrpc::Payment {
+        status: status as i32,
+        fee_msat,
+        htlcs: vec![lnrpc::HtlcAttempt::default()],
+        ..lnrpc::Payment::default()
+    }
+}
+
+#[tokio::test]
+async fn getinfo_is_exported() {
+    let lnd = MockLnd::default();
+    lnd.state().info = lnrpc::GetInfoResponse {
+        num_peers: 3,
+        block_height: 800_000,
+        ..lnrpc::GetInfoResponse::default()
+    };
+    let exporter = Exporter::start(lnd).await;
+
+    let metrics = exporter.scrape().await;
+
+    assert_has_line(&metrics, "lnd_up 1");
+    assert_has_line(&metrics, "lnd_num_peers_total 3");
+    assert_has_line(&metrics, "lnd_block_height 800000");
+    assert_has_line(
+        &metrics,
+        "lnd_exporter_scrape_success{scraper=\"getinfo\"} 1",
+    );
+}
+
+#[tokio::test]
+async fn last_success_timestamp_is_only_set_on_success() {
+    let lnd = MockLnd::default();
+    lnd.state().denied.insert("list_channels");
+    let exporter = Exporter::start(lnd).await;
+
+    let metrics = exporter.scrape().await;
+
+    let timestamp = |scraper: &str| {
+        let prefix = format!(
+            "lnd_exporter_scraper_last_success_timestamp_seconds{{scraper=\"{}\"}} ",
+            scraper
+        );
+        metrics
+            .lines()
+            .find_map(|line| line.strip_prefix(&prefix))
+            .map(|value| value.parse::<f64>().expect("timestamp"))
+    };
+    assert!(timestamp("getinfo").expect("getinfo timestamp") > 1.6e9);
+    assert_eq!(timestamp("channels"), None);
+}
+
+#[tokio::test]
+async fn listchannels_is_exported() {
+    let lnd = MockLnd::default();
+    lnd.state().channels = vec![channel(1, 400_000, 600_000), channel(2, 150_000, 50_000)];
+    let exporter = Exporter::start(lnd).await;
+
+    let metrics = exporter.scrape().await;
+
+    for line in [
+        "lnd_channel_balance_total_sat{active=\"true\",category=\"local\",chan_id=\"1\",channel_point=\"txid:1\"} 400000",
+        "lnd_channel_balance_total_sat{active=\"true\",category=\"remote\",chan_id=\"1\",channel_point=\"txid:1\"} 600000",
+        "lnd_channel_balance_total_sat{active=\"true\",category=\"local\",chan_id=\"2\",channel_point=\"txid:2\"} 150000",
+        "lnd_channel_balance_total_sat{active=\"true\",category=\"remote\",chan_id=\"2\",channel_point=\"txid:2\"} 50000",
+    ] {
+        assert_has_line(&metrics, line);
+    }
+}
+
+#[tokio::test]
+async fn listpayments_is_scraped_incrementally() {
+    use lnrpc::payment::PaymentStatus;
+
+    let lnd = MockLnd::default();
+    lnd.state().payments = vec![
+        payment(PaymentStatus::Succeeded, 1_000),
+        payment(PaymentStatus::Failed, 0),
+    ];
+    let exporter = Exporter::start(lnd).await;
+
+    let metrics = exporter.scrape().await;
+    assert_has_line(&metrics, "lnd_outgoing_payments{status=\"succeeded\"} 1");
+    assert_has_line(&metrics, "lnd_outgoing_payments{status=\"failed\"} 1");
+    assert_has_line(&metrics, "lnd_total_fee_msat 1000");
+
+    exporter
+        .lnd
+        .state()
+        .payments
+        .push(payment(PaymentStatus::Succeeded, 500));
+
+    let metrics = exporter.scrape().await;
+    assert_has_line(&metrics, "lnd_outgoing_payments{status=\"succeeded\"} 2");
+    assert_has_line(&metrics, "lnd_outgoing_payments{status=\"failed\"} 1");
+    assert_has_line(&metrics, "lnd_total_fee_msat 1500");
+
+    // The second scrape only asked for the payment added since the first one
+    assert_eq!(exporter.lnd.state().payment_index_offsets, vec![0, 2]);
+}
+
+#[tokio::test]
+async fn denied_scraper_is_disabled() {
+    let lnd = MockLnd::default();
+    lnd.state().denied.insert("list_payments");
+    let exporter = Exporter::start(lnd).await;
+
+    for _ in 0..2 {
+        let metrics = exporter.scrape().await;
+
+        assert_has_line(&metrics, "lnd_up 1");
+        assert_has_line(
+            &metrics,
+            "lnd_exporter_scraper_enabled{scraper=\"payments\"} 0",
+        );
+        assert!(!metrics.contains("lnd_outgoing_payments"));
+    }
+
+    assert_has_line(
+        &exporter.scrape().await,
+        "lnd_exporter_scrape_errors_total{grpc_code=\"permission_denied\",scraper=\"payments\"} 1",
+    );
+
+    // Once denied, the scraper is not run again
+    let calls = &exporter.lnd.state().calls;
+    assert_eq!(calls.iter().filter(|m| **m == "list_payments").count(), 1);
+}
+
+#[tokio::test]
+async fn denied_scraper_keeps_running_with_strict_permissions() {
+    let lnd = MockLnd::default();
+    lnd.state().denied.insert("list_payments");
+    let exporter = Exporter::start_with_options(
+        lnd,
+        CollectorOptions {
+            strict_permissions: true,
+            wait_unlock: None,
+            ..CollectorOptions::default()
+        },
+        MetricsConfig::new("lnd"),
+    )
+    .await;
+
+    exporter.scrape().await;
+    let metrics = exporter.scrape().await;
+
+    assert_has_line(
+        &metrics,
+        "lnd_exporter_scraper_enabled{scraper=\"payments\"} 1",
+    );
+    assert_has_line(
+        &metrics,
+        "lnd_exporter_scrape_errors_total{grpc_code=\"permission_denied\",scraper=\"payments\"} 2",
+    );
+}
+
+#[tokio::test]
+async fn build_info_is_exported() {
+    let exporter = Exporter::start(MockLnd::default()).await;
+
+    assert_has_line(
+        &exporter.scrape().await,
+        &format!(
+            "lnd_exporter_build_info{{revision=\"{}\",rustc=\"{}\",version=\"{}\"}} 1",
+            crate::REVISION,
+            crate::RUSTC_VERSION,
+            env!("CARGO_PKG_VERSION"),
+        ),
+    );
+}
+
+#[tokio::test]
+async fn scraper_permissions_are_checked_at_startup() {
+    let lnd = MockLnd::default();
+    lnd.state().missing_permissions.insert("invoices:read");
+    let exporter = Exporter::start(lnd).await;
+
+    let metrics = exporter.scrape().await;
+    assert_has_line(
+        &metrics,
+        "lnd_exporter_scraper_permitted{scraper=\"invoices\"} 0",
+    );
+    assert_has_line(
+        &metrics,
+        "lnd_exporter_scraper_permitted{scraper=\"payments\"} 1",
+    );
+    // Disabled scrapers are not checked
+    assert!(!metrics.contains("lnd_exporter_scraper_permitted{scraper=\"graph\"}"));
+}
+
+#[tokio::test]
+async fn metrics_are_prefixed_with_namespace() {
+    let exporter =
+        Exporter::start_with_config(MockLnd::default(), MetricsConfig::new("ln_lnd")).await;
+
+    let metrics = exporter.scrape().await;
+
+    assert_has_line(&metrics, "ln_lnd_up 1");
+    for line in metrics.lines().filter(|line| line.starts_with("# TYPE")) {
+        assert!(line.starts_with("# TYPE ln_lnd_"), "unprefixed {:?}", line);
+    }
+}
+
+fn node_info(alias: &str) -> lnrpc::GetInfoResponse {
+    lnrpc::GetInfoResponse {
+        identity_pubkey: "02abcdef".to_string(),
+        alias: alias.to_string(),
+        chains: vec![lnrpc::Chain {
+            chain: "bitcoin".to_string(),
+            network: "mainnet".to_string(),
+        }],
+        ..lnrpc::GetInfoResponse::default()
+    }
+}
+
+#[tokio::test]
+async fn metrics_have_const_labels() {
+    let lnd = MockLnd::default();
+    lnd.state().info = node_info("my \"node\" ⚡");
+    let mut metrics_config = MetricsConfig::new("lnd");
+    metrics_config.const_labels =
+        std::iter::once(("region".to_string(), "eu".to_string())).collect::<HashMap<_, _>>();
+    let exporter = Exporter::start_with_config(lnd, metrics_config).await;
+
+    let metrics = exporter.scrape().await;
+
+    assert_has_line(
+        &metrics,
+        "lnd_up{chain=\"bitcoin\",network=\"mainnet\",node_alias=\"my \\\"node\\\" ⚡\",node_pubkey=\"02abcdef\",region=\"eu\"} 1",
+    );
+    for line in metrics.lines().filter(|line| !line.starts_with('#')) {
+        assert!(
+            line.contains("node_pubkey=\"02abcdef\""),
+            "unlabeled {:?}",
+            line
+        );
+        assert!(line.contains("region=\"eu\""), "unlabeled {:?}", line);
+    }
+}
+
+#[tokio::test]
+async fn const_labels_are_refreshed_when_the_node_changes() {
+    let lnd = MockLnd::default();
+    lnd.state().info = node_info("before");
+    let exporter = Exporter::start(lnd).await;
+
+    let metrics = exporter.scrape().await;
+    assert!(metrics.contains("node_alias=\"before\""));
+
+    exporter.lnd.state().info = node_info("after");
+
+    let metrics = exporter.scrape().await;
+    assert!(metrics.contains("node_alias=\"after\""));
+    // Only the HTTP server metrics, which live outside the collector, keep the
+    // labels resolved at startup
+    for line in metrics.lines().filter(|line| {
+        !line.starts_with("lnd_exporter_cache_") && !line.starts_with("lnd_exporter_rate_limited_")
+    }) {
+        assert!(!line.contains("node_alias=\"before\""), "stale {:?}", line);
+    }
+}
+
+#[tokio::test]
+async fn frequent_scrapes_are_rate_limited() {
+    let mut exporter = Exporter::start(MockLnd::default()).await;
+    exporter.rate_limiter = Arc::new(RateLimiter::new(
+        1,
+        Duration::from_secs(60),
+        &MetricsConfig::new("lnd"),
+    ));
+
+    exporter.scrape().await;
+
+    let res = exporter.get("/metrics").await;
+    assert_eq!(res.status(), 429);
+    assert_eq!(res.headers()[hyper::header::RETRY_AFTER], "60");
+
+    // Health checks are not rate limited
+    assert_eq!(exporter.get("/health").await.status(), 200);
+
+    // Only the first scrape reached lnd
+    let calls = &exporter.lnd.state().calls;
+    assert_eq!(calls.iter().filter(|m| **m == "get_info").count(), 2);
+}
+
+#[tokio::test]
+async fn readiness_follows_lnd_connectivity() {
+    let exporter = Exporter::start(MockLnd::default()).await;
+    let get_info_calls = || {
+        exporter
+            .lnd
+            .state()
+            .calls
+            .iter()
+            .filter(|m| **m == "get_info")
+            .count()
+    };
+    let startup_calls = get_info_calls();
+
+    // Checked with GetInfo before the first scrape, then reused
+    assert_eq!(exporter.get("/ready").await.status(), 200);
+    assert_eq!(exporter.get("/ready").await.status(), 200);
+    assert_eq!(get_info_calls(), startup_calls + 1);
+
+    exporter.lnd.state().denied.insert("get_info");
+    exporter.scrape().await;
+    let res = exporter.get("/ready").await;
+    assert_eq!(res.status(), 503);
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("ready body");
+    assert_eq!(body, "lnd did not answer GetInfo");
+    assert_eq!(get_info_calls(), startup_calls + 2);
+
+    // Liveness is unaffected
+    assert_eq!(exporter.get("/health").await.status(), 200);
+}
+
+#[tokio::test]
+async fn landing_page_links_the_endpoints() {
+    let lnd = MockLnd::default();
+    lnd.state().info = node_info("<alice>");
+    let mut exporter = Exporter::start(lnd).await;
+    exporter.web = Arc::new(WebConfig {
+        endpoints: vec!["https://localhost:10009".to_string()],
+        ..WebConfig::default()
+    });
+    exporter.scrape().await;
+    let get_info_calls = || {
+        exporter
+            .lnd
+            .state()
+            .calls
+            .iter()
+            .filter(|call| **call == "get_info")
+            .count()
+    };
+    let scraped_calls = get_info_calls();
+
+    let res = exporter.get("/").await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()[hyper::header::CONTENT_TYPE],
+        "text/html; charset=utf-8"
+    );
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("page body");
+    let page = String::from_utf8(body.to_vec()).expect("utf-8 page");
+    assert!(page.contains(crate::VERSION));
+    for link in &["/metrics", "/health", "/ready"] {
+        assert!(page.contains(&format!("<a href=\"{}\">", link)), "{}", link);
+    }
+    assert!(page.contains("&lt;alice&gt; (https://localhost:10009)"));
+    assert_eq!(get_info_calls(), scraped_calls);
+}
+
+#[tokio::test]
+async fn metrics_are_served_at_the_telemetry_path() {
+    let mut exporter = Exporter::start(MockLnd::default()).await;
+    exporter.web = Arc::new(WebConfig {
+        telemetry_path: "/prometheus".to_string(),
+        ..WebConfig::default()
+    });
+
+    assert_has_line(&exporter.scrape().await, "lnd_up 1");
+
+    assert_eq!(exporter.get("/metrics").await.status(), 404);
+
+    let body = hyper::body::to_bytes(exporter.get("/").await.into_body())
+        .await
+        .expect("page body");
+    let page = String::from_utf8(body.to_vec()).expect("utf-8 page");
+    assert!(page.contains("<a href=\"/prometheus\">"));
+    assert!(!page.contains("/metrics"));
+}
+
+#[tokio::test]
+async fn metrics_require_the_bearer_token() {
+    let mut exporter = Exporter::start(MockLnd::default()).await;
+    exporter.web = Arc::new(WebConfig {
+        auth: Some(TokenAuth::new("s3cret", &MetricsConfig::new("lnd"))),
+        ..WebConfig::default()
+    });
+    let get_with_token = |token: &str| {
+        hyper::Request::get("/metrics")
+            .header(hyper::header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(hyper::Body::empty())
+            .expect("valid request")
+    };
+
+    let res = exporter.get("/metrics").await;
+    assert_eq!(res.status(), 401);
+    assert_eq!(
+        res.headers()[hyper::header::WWW_AUTHENTICATE],
+        "Bearer realm=\"lnd-exporter\""
+    );
+    assert_eq!(
+        exporter.get("/probe?target=localhost:1").await.status(),
+        401
+    );
+    assert_eq!(
+        exporter.request(get_with_token("s3cre")).await.status(),
+        401
+    );
+
+    // Orchestrator probes need no token
+    assert_eq!(exporter.get("/health").await.status(), 200);
+    assert_eq!(exporter.get("/ready").await.status(), 200);
+
+    let res = exporter.request(get_with_token("s3cret")).await;
+    assert_eq!(res.status(), 200);
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("metrics body");
+    let metrics = String::from_utf8(body.to_vec()).expect("utf-8 metrics");
+    assert_has_line(&metrics, "lnd_exporter_auth_rejected_requests_total 3");
+}
+
+#[tokio::test]
+async fn scrapers_wait_for_wallet_unlock() {
+    let lnd = MockLnd::default();
+    lnd.state().wallet_state = lnrpc::WalletState::Locked;
+    let exporter = Exporter::start_with_options(
+        lnd,
+        CollectorOptions {
+            wait_unlock: Some(Duration::from_millis(10)),
+            ..CollectorOptions::default()
+        },
+        MetricsConfig::new("lnd"),
+    )
+    .await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let metrics = exporter.scrape().await;
+    assert_has_line(&metrics, "lnd_wallet_state{state=\"locked\"} 1");
+    assert_has_line(&metrics, "lnd_up 0");
+    let get_info_calls = |lnd: &MockLnd| {
+        let calls = &lnd.state().calls;
+        calls.iter().filter(|m| **m == "get_info").count()
+    };
+    // Only the startup node labels resolution reached the locked wallet
+    assert_eq!(get_info_calls(&exporter.lnd), 1);
+
+    exporter.lnd.state().wallet_state = lnrpc::WalletState::ServerActive;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let metrics = exporter.scrape().await;
+    assert_has_line(&metrics, "lnd_wallet_state{state=\"server_active\"} 1");
+    assert_has_line(&metrics, "lnd_up 1");
+    assert_eq!(get_info_calls(&exporter.lnd), 2);
+}
+
+#[tokio::test]
+async fn recorded_responses_are_replayed() {
+    let dir = std::env::temp_dir().join(format!("lnd-exporter-recording-{}", std::process::id()));
+    let options = || CollectorOptions {
+        wait_unlock: None,
+        ..CollectorOptions::default()
+    };
+    // Everything but the exporter's own metrics comes from the responses
+    let scraped_lines = |metrics: &str| {
+        metrics
+            .lines()
+            .filter(|line| line.starts_with("lnd_"))
+            .filter(|line| {
+                !line.starts_with("lnd_exporter_") && !line.starts_with("lnd_grpc_request_duration")
+            })
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+
+    let lnd = MockLnd::default();
+    lnd.state().info = lnrpc::GetInfoResponse {
+        num_peers: 3,
+        ..lnrpc::GetInfoResponse::default()
+    };
+    lnd.state().channels = vec![channel(1, 1_000, 2_000)];
+    lnd.state().payments = vec![payment(lnrpc::payment::PaymentStatus::Succeeded, 7)];
+    let transport = connect(&lnd)
+        .await
+        .with_recorder(Arc::new(lnrpc::Recorder::new(&dir).expect("recorder")));
+    let exporter =
+        Exporter::start_with_transport(lnd, transport, options(), MetricsConfig::new("lnd")).await;
+    let recorded = exporter.scrape().await;
+    assert_has_line(&recorded, "lnd_num_peers_total 3");
+
+    // No lnd to dial on replay
+    let replayer = Arc::new(lnrpc::Replayer::load(&dir).expect("replayer"));
+    let exporter = Exporter::start_with_transport(
+        MockLnd::default(),
+        lnrpc::Transport::replay(replayer),
+        options(),
+        MetricsConfig::new("lnd"),
+    )
+    .await;
+    let replayed = exporter.scrape().await;
+    std::fs::remove_dir_all(&dir).expect("removed recording");
+
+    assert_eq!(scraped_lines(&replayed), scraped_lines(&recorded));
+    assert_has_line(&replayed, "lnd_up 1");
+}
+
+#[tokio::test]
+async fn nodes_are_scraped_and_labeled_independently() {
+    use clap::Parser;
+
+    let lnd = MockLnd::default();
+    lnd.state().info = lnrpc::GetInfoResponse {
+        alias: "alpha".to_string(),
+        num_peers: 3,
+        ..lnrpc::GetInfoResponse::default()
+    };
+    let (addr, tls_cert) = lnd.clone().serve().await;
+    let tls_cert_path =
+        std::env::temp_dir().join(format!("lnd-exporter-tls-{}.cert", std::process::id()));
+    std::fs::write(&tls_cert_path, tls_cert).expect("written tls cert");
+
+    // The second node is down, named as it has no alias to go by
+    let opts = crate::Opts::parse_from([
+        "lnd-exporter",
+        "--lnd-endpoint",
+        &format!("https://localhost:{}", addr.port()),
+        "--lnd-endpoint",
+        "beta=https://localhost:1",
+        "--tls-cert-path",
+        tls_cert_path.to_str().expect("utf-8 path"),
+        "--scraper-retries",
+        "0",
+        "--no-wait-unlock",
+    ]);
+    let nodes = crate::lnd_nodes(&opts);
+    let metrics_config = MetricsConfig::new("lnd");
+    let mut collectors = vec![];
+    for node in nodes.iter() {
+        let options = crate::collector_options(
+            &opts,
+            crate::collector::ScraperSet::default(),
+            Arc::new(FamilyFilter::default()),
+        );
+        let (collector, _) = crate::node_collector(&opts, node, &options, &metrics_config)
+            .await
+            .expect("node collector");
+        collectors.push(Arc::new(collector));
+    }
+    std::fs::remove_file(&tls_cert_path).expect("removed tls cert");
+
+    let registry = prometheus::Registry::new();
+    registry
+        .register(Box::new(crate::collector::MultiNodeCollector::new(
+            collectors.clone(),
+        )))
+        .expect("registered collector");
+    let exporter = Exporter {
+        lnd,
+        gather_cache: Arc::new(GatherCache::new(registry, Duration::ZERO, &metrics_config)),
+        rate_limiter: Arc::new(RateLimiter::new(1, Duration::ZERO, &metrics_config)),
+        prober: Arc::new(Prober::new(
+            HashMap::new(),
+            CollectorOptions::default(),
+            metrics_config,
+            1,
+            1,
+        )),
+        family_filter: Arc::new(FamilyFilter::default()),
+        collectors: Arc::new(collectors),
+        web: Arc::new(WebConfig::default()),
+    };
+
+    let metrics = exporter.scrape().await;
+    assert_has_line(&metrics, "lnd_up{node=\"alpha\",node_alias=\"alpha\"} 1");
+    assert_has_line(
+        &metrics,
+        "lnd_num_peers_total{node=\"alpha\",node_alias=\"alpha\"} 3",
+    );
+    assert_has_line(&metrics, "lnd_up{node=\"beta\"} 0");
+}
+
+#[tokio::test]
+async fn metrics_are_served_as_json_when_accepted() {
+    let exporter = Exporter::start(MockLnd::default()).await;
+
+    let req = hyper::Request::get("/metrics")
+        .header(hyper::header::ACCEPT, "application/json")
+        .body(hyper::Body::empty())
+        .expect("valid request");
+    let res = exporter.request(req).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()[hyper::header::CONTENT_TYPE],
+        "application/json"
+    );
+
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("metrics body");
+    let json: serde_json::Value = serde_json::from_slice(&body).expect("json metrics");
+    let lnd_up = json
+        .as_array()
+        .expect("metric families")
+        .iter()
+        .find(|mf| mf["name"] == "lnd_up")
+        .expect("lnd_up family");
+    assert_eq!(lnd_up["type"], "gauge");
+    assert_eq!(lnd_up["metrics"][0]["value"], 1.0);
+}
+
+#[tokio::test]
+async fn metrics_are_served_as_json_document() {
+    let lnd = MockLnd::default();
+    lnd.state().info = lnrpc::GetInfoResponse {
+        num_peers: 3,
+        ..lnrpc::GetInfoResponse::default()
+    };
+    let exporter = Exporter::start(lnd).await;
+
+    // Served regardless of the Accept header
+    let req = hyper::Request::get("/metrics.json")
+        .header(hyper::header::ACCEPT, "text/plain")
+        .body(hyper::Body::empty())
+        .expect("valid request");
+    let res = exporter.request(req).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()[hyper::header::CONTENT_TYPE],
+        "application/json"
+    );
+
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("metrics body");
+    let json: serde_json::Value = serde_json::from_slice(&body).expect("json metrics");
+    assert_eq!(json["schema_version"], 1);
+    let num_peers = json["families"]
+        .as_array()
+        .expect("metric families")
+        .iter()
+        .find(|mf| mf["name"] == "lnd_num_peers_total")
+        .expect("lnd_num_peers_total family");
+    assert_eq!(num_peers["metrics"][0]["value"], 3.0);
+    assert_has_line(&exporter.scrape().await, "lnd_num_peers_total 3");
+}
+
+#[tokio::test]
+async fn metrics_are_served_as_openmetrics_when_accepted() {
+    let exporter = Exporter::start(MockLnd::default()).await;
+
+    let req = hyper::Request::get("/metrics")
+        .header(
+            hyper::header::ACCEPT,
+            "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5",
+        )
+        .body(hyper::Body::empty())
+        .expect("valid request");
+    let res = exporter.request(req).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()[hyper::header::CONTENT_TYPE],
+        "application/openmetrics-text; version=1.0.0; charset=utf-8"
+    );
+
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("metrics body");
+    let metrics = String::from_utf8(body.to_vec()).expect("utf-8 metrics");
+    assert!(metrics.ends_with("\n# EOF\n"), "{}", metrics);
+    assert_has_line(&metrics, "lnd_up 1.0");
+    assert_has_line(&metrics, "# TYPE lnd_scrape_timeout counter");
+    assert!(
+        metrics.contains("\nlnd_scrape_timeout_total{"),
+        "{}",
+        metrics
+    );
+
+    let res = exporter.get("/metrics").await;
+    assert_eq!(
+        res.headers()[hyper::header::CONTENT_TYPE],
+        "text/plain; version=0.0.4"
+    );
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("metrics body");
+    assert!(!String::from_utf8_lossy(&body).contains("# EOF"));
+}
+
+#[tokio::test]
+async fn metrics_are_gzipped_when_accepted() {
+    use std::io::Read;
+
+    let exporter = Exporter::start(MockLnd::default()).await;
+
+    let req = hyper::Request::get("/metrics")
+        .header(hyper::header::ACCEPT_ENCODING, "gzip, deflate")
+        .body(hyper::Body::empty())
+        .expect("valid request");
+    let res = exporter.request(req).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()[hyper::header::CONTENT_ENCODING], "gzip");
+
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("metrics body");
+    let mut metrics = String::new();
+    flate2::read::GzDecoder::new(&body[..])
+        .read_to_string(&mut metrics)
+        .expect("gzipped metrics");
+    assert_has_line(&metrics, "lnd_up 1");
+    assert!(body.len() < metrics.len());
+
+    let res = exporter.get("/metrics").await;
+    assert!(res.headers().get(hyper::header::CONTENT_ENCODING).is_none());
+}
+
+#[tokio::test]
+async fn targets_are_probed_with_module_credentials() {
+    let lnd = MockLnd::default();
+    lnd.state().info = lnrpc::GetInfoResponse {
+        num_peers: 3,
+        ..lnrpc::GetInfoResponse::default()
+    };
+    let (addr, tls_cert) = lnd.clone().serve().await;
+
+    let mut exporter = Exporter::start(MockLnd::default()).await;
+    exporter.prober = Arc::new(Prober::new(
+        vec![(
+            "lnd".to_string(),
+            ProbeModule {
+                macaroon: b"macaroon".to_vec(),
+                tls_cert: Some(tls_cert),
+            },
+        )]
+        .into_iter()
+        .collect(),
+        CollectorOptions::default(),
+        MetricsConfig::new("lnd"),
+        1,
+        1,
+    ));
+    let probe = |query: String| {
+        let exporter = &exporter;
+        async move {
+            let res = exporter.get(&format!("/probe?{}", query)).await;
+            assert_eq!(res.status(), 200);
+
+            let body = hyper::body::to_bytes(res.into_body())
+                .await
+                .expect("probe body");
+            String::from_utf8(body.to_vec()).expect("utf-8 metrics")
+        }
+    };
+
+    let target = format!("localhost:{}", addr.port());
+    let metrics = probe(format!("target={}&module=lnd", target)).await;
+    assert_has_line(&metrics, "probe_success 1");
+    assert_has_line(&metrics, "lnd_num_peers_total 3");
+
+    // The collector of the target is kept between probes
+    probe(format!("target={}&module=lnd", target)).await;
+    assert_eq!(
+        lnd.state()
+            .calls
+            .iter()
+            .filter(|m| **m == "get_info")
+            .count(),
+        2
+    );
+
+    let metrics = probe(format!("target={}&module=unknown", target)).await;
+    assert_has_line(&metrics, "probe_success 0");
+
+    let metrics = probe("target=localhost:1&module=lnd".to_string()).await;
+    assert_has_line(&metrics, "probe_success 0");
+    assert_has_line(&metrics, "lnd_up 0");
+
+    let res = exporter.get("/probe?module=lnd").await;
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn scrapes_are_restricted_to_collect_params() {
+    let lnd = MockLnd::default();
+    lnd.state().channels = vec![channel(1, 1_000, 2_000)];
+    lnd.state().payments = vec![payment(lnrpc::payment::PaymentStatus::Succeeded, 7)];
+    let exporter = Exporter::start(lnd).await;
+
+    let res = exporter.get("/metrics?collect[]=channels").await;
+    assert_eq!(res.status(), 200);
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("metrics body");
+    let metrics = String::from_utf8(body.to_vec()).expect("utf-8 metrics");
+    assert_has_line(&metrics, "lnd_up 1");
+    assert!(metrics.contains("lnd_channel_balance_total_sat{"));
+    assert!(!metrics.contains("lnd_outgoing_payments"));
+    assert!(!metrics.contains("lnd_num_peers_total"));
+    assert!(!exporter.lnd.state().calls.contains(&"list_payments"));
+
+    let metrics = exporter.scrape().await;
+    assert!(metrics.contains("lnd_outgoing_payments"));
+
+    let res = exporter
+        .get("/metrics?collect[]=channels&collect[]=bogus")
+        .await;
+    assert_eq!(res.status(), 400);
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("error body");
+    assert!(String::from_utf8_lossy(&body).contains("expected one of"));
+}
+
+#[tokio::test]
+async fn collect_params_select_the_mempool_scraper() {
+    let mut enabled_scrapers = ScraperSet::default();
+    enabled_scrapers.insert(ScraperName::Mempool);
+    let exporter = Exporter::start_with_options(
+        MockLnd::default(),
+        CollectorOptions {
+            enabled_scrapers,
+            // Nothing listens there, so the scrape fails but still runs
+            bitcoind: Some(BitcoindClient::new(
+                "http://127.0.0.1:1".to_string(),
+                None,
+                None,
+            )),
+            wait_unlock: None,
+            ..CollectorOptions::default()
+        },
+        MetricsConfig::new("lnd"),
+    )
+    .await;
+
+    let res = exporter.get("/metrics?collect[]=mempool").await;
+    assert_eq!(res.status(), 200);
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("metrics body");
+    let metrics = String::from_utf8(body.to_vec()).expect("utf-8 metrics");
+    assert!(metrics
+        .contains("lnd_exporter_scrape_errors_total{grpc_code=\"transport\",scraper=\"mempool\"}"));
+    assert!(!metrics.contains("lnd_channel_balance_total_sat{"));
+    assert!(!exporter.lnd.state().calls.contains(&"list_channels"));
+    assert!(exporter.collectors[0]
+        .debug_status()
+        .scrape_failures
+        .contains_key("mempool"));
+}
+
+#[tokio::test]
+async fn dropped_families_skip_their_scrapers() {
+    let lnd = MockLnd::default();
+    lnd.state().payments = vec![payment(lnrpc::payment::PaymentStatus::Succeeded, 7)];
+    let exporter = Exporter::start_with_options(
+        lnd,
+        CollectorOptions {
+            family_filter: Arc::new(
+                FamilyFilter::new(
+                    Some(
+                        "lnd_(outgoing_payments|payment_.*|total_fee_msat|circular_rebalance_.*)|lnd_grpc_.*",
+                    ),
+                    None,
+                )
+                .expect("valid regexes"),
+            ),
+            wait_unlock: None,
+            ..CollectorOptions::default()
+        },
+        MetricsConfig::new("lnd"),
+    )
+    .await;
+
+    let metrics = exporter.scrape().await;
+    assert_has_line(&metrics, "lnd_up 1");
+    assert!(!metrics.contains("lnd_outgoing_payments"));
+    assert!(!metrics.contains("lnd_grpc_request_duration_seconds"));
+    assert!(!exporter.lnd.state().calls.contains(&"list_payments"));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn process_metrics_are_gathered_with_lnd_metrics() {
+    let exporter = Exporter::start(MockLnd::default()).await;
+
+    let registry = prometheus::Registry::new();
+    registry
+        .register(Box::new(SharedCollector(exporter.collectors[0].clone())))
+        .expect("registered collector");
+    registry
+        .register(Box::new(
+            prometheus::process_collector::ProcessCollector::for_self(),
+        ))
+        .expect("registered process collector");
+
+    // Collecting blocks on the scrapers, like the gather cache does
+    let families = tokio::task::spawn_blocking(move || registry.gather())
+        .await
+        .expect("gather");
+    let names = families
+        .iter()
+        .map(|mf| mf.get_name().to_string())
+        .collect::<Vec<_>>();
+    for name in [
+        "lnd_up",
+        "process_cpu_seconds_total",
+        "process_resident_memory_bytes",
+        "process_open_fds",
+    ] {
+        assert!(
+            names.iter().any(|n| n == name),
+            "missing {} in {:?}",
+            name,
+            names
+        );
+    }
+}
+
+#[tokio::test]
+async fn debug_status_reports_scrape_failures() {
+    let lnd = MockLnd::default();
+    lnd.state().info = lnrpc::GetInfoResponse {
+        alias: "alice".to_string(),
+        ..lnrpc::GetInfoResponse::default()
+    };
+    lnd.state().denied.insert("list_channels");
+    let mut exporter = Exporter::start(lnd).await;
+
+    // Disabled by default
+    assert_eq!(exporter.get("/debug/status").await.status(), 404);
+
+    exporter.web = Arc::new(WebConfig {
+        endpoints: vec!["https://redacted@localhost:10009".to_string()],
+        enable_debug: true,
+        ..WebConfig::default()
+    });
+    exporter.scrape().await;
+
+    let res = exporter.get("/debug/status").await;
+    assert_eq!(res.status(), 200);
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("status body");
+    let json: serde_json::Value = serde_json::from_slice(&body).expect("json status");
+
+    let node = &json[0];
+    assert_eq!(node["endpoint"], "https://redacted@localhost:10009");
+    assert_eq!(node["lnd_up"], true);
+    assert_eq!(node["getinfo"]["alias"], "alice");
+    assert_eq!(
+        node["scrape_failures"]["channels"]["code"],
+        "permission_denied"
+    );
+    assert_eq!(
+        node["scrape_failures"]["channels"]["error"],
+        "permission denied"
+    );
+    assert!(node["scrape_failures"].get("getinfo").is_none());
+}
+
+#[tokio::test]
+async fn healthz_reports_lnd_and_the_scrapers() {
+    let lnd = MockLnd::default();
+    lnd.state().info = lnrpc::GetInfoResponse {
+        alias: "alice".to_string(),
+        version: "0.17.0-beta".to_string(),
+        ..lnrpc::GetInfoResponse::default()
+    };
+    let exporter = Exporter::start(lnd).await;
+    exporter.scrape().await;
+
+    let res = exporter.get("/healthz").await;
+    assert_eq!(res.status(), 200);
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("health body");
+    let json: serde_json::Value = serde_json::from_slice(&body).expect("json health");
+
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["uptime_seconds"].as_f64().is_some());
+    let node = &json["nodes"][0];
+    assert_eq!(node["lnd_reachable"], true);
+    assert!(node["last_check_latency_seconds"].as_f64().is_some());
+    assert_eq!(node["lnd_version"], "0.17.0-beta");
+    assert_eq!(node["lnd_alias"], "alice");
+    assert_eq!(node["wallet_unlocked"], true);
+    assert!(node["last_scrape_success_timestamp_seconds"]["channels"]
+        .as_u64()
+        .is_some());
+
+    exporter.lnd.state().denied.insert("get_info");
+    exporter.scrape().await;
+    let res = exporter.get("/healthz").await;
+    assert_eq!(res.status(), 503);
+    let body = hyper::body::to_bytes(res.into_body())
+        .await
+        .expect("health body");
+    let json: serde_json::Value = serde_json::from_slice(&body).expect("json health");
+    assert_eq!(json["nodes"][0]["lnd_reachable"], false);
+    assert_eq!(
+        json["nodes"][0]["last_check_error"],
+        "lnd did not answer GetInfo"
+    );
+
+    // Liveness is unaffected
+    assert_eq!(exporter.get("/health").await.status(), 200);
+}
+
+#[tokio::test]
+async fn cors_headers_are_only_sent_when_configured() {
+    let mut exporter = Exporter::start(MockLnd::default()).await;
+    let request = |method: hyper::Method, path: &str| {
+        hyper::Request::builder()
+            .method(method)
+            .uri(path)
+            .header(hyper::header::ORIGIN, "https://status.example.com")
+            .body(hyper::Body::empty())
+            .expect("valid request")
+    };
+
+    let res = exporter
+        .request(request(hyper::Method::GET, "/metrics.json"))
+        .await;
+    assert_eq!(res.status(), 200);
+    assert!(res
+        .headers()
+        .get(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    exporter.web = Arc::new(WebConfig {
+        auth: Some(TokenAuth::new("s3cret", &MetricsConfig::new("lnd"))),
+        cors: Some(crate::cors::Cors::new(vec!["https://status.example.com"
+            .parse()
+            .expect("valid origin")])),
+        ..WebConfig::default()
+    });
+
+    // Preflights are answered without the bearer token
+    let res = exporter
+        .request(request(hyper::Method::OPTIONS, "/metrics.json"))
+        .await;
+    assert_eq!(res.status(), 204);
+    assert_eq!(
+        res.headers()[hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://status.example.com"
+    );
+    assert_eq!(
+        res.headers()[hyper::header::ACCESS_CONTROL_ALLOW_METHODS],
+        "GET"
+    );
+
+    let res = exporter
+        .request(request(hyper::Method::GET, "/healthz"))
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()[hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://status.example.com"
+    );
+
+    // Other endpoints are not shared
+    let res = exporter
+        .request(request(hyper::Method::GET, "/probe"))
+        .await;
+    assert!(res
+        .headers()
+        .get(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[tokio::test]
+async fn once_writes_the_metrics_and_reports_failures() {
+    let path = std::env::temp_dir().join(format!("lnd-exporter-once-{}.prom", std::process::id()));
+    let exporter = Exporter::start(MockLnd::default()).await;
+
+    let succeeded = crate::once::run(
+        &exporter.gather_cache,
+        &exporter.family_filter,
+        &exporter.collectors,
+        Some(&path),
+    )
+    .await
+    .expect("written metrics");
+    assert!(succeeded);
+    assert_has_line(
+        &std::fs::read_to_string(&path).expect("metrics file"),
+        "lnd_up 1",
+    );
+    std::fs::remove_file(&path).expect("removed metrics file");
+
+    let lnd = MockLnd::default();
+    lnd.state().denied.insert("list_channels");
+    let exporter = Exporter::start(lnd).await;
+
+    let succeeded = crate::once::run(
+        &exporter.gather_cache,
+        &exporter.family_filter,
+        &exporter.collectors,
+        Some(&path),
+    )
+    .await
+    .expect("written metrics");
+    assert!(!succeeded);
+    std::fs::remove_file(&path).expect("removed metrics file");
+}
@@ -0,0 +1,310 @@
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use lnrpc::server::{
+    lightning_server::{Lightning, LightningServer},
+    state_server::{State, StateServer},
+};
+use tonic::{Request, Response, Status, Streaming};
+
+/// Canned lnd node state, editable while the mock server runs.
+pub struct MockState {
+    pub info: lnrpc::GetInfoResponse,
+    pub wallet_state: lnrpc::WalletState,
+    pub channels: Vec<lnrpc::Channel>,
+    /// Payments, indexed by their position plus one like lnd does.
+    pub payments: Vec<lnrpc::Payment>,
+    /// Methods answered with PermissionDenied, like lnd does for macaroons
+    /// lacking their permissions.
+    pub denied: HashSet<&'static str>,
+    /// Permissions, as `entity:action`, the macaroon is checked to lack.
+    pub missing_permissions: HashSet<&'static str>,
+    /// Methods called so far, in order.
+    pub calls: Vec<&'static str>,
+    /// Index offsets of the listpayments calls so far.
+    pub payment_index_offsets: Vec<u64>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        Self {
+            info: lnrpc::GetInfoResponse::default(),
+            wallet_state: lnrpc::WalletState::ServerActive,
+            channels: vec![],
+            payments: vec![],
+            denied: HashSet::new(),
+            missing_permissions: HashSet::new(),
+            calls: vec![],
+            payment_index_offsets: vec![],
+        }
+    }
+}
+
+/// Mock of the lnd Lightning service serving [`MockState`]. Methods the
+/// exporter does not use answer Unimplemented.
+#[derive(Clone, Default)]
+pub struct MockLnd {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockLnd {
+    pub fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock state lock")
+    }
+
+    /// Serves the mock over TLS on an ephemeral local port. Returns the address
+    /// and the PEM encoded self-signed certificate of the server.
+    pub async fn serve(self) -> (SocketAddr, Vec<u8>) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+            .expect("self-signed certificate");
+        let cert_pem = cert.serialize_pem().expect("certificate pem");
+        let identity =
+            tonic::transport::Identity::from_pem(&cert_pem, cert.serialize_private_key_pem());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bound listener");
+        let addr = listener.local_addr().expect("listener address");
+
+        let server = tonic::transport::Server::builder()
+            .tls_config(tonic::transport::ServerTlsConfig::new().identity(identity))
+            .expect("server tls config")
+            .add_service(LightningServer::new(self.clone()))
+            .add_service(StateServer::new(self))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener));
+        tokio::spawn(server);
+
+        (addr, cert_pem.into_bytes())
+    }
+
+    /// Records the call and answers it from the state, unless denied.
+    #[allow(clippy::result_large_err)]
+    fn respond<T>(
+        &self,
+        method: &'static str,
+        f: impl FnOnce(&mut MockState) -> T,
+    ) -> Result<Response<T>, Status> {
+        let mut state = self.state();
+        state.calls.push(method);
+
+        if state.denied.contains(method) {
+            return Err(Status::permission_denied("permission denied"));
+        }
+
+        Ok(Response::new(f(&mut state)))
+    }
+}
+
+#[tonic::async_trait]
+impl State for MockLnd {
+    type SubscribeStateStream = tokio_stream::Empty<Result<lnrpc::SubscribeStateResponse, Status>>;
+
+    async fn subscribe_state(
+        &self,
+        _: Request<lnrpc::SubscribeStateRequest>,
+    ) -> Result<Response<Self::SubscribeStateStream>, Status> {
+        Err(Status::unimplemented("subscribe_state"))
+    }
+
+    async fn get_state(
+        &self,
+        _: Request<lnrpc::GetStateRequest>,
+    ) -> Result<Response<lnrpc::GetStateResponse>, Status> {
+        self.respond("get_state", |state| lnrpc::GetStateResponse {
+            state: state.wallet_state as i32,
+        })
+    }
+}
+
+/// Implements [`Lightning`] for [`MockLnd`] with the given methods, answering
+/// every other method with Unimplemented.
+macro_rules! mock_lightning {
+    (
+        implemented {
+            $($method:tt)*
+        }
+
+        unimplemented {
+            $($unary:ident($unary_req:ty) -> $unary_res:ty;)*
+        }
+
+        streams {
+            $($stream_method:ident($stream_req:ty) -> $stream:ident<$stream_res:ty>;)*
+        }
+    ) => {
+        #[tonic::async_trait]
+        impl Lightning for MockLnd {
+            $($method)*
+
+            $(
+                async fn $unary(
+                    &self,
+                    _: Request<$unary_req>,
+                ) -> Result<Response<$unary_res>, Status> {
+                    Err(Status::unimplemented(stringify!($unary)))
+                }
+            )*
+
+            $(
+                type $stream = tokio_stream::Empty<Result<$stream_res, Status>>;
+
+                async fn $stream_method(
+                    &self,
+                    _: Request<$stream_req>,
+                ) -> Result<Response<Self::$stream>, Status> {
+                    Err(Status::unimplemented(stringify!($stream_method)))
+                }
+            )*
+        }
+    };
+}
+
+mock_lightning! {
+    implemented {
+        async fn get_info(
+            &self,
+            _: Request<lnrpc::GetInfoRequest>,
+        ) -> Result<Response<lnrpc::GetInfoResponse>, Status> {
+            self.respond("get_info", |state| state.info.clone())
+        }
+
+        async fn list_channels(
+            &self,
+            _: Request<lnrpc::ListChannelsRequest>,
+        ) -> Result<Response<lnrpc::ListChannelsResponse>, Status> {
+            self.respond("list_channels", |state| lnrpc::ListChannelsResponse {
+                channels: state.channels.clone(),
+            })
+        }
+
+        async fn list_payments(
+            &self,
+            req: Request<lnrpc::ListPaymentsRequest>,
+        ) -> Result<Response<lnrpc::ListPaymentsResponse>, Status> {
+            let index_offset = req.get_ref().index_offset;
+
+            self.respond("list_payments", |state| {
+                state.payment_index_offsets.push(index_offset);
+
+                let payments = state
+                    .payments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, payment)| lnrpc::Payment {
+                        payment_index: i as u64 + 1,
+                        ..payment.clone()
+                    })
+                    .filter(|payment| payment.payment_index > index_offset)
+                    .collect::<Vec<_>>();
+
+                lnrpc::ListPaymentsResponse {
+                    first_index_offset: payments.first().map_or(0, |p| p.payment_index),
+                    last_index_offset: payments.last().map_or(0, |p| p.payment_index),
+                    payments,
+                }
+            })
+        }
+
+        async fn list_invoices(
+            &self,
+            _: Request<lnrpc::ListInvoiceRequest>,
+        ) -> Result<Response<lnrpc::ListInvoiceResponse>, Status> {
+            self.respond("list_invoices", |_| lnrpc::ListInvoiceResponse::default())
+        }
+
+        async fn check_macaroon_permissions(
+            &self,
+            req: Request<lnrpc::CheckMacPermRequest>,
+        ) -> Result<Response<lnrpc::CheckMacPermResponse>, Status> {
+            let res = self.respond("check_macaroon_permissions", |state| {
+                req.get_ref().permissions.iter().all(|permission| {
+                    let permission = format!("{}:{}", permission.entity, permission.action);
+                    !state.missing_permissions.contains(permission.as_str())
+                })
+            })?;
+
+            // lnd refuses macaroons lacking permissions rather than answering
+            // valid=false
+            if !res.get_ref() {
+                return Err(Status::invalid_argument("permission denied"));
+            }
+
+            Ok(Response::new(lnrpc::CheckMacPermResponse { valid: true }))
+        }
+    }
+
+    unimplemented {
+        wallet_balance(lnrpc::WalletBalanceRequest) -> lnrpc::WalletBalanceResponse;
+        channel_balance(lnrpc::ChannelBalanceRequest) -> lnrpc::ChannelBalanceResponse;
+        get_transactions(lnrpc::GetTransactionsRequest) -> lnrpc::TransactionDetails;
+        estimate_fee(lnrpc::EstimateFeeRequest) -> lnrpc::EstimateFeeResponse;
+        send_coins(lnrpc::SendCoinsRequest) -> lnrpc::SendCoinsResponse;
+        list_unspent(lnrpc::ListUnspentRequest) -> lnrpc::ListUnspentResponse;
+        send_many(lnrpc::SendManyRequest) -> lnrpc::SendManyResponse;
+        new_address(lnrpc::NewAddressRequest) -> lnrpc::NewAddressResponse;
+        sign_message(lnrpc::SignMessageRequest) -> lnrpc::SignMessageResponse;
+        verify_message(lnrpc::VerifyMessageRequest) -> lnrpc::VerifyMessageResponse;
+        connect_peer(lnrpc::ConnectPeerRequest) -> lnrpc::ConnectPeerResponse;
+        disconnect_peer(lnrpc::DisconnectPeerRequest) -> lnrpc::DisconnectPeerResponse;
+        list_peers(lnrpc::ListPeersRequest) -> lnrpc::ListPeersResponse;
+        get_recovery_info(lnrpc::GetRecoveryInfoRequest) -> lnrpc::GetRecoveryInfoResponse;
+        pending_channels(lnrpc::PendingChannelsRequest) -> lnrpc::PendingChannelsResponse;
+        closed_channels(lnrpc::ClosedChannelsRequest) -> lnrpc::ClosedChannelsResponse;
+        open_channel_sync(lnrpc::OpenChannelRequest) -> lnrpc::ChannelPoint;
+        batch_open_channel(lnrpc::BatchOpenChannelRequest) -> lnrpc::BatchOpenChannelResponse;
+        funding_state_step(lnrpc::FundingTransitionMsg) -> lnrpc::FundingStateStepResp;
+        abandon_channel(lnrpc::AbandonChannelRequest) -> lnrpc::AbandonChannelResponse;
+        send_payment_sync(lnrpc::SendRequest) -> lnrpc::SendResponse;
+        send_to_route_sync(lnrpc::SendToRouteRequest) -> lnrpc::SendResponse;
+        add_invoice(lnrpc::Invoice) -> lnrpc::AddInvoiceResponse;
+        lookup_invoice(lnrpc::PaymentHash) -> lnrpc::Invoice;
+        decode_pay_req(lnrpc::PayReqString) -> lnrpc::PayReq;
+        delete_payment(lnrpc::DeletePaymentRequest) -> lnrpc::DeletePaymentResponse;
+        delete_all_payments(lnrpc::DeleteAllPaymentsRequest) -> lnrpc::DeleteAllPaymentsResponse;
+        describe_graph(lnrpc::ChannelGraphRequest) -> lnrpc::ChannelGraph;
+        get_node_metrics(lnrpc::NodeMetricsRequest) -> lnrpc::NodeMetricsResponse;
+        get_chan_info(lnrpc::ChanInfoRequest) -> lnrpc::ChannelEdge;
+        get_node_info(lnrpc::NodeInfoRequest) -> lnrpc::NodeInfo;
+        query_routes(lnrpc::QueryRoutesRequest) -> lnrpc::QueryRoutesResponse;
+        get_network_info(lnrpc::NetworkInfoRequest) -> lnrpc::NetworkInfo;
+        stop_daemon(lnrpc::StopRequest) -> lnrpc::StopResponse;
+        debug_level(lnrpc::DebugLevelRequest) -> lnrpc::DebugLevelResponse;
+        fee_report(lnrpc::FeeReportRequest) -> lnrpc::FeeReportResponse;
+        update_channel_policy(lnrpc::PolicyUpdateRequest) -> lnrpc::PolicyUpdateResponse;
+        forwarding_history(lnrpc::ForwardingHistoryRequest) -> lnrpc::ForwardingHistoryResponse;
+        export_channel_backup(lnrpc::ExportChannelBackupRequest) -> lnrpc::ChannelBackup;
+        export_all_channel_backups(lnrpc::ChanBackupExportRequest) -> lnrpc::ChanBackupSnapshot;
+        verify_chan_backup(lnrpc::ChanBackupSnapshot) -> lnrpc::VerifyChanBackupResponse;
+        restore_channel_backups(lnrpc::RestoreChanBackupRequest) -> lnrpc::RestoreBackupResponse;
+        bake_macaroon(lnrpc::BakeMacaroonRequest) -> lnrpc::BakeMacaroonResponse;
+        list_macaroon_i_ds(lnrpc::ListMacaroonIDsRequest) -> lnrpc::ListMacaroonIDsResponse;
+        delete_macaroon_id(lnrpc::DeleteMacaroonIdRequest) -> lnrpc::DeleteMacaroonIdResponse;
+        list_permissions(lnrpc::ListPermissionsRequest) -> lnrpc::ListPermissionsResponse;
+    }
+
+    streams {
+        subscribe_transactions(lnrpc::GetTransactionsRequest)
+            -> SubscribeTransactionsStream<lnrpc::Transaction>;
+        subscribe_peer_events(lnrpc::PeerEventSubscription)
+            -> SubscribePeerEventsStream<lnrpc::PeerEvent>;
+        subscribe_channel_events(lnrpc::ChannelEventSubscription)
+            -> SubscribeChannelEventsStream<lnrpc::ChannelEventUpdate>;
+        open_channel(lnrpc::OpenChannelRequest) -> OpenChannelStream<lnrpc::OpenStatusUpdate>;
+        channel_acceptor(Streaming<lnrpc::ChannelAcceptResponse>)
+            -> ChannelAcceptorStream<lnrpc::ChannelAcceptRequest>;
+        close_channel(lnrpc::CloseChannelRequest) -> CloseChannelStream<lnrpc::CloseStatusUpdate>;
+        send_payment(Streaming<lnrpc::SendRequest>) -> SendPaymentStream<lnrpc::SendResponse>;
+        send_to_route(Streaming<lnrpc::SendToRouteRequest>)
+            -> SendToRouteStream<lnrpc::SendResponse>;
+        subscribe_invoices(lnrpc::InvoiceSubscription) -> SubscribeInvoicesStream<lnrpc::Invoice>;
+        subscribe_channel_graph(lnrpc::GraphTopologySubscription)
+            -> SubscribeChannelGraphStream<lnrpc::GraphTopologyUpdate>;
+        subscribe_channel_backups(lnrpc::ChannelBackupSubscription)
+            -> SubscribeChannelBackupsStream<lnrpc::ChanBackupSnapshot>;
+        subscribe_custom_messages(lnrpc::SubscribeCustomMessagesRequest)
+            -> SubscribeCustomMessagesStream<lnrpc::CustomMessage>;
+    }
+}
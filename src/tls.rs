@@ -0,0 +1,225 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::rustls;
+
+/// Time clients have to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Answer to plain HTTP requests, like Go servers give.
+const PLAIN_HTTP_RESPONSE: &[u8] =
+    b"HTTP/1.0 400 Bad Request\r\n\r\nClient sent an HTTP request to an HTTPS server.\n";
+
+/// First byte of a TLS handshake record.
+const TLS_HANDSHAKE: u8 = 0x16;
+
+pub type TlsStream = tokio_rustls::server::TlsStream<TcpStream>;
+
+/// Builds the TLS config of the exporter listener from PEM files, requiring
+/// client certificates signed by `client_ca_path` if given.
+pub fn server_config(
+    cert_path: &str,
+    key_path: &str,
+    client_ca_path: Option<&str>,
+) -> Result<Arc<rustls::ServerConfig>, String> {
+    let certs = read_pem(cert_path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(der) => Some(rustls::Certificate(der)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if certs.is_empty() {
+        return Err(format!("no certificate found in {}", cert_path));
+    }
+
+    let key = read_pem(key_path)?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::RSAKey(der)
+            | rustls_pemfile::Item::PKCS8Key(der)
+            | rustls_pemfile::Item::ECKey(der) => Some(rustls::PrivateKey(der)),
+            _ => None,
+        })
+        .ok_or_else(|| format!("no private key found in {}", key_path))?;
+
+    let builder = rustls::ServerConfig::builder().with_safe_defaults();
+    let builder = match client_ca_path {
+        Some(client_ca_path) => {
+            let mut roots = rustls::RootCertStore::empty();
+            for item in read_pem(client_ca_path)? {
+                if let rustls_pemfile::Item::X509Certificate(der) = item {
+                    roots
+                        .add(&rustls::Certificate(der))
+                        .map_err(|e| format!("invalid CA in {}: {}", client_ca_path, e))?;
+                }
+            }
+            if roots.is_empty() {
+                return Err(format!("no certificate found in {}", client_ca_path));
+            }
+
+            builder.with_client_cert_verifier(
+                rustls::server::AllowAnyAuthenticatedClient::new(roots).boxed(),
+            )
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    builder
+        .with_single_cert(certs, key)
+        .map(Arc::new)
+        .map_err(|e| format!("invalid certificate or key: {}", e))
+}
+
+fn read_pem(path: &str) -> Result<Vec<rustls_pemfile::Item>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("failed to open {}: {}", path, e))?;
+
+    rustls_pemfile::read_all(&mut std::io::BufReader::new(file))
+        .map_err(|e| format!("failed to read {}: {}", path, e))
+}
+
+/// Connections of a TLS listener, handed to hyper once their handshake is
+/// done so that slow clients don't hold up the others.
+pub struct TlsIncoming {
+    connections: mpsc::Receiver<TlsStream>,
+    accept_task: tokio::task::JoinHandle<()>,
+}
+
+impl TlsIncoming {
+    pub fn new(listener: TcpListener, config: Arc<rustls::ServerConfig>) -> Self {
+        let acceptor = tokio_rustls::TlsAcceptor::from(config);
+        let (tx, connections) = mpsc::channel(32);
+
+        let accept_task = tokio::spawn(async move {
+            loop {
+                let (stream, remote_addr) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        log::error!("Failed to accept connection: {}", e);
+                        continue;
+                    }
+                };
+
+                let acceptor = acceptor.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake(acceptor, stream)).await
+                    {
+                        Ok(Ok(Some(stream))) => {
+                            let _ = tx.send(stream).await;
+                        }
+                        Ok(Ok(None)) => log::warn!("Rejected plain HTTP from {}", remote_addr),
+                        Ok(Err(e)) => {
+                            log::warn!("TLS handshake with {} failed: {}", remote_addr, e)
+                        }
+                        Err(_) => log::warn!("TLS handshake with {} timed out", remote_addr),
+                    }
+                });
+            }
+        });
+
+        Self {
+            connections,
+            accept_task,
+        }
+    }
+}
+
+impl Drop for TlsIncoming {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+impl hyper::server::accept::Accept for TlsIncoming {
+    type Conn = TlsStream;
+    type Error = std::io::Error;
+
+    fn poll_accept(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.connections.poll_recv(cx).map(|conn| conn.map(Ok))
+    }
+}
+
+/// Completes the TLS handshake, or answers plain HTTP clients with a 400
+/// rather than leaving them waiting for a response. Returns `None` then.
+async fn handshake(
+    acceptor: tokio_rustls::TlsAcceptor,
+    mut stream: TcpStream,
+) -> std::io::Result<Option<TlsStream>> {
+    let mut first_byte = [0; 1];
+    if stream.peek(&mut first_byte).await? == 1 && first_byte[0] != TLS_HANDSHAKE {
+        stream.write_all(PLAIN_HTTP_RESPONSE).await?;
+        stream.shutdown().await?;
+        return Ok(None);
+    }
+
+    acceptor.accept(stream).await.map(Some)
+}
+
+/// Address of the client of a TLS connection, for logging.
+pub fn remote_addr(stream: &TlsStream) -> String {
+    match stream.get_ref().0.peer_addr() {
+        Ok(addr) => addr.to_string(),
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn plain_http_is_rejected() {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+            .expect("self-signed certificate");
+        let dir = std::env::temp_dir().join(format!("lnd-exporter-tls-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("created temp dir");
+        let cert_path = dir.join("tls.cert");
+        let key_path = dir.join("tls.key");
+        std::fs::write(&cert_path, cert.serialize_pem().expect("certificate pem"))
+            .expect("wrote cert");
+        std::fs::write(&key_path, cert.serialize_private_key_pem()).expect("wrote key");
+
+        let config = server_config(
+            cert_path.to_str().expect("utf-8 path"),
+            key_path.to_str().expect("utf-8 path"),
+            None,
+        )
+        .expect("valid tls config");
+        std::fs::remove_dir_all(&dir).expect("removed temp dir");
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bound listener");
+        let addr = listener.local_addr().expect("listener address");
+        let _incoming = TlsIncoming::new(listener, config);
+
+        let mut stream = TcpStream::connect(addr).await.expect("connected");
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .expect("sent request");
+        let mut res = String::new();
+        stream.read_to_string(&mut res).await.expect("response");
+        assert!(res.starts_with("HTTP/1.0 400 Bad Request"), "{}", res);
+
+        assert!(
+            server_config("/nonexistent/tls.cert", "/nonexistent/tls.key", None)
+                .unwrap_err()
+                .contains("/nonexistent/tls.cert")
+        );
+    }
+}